@@ -0,0 +1,132 @@
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use pror::bitset::BitSetT;
+use pror::cdcl::{Default, State, VsidsAvlConfig, VsidsConfig};
+use pror::dimacs;
+use pror::fixed_bitset::BitSet;
+
+/// Most of a step is unit propagation and watch updates, so taking the first
+/// steps of a solve from scratch mostly measures those.
+fn propagation(c: &mut Criterion) {
+    for name in ["sudoku", "factor_1234321"] {
+        let formula = dimacs::read_example(name);
+        c.bench_function(&format!("propagate_{}", name), |b| {
+            b.iter_batched(
+                || Default::new_from_vec(formula.clone()),
+                |mut solver| {
+                    for _ in 0..50 {
+                        black_box(solver.step(None));
+                    }
+                    solver
+                },
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+fn solve(c: &mut Criterion) {
+    let formula = dimacs::read_example("sudoku");
+    c.bench_function("solve_sudoku", |b| {
+        b.iter_batched(
+            || Default::new_from_vec(formula.clone()),
+            |mut solver| solver.run(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+/// The VSIDS score order as a `BTreeSet` (the default) against an `AvlTree`.
+fn score_orders(c: &mut Criterion) {
+    for name in ["sudoku", "factor_1234321"] {
+        let formula = dimacs::read_example(name);
+        c.bench_function(&format!("solve_{}_btree_scores", name), |b| {
+            b.iter_batched(
+                || State::<VsidsConfig>::new_from_vec(formula.clone()),
+                |mut solver| solver.run(),
+                BatchSize::SmallInput,
+            )
+        });
+        c.bench_function(&format!("solve_{}_avl_scores", name), |b| {
+            b.iter_batched(
+                || State::<VsidsAvlConfig>::new_from_vec(formula.clone()),
+                |mut solver| solver.run(),
+                BatchSize::SmallInput,
+            )
+        });
+    }
+}
+
+fn bitset(step: usize, bits: usize) -> BitSet {
+    let mut bitset = BitSet::create();
+    bitset.grow(bits);
+    for bit in (0..bits).step_by(step) {
+        bitset.set(bit);
+    }
+    bitset
+}
+
+fn bitsets(c: &mut Criterion) {
+    let (a, b) = (bitset(3, 1 << 16), bitset(5, 1 << 16));
+    c.bench_function("bitset_iter_intersection", |bench| {
+        bench.iter(|| black_box(&a).iter_intersection(black_box(&b)).count())
+    });
+    c.bench_function("bitset_iter_union", |bench| {
+        bench.iter(|| black_box(&a).iter_union(black_box(&b)).count())
+    });
+    c.bench_function("bitset_iter_difference", |bench| {
+        bench.iter(|| black_box(&a).iter_difference(black_box(&b)).count())
+    });
+    c.bench_function("bitset_intersect_first_set_ge", |bench| {
+        bench.iter(|| {
+            let mut count = 0;
+            let mut next = a.intersect_first_set_ge(&b, 0);
+            while let Some(bit) = next {
+                count += 1;
+                next = a.intersect_first_set_ge(&b, bit + 1);
+            }
+            count
+        })
+    });
+}
+
+/// The word loops that the `simd` feature vectorises; compare runs with and
+/// without `--features simd` on a nightly compiler.
+fn kernels(c: &mut Criterion) {
+    let (a, b) = (bitset(3, 1 << 16), bitset(5, 1 << 16));
+    c.bench_function("bitset_union_with", |bench| {
+        bench.iter_batched_ref(
+            || a.clone(),
+            |a| a.union_with(black_box(&b)),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("bitset_intersect_with", |bench| {
+        bench.iter_batched_ref(
+            || a.clone(),
+            |a| a.intersect_with(black_box(&b)),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("bitset_difference_with", |bench| {
+        bench.iter_batched_ref(
+            || a.clone(),
+            |a| a.difference_with(black_box(&b)),
+            BatchSize::SmallInput,
+        )
+    });
+    c.bench_function("bitset_count", |bench| bench.iter(|| black_box(&a).count()));
+    c.bench_function("bitset_count_intersection", |bench| {
+        bench.iter(|| black_box(&a).count_intersection(black_box(&b)))
+    });
+    let formula = dimacs::read_example("factor_1234321");
+    c.bench_function("solve_factor_1234321", |b| {
+        b.iter_batched(
+            || Default::new_from_vec(formula.clone()),
+            |mut solver| solver.run(),
+            BatchSize::SmallInput,
+        )
+    });
+}
+
+criterion_group!(benches, propagation, solve, score_orders, bitsets, kernels);
+criterion_main!(benches);
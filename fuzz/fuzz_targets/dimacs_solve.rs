@@ -0,0 +1,70 @@
+//! Feeds arbitrary bytes through the DIMACS parser and solves the result
+//! with the debug configs, which check the solver's invariants after every
+//! step, comparing the answer against the reference DPLL.
+//!
+//! Run with `cargo fuzz run dimacs_solve` from the repository root.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pror::arbitrary::{check_against_dpll, Assumptions, Cnf, MAX_VARS};
+use pror::dimacs;
+use pror::solver::{build_solver, Heuristic, SolverOptions};
+
+/// More clauses than this only slow the reference solver down.
+const MAX_CLAUSES: usize = 64;
+
+/// Fold a parsed literal onto `1..=MAX_VARS`, keeping its sign, so the
+/// reference solver stays fast and huge variables don't allocate huge
+/// tables.
+fn fold(lit: isize) -> isize {
+    let var = (lit.unsigned_abs() - 1) % MAX_VARS + 1;
+    if lit > 0 {
+        var as isize
+    } else {
+        -(var as isize)
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    // the first byte picks the heuristic and how many of the following
+    // bytes are assumptions, the rest is DIMACS text
+    let Some((&control, rest)) = data.split_first() else {
+        return;
+    };
+    let num_assumptions = (control as usize >> 1) % 4;
+    let (assumptions, text) = rest.split_at(num_assumptions.min(rest.len()));
+    let assumptions = Assumptions {
+        literals: assumptions
+            .iter()
+            .map(|&byte| {
+                let var = (byte >> 1) as isize + 1;
+                fold(if byte & 1 == 0 { var } else { -var })
+            })
+            .collect(),
+    };
+    let cnf = Cnf {
+        clauses: dimacs::read_string(&String::from_utf8_lossy(text))
+            .into_iter()
+            .take(MAX_CLAUSES)
+            .map(|clause| clause.into_iter().map(fold).collect())
+            .collect(),
+    };
+
+    let options = SolverOptions {
+        heuristic: if control & 1 == 0 {
+            Heuristic::Vsids
+        } else {
+            Heuristic::Random
+        },
+        debug: true,
+        ..SolverOptions::default()
+    };
+    let mut solver = build_solver(options);
+    if let Err(e) = check_against_dpll(solver.as_mut(), &cnf, &assumptions) {
+        panic!(
+            "{}\nclauses: {:?}\nassumptions: {:?}",
+            e, cnf.clauses, assumptions.literals
+        );
+    }
+});
@@ -0,0 +1,111 @@
+use std::fs;
+
+/// A combinational and-inverter graph in the AIGER format. Literals are
+/// `2 * var + negated`, with `0` and `1` the constants false and true.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Aiger {
+    pub max_var: usize,
+    pub inputs: Vec<usize>,
+    pub outputs: Vec<usize>,
+    /// `(lhs, rhs0, rhs1)` for `lhs = rhs0 ∧ rhs1`
+    pub ands: Vec<(usize, usize, usize)>,
+}
+
+fn parse_line(line: Option<&str>, expected: usize) -> Result<Vec<usize>, String> {
+    let line = line.ok_or_else(|| "unexpected end of input".to_string())?;
+    let nums = line
+        .split_whitespace()
+        .map(|tok| tok.parse::<usize>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("bad line {:?}: {}", line, err))?;
+    if nums.len() != expected {
+        return Err(format!("expected {} numbers in line {:?}", expected, line));
+    }
+    Ok(nums)
+}
+
+/// Parse the ASCII (`aag`) variant. Latches are not supported.
+pub fn read_string(s: &str) -> Result<Aiger, String> {
+    let mut lines = s.lines();
+    let header = lines.next().ok_or_else(|| "empty input".to_string())?;
+    let header = match header.strip_prefix("aag ") {
+        Some(header) => parse_line(Some(header), 5)?,
+        None => return Err(format!("expected an aag header, got {:?}", header)),
+    };
+    let (max_var, num_inputs, num_latches, num_outputs, num_ands) =
+        (header[0], header[1], header[2], header[3], header[4]);
+    if num_latches != 0 {
+        return Err("latches are not supported".to_string());
+    }
+    let inputs = (0..num_inputs)
+        .map(|_| parse_line(lines.next(), 1).map(|nums| nums[0]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let outputs = (0..num_outputs)
+        .map(|_| parse_line(lines.next(), 1).map(|nums| nums[0]))
+        .collect::<Result<Vec<_>, _>>()?;
+    let ands = (0..num_ands)
+        .map(|_| parse_line(lines.next(), 3).map(|nums| (nums[0], nums[1], nums[2])))
+        .collect::<Result<Vec<_>, _>>()?;
+    let aiger = Aiger {
+        max_var,
+        inputs,
+        outputs,
+        ands,
+    };
+    let all_lits = aiger
+        .inputs
+        .iter()
+        .chain(&aiger.outputs)
+        .chain(aiger.ands.iter().flat_map(|(a, b, c)| [a, b, c]));
+    for &lit in all_lits {
+        if lit / 2 > max_var {
+            return Err(format!("literal {} is above the maximum variable", lit));
+        }
+    }
+    Ok(aiger)
+}
+
+pub fn read_file(path: &str) -> Result<Aiger, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    read_string(&contents)
+}
+
+impl Aiger {
+    /// The CNF variable standing for the constant false.
+    fn false_var(&self) -> isize {
+        self.max_var as isize + 1
+    }
+
+    /// AIGER variable `v` becomes CNF variable `v`.
+    pub fn cnf_literal(&self, lit: usize) -> isize {
+        let var = match lit / 2 {
+            0 => self.false_var(),
+            var => var as isize,
+        };
+        if lit.is_multiple_of(2) {
+            var
+        } else {
+            -var
+        }
+    }
+
+    /// Tseitin encoding of the and gates, asserting that every output is
+    /// true.
+    pub fn to_cnf(&self) -> Vec<Vec<isize>> {
+        let mut clauses = vec![vec![-self.false_var()]];
+        for &(lhs, rhs0, rhs1) in &self.ands {
+            let (a, b, c) = (
+                self.cnf_literal(lhs),
+                self.cnf_literal(rhs0),
+                self.cnf_literal(rhs1),
+            );
+            clauses.push(vec![-a, b]);
+            clauses.push(vec![-a, c]);
+            clauses.push(vec![a, -b, -c]);
+        }
+        for &output in &self.outputs {
+            clauses.push(vec![self.cnf_literal(output)]);
+        }
+        clauses
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::BTreeSet;
+
+/// Cheap syntactic measurements of a CNF, for choosing solver settings
+/// before solving it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InstanceFeatures {
+    pub num_vars: usize,
+    pub num_clauses: usize,
+    /// Clauses per variable.
+    pub ratio: f64,
+    /// The fraction of clauses with at most one positive literal.
+    pub horn_fraction: f64,
+    pub average_clause_len: f64,
+}
+
+impl InstanceFeatures {
+    /// Measure `clauses`.
+    pub fn of(clauses: &[Vec<isize>]) -> Self {
+        let vars = clauses
+            .iter()
+            .flatten()
+            .map(|lit| lit.unsigned_abs())
+            .collect::<BTreeSet<_>>();
+        let num_clauses = clauses.len();
+        if num_clauses == 0 {
+            return Self::default();
+        }
+        let num_horn = clauses
+            .iter()
+            .filter(|clause| clause.iter().filter(|&&lit| lit > 0).count() <= 1)
+            .count();
+        let num_literals = clauses.iter().map(|clause| clause.len()).sum::<usize>();
+        Self {
+            num_vars: vars.len(),
+            num_clauses,
+            ratio: num_clauses as f64 / vars.len().max(1) as f64,
+            horn_fraction: num_horn as f64 / num_clauses as f64,
+            average_clause_len: num_literals as f64 / num_clauses as f64,
+        }
+    }
+
+    /// Mostly Horn clauses, whose minimal model is the all-false one, so
+    /// deciding variables false first rarely needs undoing.
+    pub fn is_mostly_horn(&self) -> bool {
+        self.horn_fraction >= 0.9
+    }
+
+    /// Short clauses of about one length near the satisfiability threshold,
+    /// like uniform random 3-SAT: little structure for rapid restarts and
+    /// fast-moving activities to exploit.
+    pub fn looks_random(&self) -> bool {
+        (2.5..=3.5).contains(&self.average_clause_len) && self.ratio >= 3.5
+    }
+}
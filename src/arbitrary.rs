@@ -0,0 +1,150 @@
+//! Random formulas and assumption sets for fuzzing with quickcheck, and a
+//! check of any `Solver` against the reference DPLL in `dpll`.
+
+use crate::dpll;
+use crate::sat::{ints_of_literals, SatResult};
+use crate::solver::Solver;
+use quickcheck::{Arbitrary, Gen};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Variables are drawn from `1..=MAX_VARS`, small enough for `dpll::solve`.
+pub const MAX_VARS: usize = 10;
+
+#[derive(Clone, Debug)]
+pub struct Cnf {
+    pub clauses: Vec<Vec<isize>>,
+}
+
+/// Literals to assume, possibly contradicting each other.
+#[derive(Clone, Debug)]
+pub struct Assumptions {
+    pub literals: Vec<isize>,
+}
+
+fn arbitrary_literal(g: &mut Gen, num_vars: usize) -> isize {
+    let var = (usize::arbitrary(g) % num_vars + 1) as isize;
+    if bool::arbitrary(g) {
+        var
+    } else {
+        -var
+    }
+}
+
+impl Arbitrary for Cnf {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let num_vars = usize::arbitrary(g) % MAX_VARS + 1;
+        let num_clauses = usize::arbitrary(g) % (4 * num_vars + 1);
+        let clauses = (0..num_clauses)
+            .map(|_| {
+                let len = usize::arbitrary(g) % 4 + 1;
+                (0..len).map(|_| arbitrary_literal(g, num_vars)).collect()
+            })
+            .collect();
+        Cnf { clauses }
+    }
+
+    /// Drop a clause, or a literal from a clause of two or more.
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let clauses = self.clauses.clone();
+        let without_clause = (0..clauses.len()).map({
+            let clauses = clauses.clone();
+            move |i| {
+                let mut clauses = clauses.clone();
+                clauses.remove(i);
+                Cnf { clauses }
+            }
+        });
+        let without_literal = (0..clauses.len())
+            .flat_map({
+                let clauses = clauses.clone();
+                move |i| (0..clauses[i].len()).map(move |j| (i, j))
+            })
+            .filter({
+                let clauses = clauses.clone();
+                move |&(i, _)| clauses[i].len() > 1
+            })
+            .map(move |(i, j)| {
+                let mut clauses = clauses.clone();
+                clauses[i].remove(j);
+                Cnf { clauses }
+            });
+        Box::new(without_clause.chain(without_literal))
+    }
+}
+
+impl Arbitrary for Assumptions {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let len = usize::arbitrary(g) % 4;
+        Assumptions {
+            literals: (0..len).map(|_| arbitrary_literal(g, MAX_VARS)).collect(),
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        let literals = self.literals.clone();
+        Box::new((0..literals.len()).map(move |i| {
+            let mut literals = literals.clone();
+            literals.remove(i);
+            Assumptions { literals }
+        }))
+    }
+}
+
+fn satisfies(clauses: &[Vec<isize>], model: &BTreeMap<usize, bool>) -> bool {
+    clauses.iter().all(|clause| {
+        clause
+            .iter()
+            .any(|&lit| model.get(&lit.unsigned_abs()) == Some(&(lit > 0)))
+    })
+}
+
+/// Add `cnf` to `solver`, solve under `assumptions` and check the result
+/// against `dpll::solve`: a model has to satisfy the clauses and the
+/// assumptions, and an unsat core has to be a subset of the assumptions
+/// that is itself unsatisfiable with the clauses. Assumptions on variables
+/// that don't occur in `cnf` are dropped, as solvers reject those.
+pub fn check_against_dpll(
+    solver: &mut dyn Solver,
+    cnf: &Cnf,
+    assumptions: &Assumptions,
+) -> Result<(), String> {
+    for clause in &cnf.clauses {
+        solver.add_clause(clause.clone());
+    }
+    let vars = cnf
+        .clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.unsigned_abs())
+        .collect::<BTreeSet<_>>();
+    let assumptions = assumptions
+        .literals
+        .iter()
+        .copied()
+        .filter(|lit| vars.contains(&lit.unsigned_abs()))
+        .collect::<Vec<_>>();
+    let expected = dpll::solve(&cnf.clauses, &assumptions);
+    match (solver.run_with_assumptions(&assumptions), expected) {
+        (SatResult::Sat(model), Some(_)) => {
+            if !satisfies(&cnf.clauses, &model) {
+                return Err(format!("model {:?} falsifies a clause", model));
+            }
+            let units = assumptions.iter().map(|&lit| vec![lit]);
+            if !satisfies(&units.collect::<Vec<_>>(), &model) {
+                return Err(format!("model {:?} falsifies an assumption", model));
+            }
+            Ok(())
+        }
+        (SatResult::UnsatCore(core), None) => {
+            let core = ints_of_literals(&core);
+            if let Some(lit) = core.iter().find(|lit| !assumptions.contains(lit)) {
+                return Err(format!("core {:?} has {}, not an assumption", core, lit));
+            }
+            if dpll::solve(&cnf.clauses, &core).is_some() {
+                return Err(format!("core {:?} is satisfiable", core));
+            }
+            Ok(())
+        }
+        (result, expected) => Err(format!("got {:?}, dpll gave {:?}", result, expected)),
+    }
+}
@@ -0,0 +1,73 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+fn grow_clique(start: isize, adjacency: &BTreeMap<isize, BTreeSet<isize>>) -> Vec<isize> {
+    let mut clique = vec![start];
+    let mut candidates = adjacency[&start].clone();
+    // greedily take the candidate keeping the most other candidates alive
+    while let Some(next) = candidates
+        .iter()
+        .copied()
+        .max_by_key(|c| (adjacency[c].intersection(&candidates).count(), -c))
+    {
+        clique.push(next);
+        candidates = candidates
+            .intersection(&adjacency[&next])
+            .copied()
+            .collect();
+    }
+    clique
+}
+
+/// Find at-most-one constraints written pairwise as binary clauses `¬a ∨ ¬b`
+/// and pull them out of `clauses`. Greedily grown cliques of at least
+/// `min_size` literals are returned as groups (for `State::add_at_most_one`)
+/// alongside the clauses that were not absorbed into one.
+pub fn extract(clauses: Vec<Vec<isize>>, min_size: usize) -> (Vec<Vec<isize>>, Vec<Vec<isize>>) {
+    let mut adjacency: BTreeMap<isize, BTreeSet<isize>> = BTreeMap::new();
+    let mut clauses_of_edge: BTreeMap<(isize, isize), Vec<usize>> = BTreeMap::new();
+    for (idx, clause) in clauses.iter().enumerate() {
+        if let [x, y] = clause.as_slice() {
+            if x.abs() == y.abs() {
+                continue;
+            }
+            let (a, b) = (-x, -y);
+            adjacency.entry(a).or_default().insert(b);
+            adjacency.entry(b).or_default().insert(a);
+            clauses_of_edge
+                .entry((a.min(b), a.max(b)))
+                .or_default()
+                .push(idx);
+        }
+    }
+
+    let mut starts = adjacency.keys().copied().collect::<Vec<_>>();
+    starts.sort_by_key(|lit| std::cmp::Reverse(adjacency[lit].len()));
+
+    let mut removed = vec![false; clauses.len()];
+    let mut groups = vec![];
+    for start in starts {
+        while !adjacency[&start].is_empty() {
+            let clique = grow_clique(start, &adjacency);
+            if clique.len() < min_size.max(2) {
+                break;
+            }
+            for (i, &a) in clique.iter().enumerate() {
+                for &b in &clique[i + 1..] {
+                    adjacency.get_mut(&a).unwrap().remove(&b);
+                    adjacency.get_mut(&b).unwrap().remove(&a);
+                    for &idx in &clauses_of_edge[&(a.min(b), a.max(b))] {
+                        removed[idx] = true;
+                    }
+                }
+            }
+            groups.push(clique);
+        }
+    }
+
+    let remaining = clauses
+        .into_iter()
+        .zip(removed)
+        .filter_map(|(clause, removed)| if removed { None } else { Some(clause) })
+        .collect();
+    (remaining, groups)
+}
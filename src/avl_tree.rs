@@ -1,10 +1,13 @@
 use std::cmp::max;
 use std::fmt::Write;
+use std::ops::{Bound, RangeBounds};
 
 struct Node<K, V> {
     key: K,
     value: V,
     height: usize,
+    /// Number of nodes in the subtree rooted here.
+    size: usize,
     left: Option<usize>,
     right: Option<usize>,
 }
@@ -33,6 +36,7 @@ impl<K, V> NodePool<K, V> {
                 key,
                 value,
                 height: 1,
+                size: 1,
                 left: None,
                 right: None,
             };
@@ -43,6 +47,7 @@ impl<K, V> NodePool<K, V> {
                 key,
                 value,
                 height: 1,
+                size: 1,
                 left: None,
                 right: None,
             });
@@ -72,10 +77,15 @@ impl<K: Ord + Clone, V: Clone> AvlTree<K, V> {
         idx.map_or(0, |i| self.pool.nodes[i].height)
     }
 
-    fn update_height(&mut self, idx: usize) {
-        let lh = self.height_of(self.pool.nodes[idx].left);
-        let rh = self.height_of(self.pool.nodes[idx].right);
-        self.pool.nodes[idx].height = max(lh, rh) + 1;
+    fn size_of(&self, idx: Option<usize>) -> usize {
+        idx.map_or(0, |i| self.pool.nodes[i].size)
+    }
+
+    /// Recompute the height and size of `idx` from its children.
+    fn update_node(&mut self, idx: usize) {
+        let (left, right) = (self.pool.nodes[idx].left, self.pool.nodes[idx].right);
+        self.pool.nodes[idx].height = max(self.height_of(left), self.height_of(right)) + 1;
+        self.pool.nodes[idx].size = self.size_of(left) + self.size_of(right) + 1;
     }
 
     fn balance_factor(&self, idx: usize) -> isize {
@@ -89,8 +99,8 @@ impl<K: Ord + Clone, V: Clone> AvlTree<K, V> {
         let t2 = self.pool.nodes[x].right;
         self.pool.nodes[x].right = Some(y);
         self.pool.nodes[y].left = t2;
-        self.update_height(y);
-        self.update_height(x);
+        self.update_node(y);
+        self.update_node(x);
         x
     }
 
@@ -99,13 +109,13 @@ impl<K: Ord + Clone, V: Clone> AvlTree<K, V> {
         let t2 = self.pool.nodes[y].left;
         self.pool.nodes[y].left = Some(x);
         self.pool.nodes[x].right = t2;
-        self.update_height(x);
-        self.update_height(y);
+        self.update_node(x);
+        self.update_node(y);
         y
     }
 
     fn rebalance(&mut self, idx: usize) -> usize {
-        self.update_height(idx);
+        self.update_node(idx);
         let bf = self.balance_factor(idx);
         if bf > 1 {
             if self.balance_factor(self.pool.nodes[idx].left.unwrap()) < 0 {
@@ -146,7 +156,7 @@ impl<K: Ord + Clone, V: Clone> AvlTree<K, V> {
         self.root = Some(r);
     }
 
-    pub fn get(&self, key: &K) -> Option<&V> {
+    fn find(&self, key: &K) -> Option<usize> {
         let mut cur = self.root;
         while let Some(i) = cur {
             if key < &self.pool.nodes[i].key {
@@ -154,12 +164,85 @@ impl<K: Ord + Clone, V: Clone> AvlTree<K, V> {
             } else if key > &self.pool.nodes[i].key {
                 cur = self.pool.nodes[i].right;
             } else {
-                return Some(&self.pool.nodes[i].value);
+                return Some(i);
             }
         }
         None
     }
 
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.find(key).map(|i| &self.pool.nodes[i].value)
+    }
+
+    pub fn get_mut(&mut self, key: &K) -> Option<&mut V> {
+        self.find(key).map(|i| &mut self.pool.nodes[i].value)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.find(key).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.size_of(self.root)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    pub fn clear(&mut self) {
+        self.pool.nodes.clear();
+        self.pool.free_list.clear();
+        self.root = None;
+    }
+
+    /// The entry with the `n`th smallest key (0-based).
+    pub fn nth(&self, n: usize) -> Option<(&K, &V)> {
+        let mut n = n;
+        let mut cur = self.root;
+        while let Some(i) = cur {
+            let node = &self.pool.nodes[i];
+            let left = self.size_of(node.left);
+            match n.cmp(&left) {
+                std::cmp::Ordering::Less => cur = node.left,
+                std::cmp::Ordering::Equal => return Some((&node.key, &node.value)),
+                std::cmp::Ordering::Greater => {
+                    n -= left + 1;
+                    cur = node.right;
+                }
+            }
+        }
+        None
+    }
+
+    /// The entry with the `n`th largest key (0-based).
+    pub fn nth_back(&self, n: usize) -> Option<(&K, &V)> {
+        self.len().checked_sub(n + 1).and_then(|n| self.nth(n))
+    }
+
+    /// Number of keys less than `key`, whether or not `key` is present.
+    pub fn rank(&self, key: &K) -> usize {
+        let mut rank = 0;
+        let mut cur = self.root;
+        while let Some(i) = cur {
+            let node = &self.pool.nodes[i];
+            if key <= &node.key {
+                cur = node.left;
+            } else {
+                rank += self.size_of(node.left) + 1;
+                cur = node.right;
+            }
+        }
+        rank
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.find(&key) {
+            Some(idx) => Entry::Occupied(OccupiedEntry { tree: self, idx }),
+            None => Entry::Vacant(VacantEntry { tree: self, key }),
+        }
+    }
+
     fn min_value_node(&self, mut idx: usize) -> usize {
         while let Some(l) = self.pool.nodes[idx].left {
             idx = l;
@@ -215,8 +298,344 @@ impl<K: Ord + Clone, V: Clone> AvlTree<K, V> {
         }
     }
 
-    pub fn remove(&mut self, key: &K) {
+    /// Remove `key`, returning its value if it was present.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let value = self.get(key)?.clone();
         self.root = self.delete_node(self.root, KeyOrIdx::Key(key));
+        Some(value)
+    }
+
+    /// The entry with the smallest key.
+    pub fn first(&self) -> Option<(&K, &V)> {
+        let mut idx = self.root?;
+        while let Some(l) = self.pool.nodes[idx].left {
+            idx = l;
+        }
+        Some((&self.pool.nodes[idx].key, &self.pool.nodes[idx].value))
+    }
+
+    /// The entry with the largest key.
+    pub fn last(&self) -> Option<(&K, &V)> {
+        let mut idx = self.root?;
+        while let Some(r) = self.pool.nodes[idx].right {
+            idx = r;
+        }
+        Some((&self.pool.nodes[idx].key, &self.pool.nodes[idx].value))
+    }
+
+    /// Entries in key order; iterate `.rev()` for descending order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        self.range(..)
+    }
+
+    /// Entries whose keys fall in `range`, in key order.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> Iter<'_, K, V> {
+        let nodes = &self.pool.nodes;
+        let mut front = vec![];
+        let mut cur = self.root;
+        while let Some(i) = cur {
+            let above_start = match range.start_bound() {
+                Bound::Included(start) => &nodes[i].key >= start,
+                Bound::Excluded(start) => &nodes[i].key > start,
+                Bound::Unbounded => true,
+            };
+            if above_start {
+                front.push(i);
+                cur = nodes[i].left;
+            } else {
+                cur = nodes[i].right;
+            }
+        }
+        let mut back = vec![];
+        let mut cur = self.root;
+        while let Some(i) = cur {
+            let below_end = match range.end_bound() {
+                Bound::Included(end) => &nodes[i].key <= end,
+                Bound::Excluded(end) => &nodes[i].key < end,
+                Bound::Unbounded => true,
+            };
+            if below_end {
+                back.push(i);
+                cur = nodes[i].right;
+            } else {
+                cur = nodes[i].left;
+            }
+        }
+        Iter { nodes, front, back }
+    }
+
+    pub fn keys(&self) -> impl DoubleEndedIterator<Item = &K> + '_ {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &V> + '_ {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Build a perfectly balanced tree from entries in strictly increasing
+    /// key order, in O(n) with no rebalancing.
+    pub fn from_sorted_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = AvlTree::new();
+        for (key, value) in iter {
+            if let Some(prev) = tree.pool.nodes.last() {
+                assert!(
+                    prev.key < key,
+                    "from_sorted_iter: keys not strictly increasing"
+                );
+            }
+            tree.pool.alloc(key, value);
+        }
+        tree.root = tree.build_balanced(0, tree.pool.nodes.len());
+        tree
+    }
+
+    /// Link `pool.nodes[lo..hi]`, already in key order, into a balanced
+    /// subtree.
+    fn build_balanced(&mut self, lo: usize, hi: usize) -> Option<usize> {
+        if lo == hi {
+            return None;
+        }
+        let mid = lo + (hi - lo) / 2;
+        self.pool.nodes[mid].left = self.build_balanced(lo, mid);
+        self.pool.nodes[mid].right = self.build_balanced(mid + 1, hi);
+        self.update_node(mid);
+        Some(mid)
+    }
+
+    /// Empty the tree, returning its entries in key order.
+    fn take_sorted(&mut self) -> Vec<(K, V)> {
+        let mut order = Vec::with_capacity(self.len());
+        let mut stack = vec![];
+        let mut cur = self.root;
+        while cur.is_some() || !stack.is_empty() {
+            while let Some(i) = cur {
+                stack.push(i);
+                cur = self.pool.nodes[i].left;
+            }
+            let i = stack.pop().unwrap();
+            order.push(i);
+            cur = self.pool.nodes[i].right;
+        }
+        let mut nodes = std::mem::take(&mut self.pool.nodes)
+            .into_iter()
+            .map(Some)
+            .collect::<Vec<_>>();
+        self.clear();
+        order
+            .into_iter()
+            .map(|i| {
+                let node = nodes[i].take().unwrap();
+                (node.key, node.value)
+            })
+            .collect()
+    }
+
+    /// Move every entry of `other` into `self`, leaving `other` empty. On
+    /// equal keys the value from `other` wins. Linear in the size of both.
+    pub fn append(&mut self, other: &mut Self) {
+        if other.is_empty() {
+            return;
+        }
+        if self.is_empty() {
+            std::mem::swap(self, other);
+            return;
+        }
+        let mut ours = self.take_sorted().into_iter().peekable();
+        let mut theirs = other.take_sorted().into_iter().peekable();
+        let mut merged = Vec::with_capacity(ours.len() + theirs.len());
+        loop {
+            let next = match (ours.peek(), theirs.peek()) {
+                (None, None) => break,
+                (Some(_), None) => ours.next(),
+                (None, Some(_)) => theirs.next(),
+                (Some((a, _)), Some((b, _))) => match a.cmp(b) {
+                    std::cmp::Ordering::Less => ours.next(),
+                    std::cmp::Ordering::Greater => theirs.next(),
+                    std::cmp::Ordering::Equal => {
+                        ours.next();
+                        theirs.next()
+                    }
+                },
+            };
+            merged.extend(next);
+        }
+        *self = AvlTree::from_sorted_iter(merged);
+    }
+
+    /// Split off every entry with a key `>= key` into a new tree.
+    pub fn split_off(&mut self, key: &K) -> Self {
+        let at = self.rank(key);
+        if at == 0 {
+            return std::mem::take(self);
+        }
+        if at == self.len() {
+            return AvlTree::new();
+        }
+        let mut entries = self.take_sorted();
+        let upper = entries.split_off(at);
+        *self = AvlTree::from_sorted_iter(entries);
+        AvlTree::from_sorted_iter(upper)
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> FromIterator<(K, V)> for AvlTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut tree = AvlTree::new();
+        for (key, value) in iter {
+            tree.insert(key, value);
+        }
+        tree
+    }
+}
+
+impl<K: Ord + Clone, V: Clone> Default for AvlTree<K, V> {
+    fn default() -> Self {
+        AvlTree::new()
+    }
+}
+
+/// A view into a single entry of an `AvlTree`, from `AvlTree::entry`.
+pub enum Entry<'a, K: Ord + Clone, V: Clone> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K: Ord + Clone, V: Clone> {
+    tree: &'a mut AvlTree<K, V>,
+    idx: usize,
+}
+
+pub struct VacantEntry<'a, K: Ord + Clone, V: Clone> {
+    tree: &'a mut AvlTree<K, V>,
+    key: K,
+}
+
+impl<'a, K: Ord + Clone, V: Clone> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, f: F) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.tree.pool.nodes[self.idx].key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.tree.pool.nodes[self.idx].value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.tree.pool.nodes[self.idx].value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.tree.pool.nodes[self.idx].value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.tree.insert(self.key.clone(), value);
+        // rebalancing moves links, not nodes, but the new node's index is
+        // only known to the allocator
+        let idx = self.tree.find(&self.key).unwrap();
+        &mut self.tree.pool.nodes[idx].value
+    }
+}
+
+/// In-order iterator over (a range of) an `AvlTree`. Each end keeps the path
+/// to its next node; the ends have met once the front's next key is past
+/// the back's.
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    front: Vec<usize>,
+    back: Vec<usize>,
+}
+
+impl<'a, K: Ord, V> Iter<'a, K, V> {
+    fn exhausted(&self) -> bool {
+        match (self.front.last(), self.back.last()) {
+            (Some(&f), Some(&b)) => self.nodes[f].key > self.nodes[b].key,
+            _ => true,
+        }
+    }
+}
+
+impl<'a, K: Ord, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted() {
+            return None;
+        }
+        let i = self.front.pop()?;
+        let mut cur = self.nodes[i].right;
+        while let Some(j) = cur {
+            self.front.push(j);
+            cur = self.nodes[j].left;
+        }
+        Some((&self.nodes[i].key, &self.nodes[i].value))
+    }
+}
+
+impl<'a, K: Ord, V> DoubleEndedIterator for Iter<'a, K, V> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.exhausted() {
+            return None;
+        }
+        let i = self.back.pop()?;
+        let mut cur = self.nodes[i].left;
+        while let Some(j) = cur {
+            self.back.push(j);
+            cur = self.nodes[j].right;
+        }
+        Some((&self.nodes[i].key, &self.nodes[i].value))
+    }
+}
+
+impl<'a, K: Ord + Clone, V: Clone> IntoIterator for &'a AvlTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
     }
 }
 
@@ -409,4 +828,157 @@ mod tests {
         let (balanced, _) = check_balance(tree.root, &tree.pool);
         assert!(balanced, "Tree is unbalanced after operations");
     }
+
+    #[test]
+    fn test_iter_and_range() {
+        let mut tree = AvlTree::new();
+        for k in (0..40).rev().step_by(3) {
+            tree.insert(k, k * 10);
+        }
+        tree.remove(&18);
+        let keys = tree.keys().copied().collect::<Vec<_>>();
+        assert_eq!(keys, inorder_keys(&tree));
+        assert_eq!(
+            tree.values().rev().take(2).collect::<Vec<_>>(),
+            vec![&390, &360]
+        );
+        let s = format!(
+            "{:?} {:?} {:?} {:?}",
+            tree.range(10..21).collect::<Vec<_>>(),
+            tree.range(..=3).rev().collect::<Vec<_>>(),
+            tree.range(38..).map(|(k, _)| *k).collect::<Vec<_>>(),
+            tree.range(16..17).next(),
+        );
+        let expect = expect!["[(12, 120), (15, 150)] [(3, 30), (0, 0)] [39] None"];
+        expect.assert_eq(&s);
+
+        // the two ends meet in the middle
+        let mut iter = tree.range(5..=15);
+        let s = format!(
+            "{:?} {:?} {:?} {:?} {:?}",
+            iter.next(),
+            iter.next_back(),
+            iter.next(),
+            iter.next_back(),
+            iter.next()
+        );
+        let expect = expect!["Some((6, 60)) Some((15, 150)) Some((9, 90)) Some((12, 120)) None"];
+        expect.assert_eq(&s);
+        assert_eq!(AvlTree::<i32, i32>::new().iter().next(), None);
+    }
+
+    #[test]
+    fn test_entry_and_len() {
+        let mut tree = AvlTree::new();
+        assert!(tree.is_empty());
+        for word in ["b", "a", "c", "a", "b", "a"] {
+            *tree.entry(word).or_insert(0) += 1;
+        }
+        assert_eq!(tree.len(), 3);
+        assert_eq!(
+            tree.iter().collect::<Vec<_>>(),
+            vec![(&"a", &3), (&"b", &2), (&"c", &1)]
+        );
+        tree.entry("c")
+            .and_modify(|n| *n *= 10)
+            .or_insert_with(|| unreachable!());
+        tree.entry("d").and_modify(|_| unreachable!()).or_default();
+        assert_eq!(tree.get(&"c"), Some(&10));
+        assert!(tree.contains_key(&"d"));
+        assert!(!tree.contains_key(&"e"));
+        if let Entry::Occupied(mut entry) = tree.entry("a") {
+            assert_eq!(entry.insert(7), 3);
+        }
+        assert_eq!(tree.get(&"a"), Some(&7));
+
+        assert_eq!(tree.remove(&"b"), Some(2));
+        assert_eq!(tree.remove(&"missing"), None);
+        assert_eq!(tree.len(), 3);
+        tree.insert("e", 1);
+        assert_eq!(tree.len(), 4);
+        tree.clear();
+        assert!(tree.is_empty());
+        assert_eq!(tree.len(), 0);
+        assert_eq!(tree.get(&"a"), None);
+    }
+
+    #[test]
+    fn test_order_statistics() {
+        let mut tree = AvlTree::new();
+        for k in (0..200).rev() {
+            tree.insert(k * 2, k);
+        }
+        for k in (0..200).step_by(3) {
+            tree.remove(&(k * 2));
+        }
+        let keys = inorder_keys(&tree);
+        assert_eq!(tree.len(), keys.len());
+        for (n, key) in keys.iter().enumerate() {
+            assert_eq!(tree.nth(n).map(|(k, _)| k), Some(key));
+            assert_eq!(tree.rank(key), n);
+            // a missing key ranks like the next one up
+            assert_eq!(tree.rank(&(key - 1)), n);
+        }
+        assert_eq!(tree.nth(keys.len()), None);
+        assert_eq!(tree.first().map(|(k, _)| k), keys.first());
+        assert_eq!(tree.last().map(|(k, _)| k), keys.last());
+        assert_eq!(tree.nth_back(0).map(|(k, _)| *k), keys.last().copied());
+        assert_eq!(tree.nth_back(keys.len()), None);
+        assert_eq!(tree.rank(&1000), keys.len());
+    }
+
+    #[test]
+    fn test_bulk_build_append_split() {
+        let tree = AvlTree::from_sorted_iter((0..7).map(|k| (k, k * 10)));
+        let expect = expect![[r#"
+                0: 0
+              1: 10
+                2: 20
+            3: 30
+                4: 40
+              5: 50
+                6: 60
+        "#]];
+        expect.assert_eq(&pretty_print_to_string(&tree));
+
+        for n in [0, 1, 2, 100, 1000] {
+            let tree = AvlTree::from_sorted_iter((0..n).map(|k| (k, ())));
+            assert!(check_balance(tree.root, &tree.pool).0);
+            assert_eq!(tree.len(), n);
+            assert_eq!(tree.nth(n / 2).map(|(k, _)| *k), (n > 0).then_some(n / 2));
+        }
+
+        let mut evens = AvlTree::from_sorted_iter((0..100).step_by(2).map(|k| (k, "a")));
+        let mut thirds = (0..100)
+            .step_by(3)
+            .map(|k| (k, "b"))
+            .collect::<AvlTree<_, _>>();
+        evens.append(&mut thirds);
+        assert!(thirds.is_empty());
+        assert!(check_balance(evens.root, &evens.pool).0);
+        let mut expected = (0..100)
+            .filter(|k| k % 2 == 0 || k % 3 == 0)
+            .map(|k| (k, if k % 3 == 0 { "b" } else { "a" }))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            evens.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            expected
+        );
+
+        let upper = evens.split_off(&51);
+        let at = expected.iter().position(|(k, _)| *k >= 51).unwrap();
+        let expected_upper = expected.split_off(at);
+        assert_eq!(
+            evens.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            expected
+        );
+        assert_eq!(
+            upper.iter().map(|(k, v)| (*k, *v)).collect::<Vec<_>>(),
+            expected_upper
+        );
+        assert!(check_balance(upper.root, &upper.pool).0);
+        assert_eq!(evens.split_off(&1000).len(), 0);
+        assert_eq!(evens.split_off(&0).len(), expected.len());
+        assert!(evens.is_empty());
+    }
 }
@@ -28,6 +28,10 @@ pub trait BitSetT {
     fn first_set_ge(&self, bit: usize) -> Option<usize>;
     /// Find the first unset bit ≥ `bit`.
     fn first_unset_ge(&self, bit: usize) -> Option<usize>;
+    /// Find the last set bit, or `None`.
+    fn last_set(&self) -> Option<usize>;
+    /// Find the last set bit ≤ `bit`.
+    fn first_set_le(&self, bit: usize) -> Option<usize>;
 
     /// In-place union: `self |= other`.
     fn union_with(&mut self, other: &Self);
@@ -36,6 +40,16 @@ pub trait BitSetT {
     fn intersect_with(&mut self, other: &Self);
     /// In-place difference: `self &= !other`.
     fn difference_with(&mut self, other: &Self);
+    /// Flip every bit below `capacity()`.
+    fn complement(&mut self);
+
+    /// Keep only the set bits for which `pred` holds.
+    fn retain(&mut self, mut pred: impl FnMut(usize) -> bool) {
+        let dropped = self.iter().filter(|&bit| !pred(bit)).collect::<Vec<_>>();
+        for bit in dropped {
+            self.clear(bit);
+        }
+    }
 
     fn pop_first_set(&mut self) -> Option<usize> {
         let res = self.first_set()?;
@@ -48,6 +62,25 @@ pub trait BitSetT {
 
     fn count(&self) -> usize;
 
+    /// Hint that `count` will be called often, so implementations that can
+    /// keep a running count should start doing so.
+    fn cache_count(&mut self) {}
+
+    /// `|self ∩ other|`, without building the intersection.
+    fn count_intersection(&self, other: &Self) -> usize {
+        self.iter_intersection(other).count()
+    }
+
+    /// `|self ∪ other|`, without building the union.
+    fn count_union(&self, other: &Self) -> usize {
+        self.count() + other.count() - self.count_intersection(other)
+    }
+
+    /// `|self \ other|`, without building the difference.
+    fn count_difference(&self, other: &Self) -> usize {
+        self.count() - self.count_intersection(other)
+    }
+
     fn iter(&self) -> impl Iterator<Item = usize> + '_ {
         let mut after = 0;
         iter::from_fn(move || {
@@ -61,6 +94,26 @@ pub trait BitSetT {
         })
     }
 
+    /// Set bits in `range`, in order.
+    fn iter_range(&self, range: std::ops::Range<usize>) -> impl Iterator<Item = usize> + '_ {
+        let mut after = range.start;
+        iter::from_fn(move || {
+            let res = self.first_set_ge(after).filter(|&bit| bit < range.end)?;
+            after = res + 1;
+            Some(res)
+        })
+    }
+
+    /// Set bits from the highest down.
+    fn iter_rev(&self) -> impl Iterator<Item = usize> + '_ {
+        let mut next = self.last_set();
+        iter::from_fn(move || {
+            let res = next?;
+            next = res.checked_sub(1).and_then(|bit| self.first_set_le(bit));
+            Some(res)
+        })
+    }
+
     fn intersect_first_set_ge(&self, other: &Self, ge: usize) -> Option<usize> {
         match (self.first_set_ge(ge), other.first_set_ge(ge)) {
             (Some(a), Some(b)) if a == b => Some(a),
@@ -139,7 +192,14 @@ pub trait BitSetT {
     }
 }
 
+impl<B: BitSetT> crate::pool::Reset for B {
+    fn reset(&mut self) {
+        self.clear_all()
+    }
+}
+
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct BTreeBitSet {
     set: std::collections::BTreeSet<usize>,
 }
@@ -220,6 +280,14 @@ impl BitSetT for BTreeBitSet {
         Some(expected)
     }
 
+    fn last_set(&self) -> Option<usize> {
+        self.set.iter().next_back().copied()
+    }
+
+    fn first_set_le(&self, bit: usize) -> Option<usize> {
+        self.set.range(..=bit).next_back().copied()
+    }
+
     fn union_with(&mut self, other: &Self) {
         for &x in &other.set {
             self.set.insert(x);
@@ -242,6 +310,12 @@ impl BitSetT for BTreeBitSet {
             .collect::<std::collections::BTreeSet<_>>();
     }
 
+    fn complement(&mut self) {
+        self.set = (0..self.capacity())
+            .filter(|bit| !self.set.contains(bit))
+            .collect::<std::collections::BTreeSet<_>>();
+    }
+
     fn intersect(&mut self, a: &Self, b: &Self) {
         self.set = a
             .set
@@ -257,6 +331,14 @@ impl BitSetT for BTreeBitSet {
     fn count(&self) -> usize {
         self.set.len()
     }
+
+    fn retain(&mut self, mut pred: impl FnMut(usize) -> bool) {
+        self.set.retain(|&bit| pred(bit))
+    }
+
+    fn count_intersection(&self, other: &Self) -> usize {
+        self.set.intersection(&other.set).count()
+    }
 }
 
 // Optional: expose iterator helpers similar to trait defaults
@@ -265,6 +347,17 @@ impl BTreeBitSet {
         self.set.iter().copied()
     }
 
+    pub fn iter_rev(&self) -> impl Iterator<Item = usize> + '_ {
+        self.set.iter().rev().copied()
+    }
+
+    pub fn iter_range(&self, range: std::ops::Range<usize>) -> impl Iterator<Item = usize> + '_ {
+        // `BTreeSet::range` panics on a decreasing range
+        self.set
+            .range(range.start..range.end.max(range.start))
+            .copied()
+    }
+
     pub fn iter_union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = usize> + 'a {
         let mut a_iter = self.set.iter().peekable();
         let mut b_iter = other.set.iter().peekable();
@@ -1,26 +1,308 @@
+use crate::avl_tree::AvlTree;
 use crate::bitset::{BTreeBitSet, BitSetT};
+use crate::components::connected_components;
+use crate::description::Description;
+use crate::dimacs::{self, IncCnfLine};
+use crate::drup::ProofStep;
 use crate::fixed_bitset;
+use crate::horn;
+use crate::two_sat;
 use crate::luby::Luby;
-use crate::pool::Pool;
+use crate::model_count::{self, ModelCount};
+use crate::occurrences::OccurrenceLists;
+use crate::reduce_schedule::ReduceSchedule;
+use crate::reduction::{ByActivity, ReductionCandidate, ReductionPolicy};
+use crate::restart::RestartSchedule;
+use crate::pool::{Pool, PoolStats};
+use crate::reconstruction::Reconstruction;
 use crate::sat::*;
+use crate::stats::{Reporter, Stats};
 use crate::tombstone::*;
+use crate::trace::{Trace, TraceEvent};
 use itertools::Itertools;
 use ordered_float::OrderedFloat;
 use quickcheck::Gen;
 use rand::prelude::*;
 use rand_pcg::Pcg64;
-use std::cell::RefCell;
-use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Mutex;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 pub trait ConfigT: Sized {
     type BitSet: BitSetT + Clone;
+    /// Unassigned literals ordered by VSIDS score.
+    type ScoreOrder: ScoreOrder;
 
     fn choose_literal(state: &mut State<Self>) -> Option<Literal>;
 
+    /// How `choose_literal` picks, for `State::describe`.
+    const HEURISTIC: &'static str;
     const DEBUG: bool;
     const CHECK_RESULTS: bool; // check the assignments actually match
+    /// Check `State::invariant_violations` after every step, panicking
+    /// with them if there are any.
+    const CHECK_INVARIANTS: bool = false;
 }
 
+/// An ordered set of `(score, literal)` pairs, for finding the unassigned
+/// literal with the highest VSIDS score.
+pub trait ScoreOrder: std::default::Default + Send {
+    fn insert(&mut self, entry: (OrderedFloat<f64>, Literal)) -> bool;
+    fn remove(&mut self, entry: &(OrderedFloat<f64>, Literal)) -> bool;
+    fn last(&self) -> Option<&(OrderedFloat<f64>, Literal)>;
+}
+
+impl ScoreOrder for BTreeSet<(OrderedFloat<f64>, Literal)> {
+    fn insert(&mut self, entry: (OrderedFloat<f64>, Literal)) -> bool {
+        BTreeSet::insert(self, entry)
+    }
+
+    fn remove(&mut self, entry: &(OrderedFloat<f64>, Literal)) -> bool {
+        BTreeSet::remove(self, entry)
+    }
+
+    fn last(&self) -> Option<&(OrderedFloat<f64>, Literal)> {
+        BTreeSet::last(self)
+    }
+}
+
+impl ScoreOrder for AvlTree<(OrderedFloat<f64>, Literal), ()> {
+    fn insert(&mut self, entry: (OrderedFloat<f64>, Literal)) -> bool {
+        let new = !self.contains_key(&entry);
+        AvlTree::insert(self, entry, ());
+        new
+    }
+
+    fn remove(&mut self, entry: &(OrderedFloat<f64>, Literal)) -> bool {
+        AvlTree::remove(self, entry).is_some()
+    }
+
+    fn last(&self) -> Option<&(OrderedFloat<f64>, Literal)> {
+        AvlTree::last(self).map(|(entry, _)| entry)
+    }
+}
+
+/// An external theory consulted whenever unit propagation reaches a fixpoint.
+pub trait Theory: Send {
+    /// Given the literals currently on the trail (oldest first), return
+    /// lemmas: clauses implied by the theory that are falsified (a conflict)
+    /// or unit (an implication) under the trail. Returning nothing means the
+    /// trail is consistent with the theory.
+    fn propagate(&mut self, trail: &[Literal]) -> Vec<Vec<isize>>;
+}
+
+/// Read-only view of the search handed to branching and event callbacks.
+pub struct BranchingView<'a, Config: ConfigT> {
+    state: &'a State<Config>,
+}
+
+impl<'a, Config: ConfigT> BranchingView<'a, Config> {
+    /// `None` if `var` is unassigned or unknown to the solver.
+    pub fn value(&self, var: usize) -> Option<bool> {
+        if !self.state.all_variables.contains(var) || self.state.unassigned_variables.contains(var) {
+            None
+        } else {
+            Some(self.state.assignments.contains(var))
+        }
+    }
+
+    pub fn unassigned_variables(&self) -> impl Iterator<Item = usize> + '_ {
+        self.state.unassigned_variables.iter()
+    }
+
+    pub fn decision_level(&self) -> usize {
+        self.state.decision_level
+    }
+
+    /// The VSIDS activity of `literal`.
+    pub fn score(&self, literal: Literal) -> f64 {
+        self.state.score_for_literal[literal.variable()][literal.value()]
+    }
+
+    pub fn trail(&self) -> impl Iterator<Item = Literal> + '_ {
+        self.state.trail.iter().map(|entry| entry.literal)
+    }
+
+    /// The trail with the decision level of each literal.
+    pub fn trail_with_levels(&self) -> impl Iterator<Item = (Literal, usize)> + '_ {
+        self.state
+            .trail
+            .iter()
+            .map(|entry| (entry.literal, entry.decision_level))
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.state.stats()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClauseOrigin {
+    Original,
+    Learned,
+}
+
+/// The answer of `State::solve_with_timeout`.
+#[derive(Debug)]
+pub struct SolveOutcome {
+    /// `SatResult::Unknown` if it timed out.
+    pub result: SatResult,
+    pub timed_out: bool,
+    pub elapsed: Duration,
+    /// The worker's stats when it stopped.
+    pub stats: Stats,
+}
+
+/// The answer of `State::solve_cubes`.
+#[cfg(feature = "rayon")]
+#[derive(Debug)]
+pub struct CubeOutcome {
+    /// A model if some cube is satisfiable, `SatResult::Unknown` if a cube
+    /// couldn't be decided, and otherwise an empty core: every cube is
+    /// unsatisfiable.
+    pub result: SatResult,
+    /// Summed over the solvers of all the cubes.
+    pub stats: Stats,
+}
+
+/// A snapshot of one clause in the database, see `State::clauses_iter`.
+#[derive(Clone, Debug)]
+pub struct ClauseInfo {
+    pub literals: Vec<Literal>,
+    pub activity: f64,
+    pub lbd: usize,
+    pub origin: ClauseOrigin,
+}
+
+/// Why an assigned variable has its value; implied assignments carry the
+/// literals of the clause that forced them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReasonView {
+    Decision,
+    Implied(Vec<Literal>),
+}
+
+/// Why a literal holds on the trail, from `State::explain`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Explanation {
+    pub literal: Literal,
+    /// The decisions it follows from, with their levels, in trail order.
+    /// Empty if it holds at level 0.
+    pub decisions: Vec<(Literal, usize)>,
+    /// The propagations from those decisions to `literal`, in trail order.
+    pub steps: Vec<ExplanationStep>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExplanationStep {
+    pub literal: Literal,
+    pub level: usize,
+    /// The clause that became unit and forced `literal`.
+    pub clause: Vec<Literal>,
+    /// Whether `clause` was learned rather than part of the input.
+    pub learned: bool,
+}
+
+impl std::fmt::Display for ExplanationStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let clause = self
+            .clause
+            .iter()
+            .map(|lit| lit.to_string())
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{} @ {} by ({}){}",
+            self.literal.to_string(),
+            self.level,
+            clause.join(" "),
+            if self.learned { ", learned" } else { "" }
+        )
+    }
+}
+
+/// How a conflict at level 0 refutes the formula: the propagations, all
+/// forced by unit clauses in the end, that falsify `conflict`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Refutation {
+    /// Empty if the formula has the empty clause.
+    pub conflict: Vec<Literal>,
+    pub steps: Vec<ExplanationStep>,
+}
+
+impl std::fmt::Display for Refutation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let conflict = self
+            .conflict
+            .iter()
+            .map(|lit| lit.to_string())
+            .collect::<Vec<_>>();
+        writeln!(f, "({}) is falsified by", conflict.join(" "))?;
+        for step in &self.steps {
+            writeln!(f, "  {}", step)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Explanation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let decisions = self
+            .decisions
+            .iter()
+            .map(|(lit, level)| format!("{} @ {}", lit.to_string(), level))
+            .collect::<Vec<_>>();
+        if decisions.is_empty() {
+            writeln!(f, "{} holds at level 0", self.literal.to_string())?;
+        } else {
+            writeln!(
+                f,
+                "{} follows from deciding {}",
+                self.literal.to_string(),
+                decisions.join(", ")
+            )?;
+        }
+        for step in &self.steps {
+            writeln!(f, "  {}", step)?;
+        }
+        Ok(())
+    }
+}
+
+pub type Brancher<Config> = Box<dyn FnMut(&BranchingView<Config>) -> Option<Literal> + Send>;
+
+/// What happens in a search, as passed to an `EventHook`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SearchEvent {
+    /// A decision, before it is propagated.
+    Decision(Literal),
+    /// A conflict, once its clause has been learned and the solver has
+    /// backjumped.
+    Conflict(LearnedClause),
+    Restart,
+    /// A model found by `State::models`, before it is yielded; the solver's
+    /// `Stats::models_found` counts them.
+    Model,
+    /// `State::models` has found every model.
+    Exhausted,
+}
+
+/// Called with every `SearchEvent`, e.g. to draw the search as it goes.
+pub type EventHook<Config> = Box<dyn FnMut(&SearchEvent, &BranchingView<Config>) + Send>;
+
+/// Receives learned clauses as DIMACS literals, e.g. to share them with other
+/// solvers in a portfolio or to log candidate lemmas.
+pub type ClauseExporter = Box<dyn FnMut(&[isize]) + Send>;
+
+/// Behind a mutex rather than a `RefCell` so that a `State` can be moved to
+/// another thread.
+type DebugWriter = Mutex<Box<dyn std::fmt::Write + Send>>;
+
+/// Trace output for debug configs. Goes to the state's debug writer when one
+/// is set, and otherwise through the `log` facade so the embedder's logger
+/// decides whether it is shown.
 #[macro_export]
 macro_rules! debug {
     ($writer:expr, $($arg:tt)+) => {
@@ -28,10 +310,10 @@ macro_rules! debug {
             match $writer {
                 Some(ref w) => {
                     use std::fmt::Write as _;
-                    let _ = writeln!(w.borrow_mut(), $($arg)+);
+                    let _ = writeln!(w.lock().unwrap(), $($arg)+);
                 }
                 None => {
-                    eprintln!($($arg)+);
+                    ::log::trace!($($arg)+);
                 }
             }
         }
@@ -39,7 +321,7 @@ macro_rules! debug {
 
     ($($arg:tt)+) => {
         if Config::DEBUG {
-            eprintln!($($arg)+);
+            ::log::debug!($($arg)+);
         }
     };
 }
@@ -84,7 +366,7 @@ impl<T> std::ops::IndexMut<bool> for TfPair<T> {
 }
 
 pub struct State<Config: ConfigT> {
-    luby: Luby,
+    restart_schedule: Box<dyn RestartSchedule>,
     conflicts: u64,
     cla_inc: f64,
     cla_decay_factor: f64,
@@ -92,12 +374,13 @@ pub struct State<Config: ConfigT> {
     vsids_inc: f64,
     vsids_decay_factor: f64,
     vsids_activity_rescale: f64,
-    literal_by_score: BTreeSet<(OrderedFloat<f64>, Literal)>,
-    simplify_clauses_every: usize,
+    literal_by_score: Config::ScoreOrder,
+    reduce_schedule: ReduceSchedule,
+    reduction_policy: Box<dyn ReductionPolicy>,
+    bump_reasons: bool,
     all_variables: Config::BitSet,
     assignments: Config::BitSet,
-    clauses_first_tombstone: Option<usize>,
-    clauses: Vec<TombStone<Clause<Config::BitSet>>>,
+    clauses: GenVec<Clause<Config::BitSet>>,
     clause_sorting_buckets: Vec<ClauseIdx>,
     ready_for_unit_prop: Config::BitSet,
     trail: Vec<TrailEntry>,
@@ -111,9 +394,51 @@ pub struct State<Config: ConfigT> {
     bitset_pool: Pool<Config::BitSet>,
     iterations: usize,
     rng: Pcg64,
-    debug_writer: Option<RefCell<Box<dyn std::fmt::Write>>>,
+    debug_writer: Option<DebugWriter>,
     instantly_unsat: bool,
     current_assumptions: Vec<Literal>,
+    refutation: Option<Refutation>,
+    at_most_ones: Vec<Vec<Literal>>,
+    at_most_ones_by_literal: BTreeMap<Literal, Vec<usize>>,
+    at_most_one_pairs: HashSet<(Literal, Literal)>,
+    theory: Option<Box<dyn Theory>>,
+    theory_lemmas: HashSet<Vec<isize>>,
+    /// Learned clauses by their (sorted) literals, to avoid learning the
+    /// same clause twice. Entries may be stale; check the generation.
+    learned_clause_set: HashMap<Vec<Literal>, (ClauseIdx, Generation)>,
+    problem_clauses: Arc<Vec<Vec<isize>>>,
+    brancher: Option<Brancher<Config>>,
+    event_hook: Option<EventHook<Config>>,
+    /// Called with every new learned clause whose LBD is at most the bound.
+    clause_exporter: Option<(usize, ClauseExporter)>,
+    stats: Stats,
+    reporter: Option<Reporter>,
+    phases: Vec<Option<bool>>,
+    /// The phase of variables without one in `phases`.
+    default_phase: Option<bool>,
+    recording: Option<Vec<TraceEvent>>,
+    replaying: VecDeque<TraceEvent>,
+    reconstruction: Reconstruction,
+    fast_paths: bool,
+    trail_saving: bool,
+    /// The trail undone by the last backjump, oldest first, with the
+    /// generations of the reason clauses, and where each variable is in it.
+    saved_trail: Vec<(Literal, Reason, Generation)>,
+    saved_trail_idx_by_var: HashMap<usize, usize>,
+    learned_memory_limit: Option<usize>,
+    not_renamable_horn: bool,
+    proof: Option<Vec<ProofStep>>,
+    /// Restarts between asymmetric tautology elimination passes.
+    ate_interval: Option<u64>,
+    occurrences: Option<OccurrenceLists>,
+    cancel: Option<Arc<AtomicBool>>,
+    assumption_order: AssumptionOrder,
+    /// By variable, the number of the last unsat core it was in.
+    last_failure: HashMap<usize, u64>,
+    num_cores: u64,
+    /// The model of the last `run` that found one, until the problem
+    /// changes.
+    cached_model: Option<BTreeMap<usize, bool>>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -146,18 +471,7 @@ impl<Config: ConfigT> State<Config> {
     }
 
     fn push_clause(&mut self, clause: Clause<Config::BitSet>) -> usize {
-        match self.clauses_first_tombstone {
-            None => {
-                self.clauses.push(TombStone::new(0, clause));
-                self.clauses.len() - 1
-            }
-            Some(idx) => {
-                let gen = self.clauses[idx].generation().clone();
-                self.clauses_first_tombstone = self.clauses[idx].tombstone_idx_exn();
-                self.clauses[idx] = TombStone::new(gen + 1, clause);
-                idx
-            }
-        }
+        self.clauses.insert(clause)
     }
 
     fn maybe_add_var(&mut self, var: usize) {
@@ -171,10 +485,8 @@ impl<Config: ConfigT> State<Config> {
         if var >= self.clauses_by_var.len() {
             let to_add = var - self.clauses_by_var.len() + 1;
             for _ in 0..to_add {
-                let mut first = self.bitset_pool.acquire(|| Config::BitSet::create());
-                let mut second = self.bitset_pool.acquire(|| Config::BitSet::create());
-                first.clear_all();
-                second.clear_all();
+                let first = self.bitset_pool.acquire_reset(Config::BitSet::create);
+                let second = self.bitset_pool.acquire_reset(Config::BitSet::create);
                 self.clauses_by_var.push(TfPair { first, second });
                 self.trail_entry_idx_by_var.push(None);
                 self.score_for_literal.push(TfPair {
@@ -199,19 +511,123 @@ impl<Config: ConfigT> State<Config> {
 
     }
 
-    pub fn add_clause(&mut self, clause_vec: Vec<isize>) {
-        let mut variables = self.bitset_pool.acquire(|| Config::BitSet::create());
-        let mut negatives = self.bitset_pool.acquire(|| Config::BitSet::create());
-        variables.clear_all();
-        negatives.clear_all();
+    /// Add a clause to the problem, also between steps of a search. If the
+    /// trail falsifies it, the search backjumps until it doesn't, leaving the
+    /// clause unit and queued for propagation if only one literal was
+    /// falsified at the latest level, and an empty clause or one falsified at
+    /// level 0 makes the problem unsatisfiable. Panics if a variable is above
+    /// `MAX_VARIABLE`; `try_add_clause` returns an error instead.
+    pub fn add_clause(&mut self, clause: impl IntoIterator<Item = isize>) {
+        if let Err(err) = self.try_add_clause(clause) {
+            panic!("{}", err);
+        }
+    }
+
+    /// Like `add_clause`, but leaves the solver as it was and fails if a
+    /// variable is too large for a `Literal`.
+    pub fn try_add_clause(
+        &mut self,
+        clause: impl IntoIterator<Item = isize>,
+    ) -> Result<(), VariableTooLarge> {
+        let clause_vec = clause
+            .into_iter()
+            .map(check_literal)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.cached_model = None;
+        Arc::make_mut(&mut self.problem_clauses).push(clause_vec.clone());
+        if is_tautology(&clause_vec) {
+            for lit in clause_vec {
+                self.maybe_add_var(lit.unsigned_abs());
+            }
+            return Ok(());
+        }
+        let (idx, literals) = self.push_clause_watched_by_trail(&clause_vec);
+        match literals[..] {
+            [] | [(_, Some(0)), ..] => self.instantly_unsat = true,
+            [(_, Some(level)), ..] => {
+                // undo the latest level, or back to the level below if the
+                // clause has just one literal at it
+                let backjump_level = match literals.get(1) {
+                    Some(&(_, Some(next))) if next < level => next,
+                    _ => level - 1,
+                };
+                self.remove_from_trail_helper(Some(backjump_level));
+                self.ready_for_unit_prop.set(idx);
+            }
+            [(lit, None)] | [(lit, None), (_, Some(_)), ..]
+                if self.unassigned_variables.contains(lit.variable()) =>
+            {
+                self.ready_for_unit_prop.set(idx);
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn add_clause_idx(&mut self, clause_vec: Vec<isize>) -> usize {
+        let idx = self.push_clause_vec(&clause_vec);
+        self.index_clause(idx, &clause_vec);
+        idx
+    }
+
+    /// Add many clauses at once. With an empty trail this extends the
+    /// problem clauses once for the whole batch and registers the variables
+    /// of every clause, tautologies included, then pushes all the clauses
+    /// before indexing and watching them in one pass. Otherwise it is just
+    /// `add_clause` on each clause in turn. Panics like `add_clause` on a
+    /// variable above `MAX_VARIABLE`, before changing anything.
+    pub fn add_clauses<Lits: IntoIterator<Item = isize>>(
+        &mut self,
+        clauses: impl IntoIterator<Item = Lits>,
+    ) {
+        let clauses = clauses
+            .into_iter()
+            .map(|clause| {
+                clause
+                    .into_iter()
+                    .map(|lit| check_literal(lit).unwrap_or_else(|err| panic!("{}", err)))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        // watches built in bulk ignore the trail
+        if !self.trail.is_empty() {
+            for clause in clauses {
+                self.add_clause(clause);
+            }
+            return;
+        }
+        self.cached_model = None;
+        Arc::make_mut(&mut self.problem_clauses).extend(clauses.iter().cloned());
+        for lit in clauses.iter().flatten() {
+            self.maybe_add_var(lit.unsigned_abs());
+        }
+        let clauses = clauses
+            .into_iter()
+            .filter(|clause_vec| !is_tautology(clause_vec))
+            .collect::<Vec<_>>();
+        if clauses.iter().any(|clause_vec| clause_vec.is_empty()) {
+            self.instantly_unsat = true;
+        }
+        let idxs = clauses
+            .iter()
+            .map(|clause_vec| self.push_clause_vec(clause_vec))
+            .collect::<Vec<_>>();
+        for (idx, clause_vec) in idxs.into_iter().zip(&clauses) {
+            self.index_clause(idx, clause_vec);
+        }
+    }
+
+    fn push_clause_vec(&mut self, clause_vec: &[isize]) -> usize {
+        let mut variables = self.bitset_pool.acquire_reset(Config::BitSet::create);
+        let mut negatives = self.bitset_pool.acquire_reset(Config::BitSet::create);
         let mut tautology = false;
-        for lit in &clause_vec {
+        for lit in clause_vec {
             if *lit == 0 {
                 panic!("Can't have 0 vars");
             }
             let var = lit.abs() as usize;
             let value = *lit >= 0;
-            if variables.contains(var) && negatives.contains(var) != value {
+            if variables.contains(var) && negatives.contains(var) == value {
                 tautology = true;
             }
             variables.set(var);
@@ -228,24 +644,87 @@ impl<Config: ConfigT> State<Config> {
             num_units: 0,
             score: 0.0,
             from_conflict: false,
+            lbd: 0,
         };
-        let idx = self.push_clause(clause);
+        self.push_clause(clause)
+    }
 
-        for lit in clause_vec {
-            let var = lit.abs() as usize;
+    fn index_clause(&mut self, idx: usize, clause_vec: &[isize]) {
+        self.index_clause_occurrences(idx, clause_vec);
+        self.update_watch_literals_for_new_clause(idx);
+    }
+
+    fn index_clause_occurrences(&mut self, idx: usize, clause_vec: &[isize]) {
+        for &lit in clause_vec {
+            let var = lit.unsigned_abs();
             let value = lit > 0;
             self.clauses_by_var[var][value].set(idx);
         }
+        if let Some(occurrences) = self.occurrences.as_mut() {
+            occurrences.add(idx, self.clauses[idx].value_exn().iter_literals());
+        }
+    }
 
-        Self::update_watch_literals_for_new_clause_helper(
-            &self.debug_writer,
-            &self.clauses[idx].value_exn(),
-            idx,
-            self.clauses[idx].generation().clone(),
-            &mut self.watched_clauses,
-            &mut self.ready_for_unit_prop,
-            &self.unassigned_variables,
-        );
+    /// Constrain at most one of `lits` to be true. The group is propagated
+    /// directly: the binary clause `¬a ∨ ¬b` for a pair is only added to the
+    /// database once `a` being true first has to force (or conflict with) `b`.
+    pub fn add_at_most_one(&mut self, lits: Vec<isize>) {
+        self.cached_model = None;
+        let group_idx = self.at_most_ones.len();
+        let mut group = Vec::with_capacity(lits.len());
+        for lit in lits {
+            if lit == 0 {
+                panic!("Can't have 0 vars");
+            }
+            let literal = Literal::from(lit);
+            self.maybe_add_var(literal.variable());
+            self.at_most_ones_by_literal
+                .entry(literal)
+                .or_default()
+                .push(group_idx);
+            group.push(literal);
+        }
+        self.at_most_ones.push(group);
+    }
+
+    fn propagate_at_most_ones(&mut self, literal: Literal) -> Option<ClauseIdx> {
+        let groups = match self.at_most_ones_by_literal.get(&literal) {
+            None => return None,
+            Some(groups) => groups.clone(),
+        };
+        for group_idx in groups {
+            for other_idx in 0..self.at_most_ones[group_idx].len() {
+                let other = self.at_most_ones[group_idx][other_idx];
+                let var = other.variable();
+                let assigned = !self.unassigned_variables.contains(var);
+                if other == literal || (assigned && self.assignments.contains(var) != other.value())
+                {
+                    continue;
+                }
+                // pairs that already have a clause are handled by its watches
+                if !self.at_most_one_pairs.insert((literal.min(other), literal.max(other))) {
+                    continue;
+                }
+                let idx = self.add_clause_idx(vec![
+                    literal.negate().into(),
+                    other.negate().into(),
+                ]);
+                if assigned {
+                    return Some(ClauseIdx(idx));
+                }
+            }
+        }
+        None
+    }
+
+    fn satisfies_at_most_ones(&self, assignments: &BTreeMap<usize, bool>) -> bool {
+        self.at_most_ones.iter().all(|group| {
+            group
+                .iter()
+                .filter(|lit| assignments.get(&lit.variable()) == Some(&lit.value()))
+                .count()
+                <= 1
+        })
     }
 
     fn delete_clause(&mut self, idx: usize) {
@@ -260,23 +739,17 @@ impl<Config: ConfigT> State<Config> {
                 }
             }
         }
-        let mut rep_variables = Config::BitSet::create();
-        let mut rep_negatives = Config::BitSet::create();
-        std::mem::swap(
-            &mut rep_variables,
-            &mut self.clauses[idx].value_mut().unwrap().variables,
-        );
-        std::mem::swap(
-            &mut rep_negatives,
-            &mut self.clauses[idx].value_mut().unwrap().negatives,
-        );
-        self.bitset_pool.release(rep_variables);
-        self.bitset_pool.release(rep_negatives);
-        self.clauses[idx] = TombStone::TombStone(
-            self.clauses[idx].generation().clone() + 1,
-            self.clauses_first_tombstone.clone(),
-        );
-        self.clauses_first_tombstone = Some(idx);
+        let clause = self.clauses.remove(idx).unwrap();
+        if let Some(occurrences) = self.occurrences.as_mut() {
+            occurrences.remove(idx, clause.iter_literals());
+        }
+        if let Some(proof) = self.proof.as_mut() {
+            proof.push(ProofStep::Delete(ints_of_literals(
+                &clause.iter_literals().collect::<Vec<_>>(),
+            )));
+        }
+        self.bitset_pool.release(clause.variables);
+        self.bitset_pool.release(clause.negatives);
     }
 
     fn assignments(&self) -> BTreeMap<usize, bool> {
@@ -338,15 +811,13 @@ impl<Config: ConfigT> State<Config> {
         match trail_entry.reason {
             Reason::Decision(_) => (),
             Reason::ClauseIdx(clause_idx) => {
-                self.clauses[clause_idx].value_mut().unwrap().num_units -= 1
+                self.clauses.get_mut_exn(clause_idx).num_units -= 1
             }
         };
     }
 
     fn acquire_bitset(&mut self) -> Config::BitSet {
-        let mut res = self.bitset_pool.acquire(|| Config::BitSet::create());
-        res.clear_all();
-        res
+        self.bitset_pool.acquire_reset(Config::BitSet::create)
     }
 
     fn free_bitset(&mut self, bitset: Config::BitSet) {
@@ -367,7 +838,7 @@ impl<Config: ConfigT> State<Config> {
     ) -> bool {
         let ClauseIdx(idx) = clause_idx;
         let expected = self.watched_clauses(literal).get(&clause_idx).unwrap();
-        if self.clauses[idx].generation() == expected {
+        if self.clauses.generation(idx) == *expected {
             return false;
         }
         self.watched_clauses_mut(literal).remove(&clause_idx);
@@ -473,7 +944,7 @@ impl<Config: ConfigT> State<Config> {
         match trail_entry.reason {
             Reason::Decision(_) => (),
             Reason::ClauseIdx(clause_idx) => {
-                self.clauses[clause_idx].value_mut().unwrap().num_units += 1;
+                self.clauses.get_mut_exn(clause_idx).num_units += 1;
             }
         };
         self.literal_by_score.remove(&(
@@ -488,6 +959,7 @@ impl<Config: ConfigT> State<Config> {
         self.unassigned_variables.clear(var);
         self.trail.push(trail_entry);
         self.update_watched_clauses(literal)
+            .or_else(|| self.propagate_at_most_ones(literal))
     }
 
     fn clause_string(&self, clause_idx: ClauseIdx) -> String {
@@ -506,6 +978,10 @@ impl<Config: ConfigT> State<Config> {
                 .collect::<Vec<_>>()
                 .join("; ")
         );
+        self.stats.propagations += 1;
+        if self.bump_reasons && self.clauses[clause_idx.0].value_exn().from_conflict {
+            self.add_clause_activity_and_maybe_rescale(clause_idx.0);
+        }
         let decision_level = self.decision_level;
         let trail_entry = TrailEntry {
             literal,
@@ -516,6 +992,13 @@ impl<Config: ConfigT> State<Config> {
     }
 
     fn unit_propagate(&mut self) -> UnitPropagationResult {
+        let start = Instant::now();
+        let res = self.unit_propagate_inner();
+        self.stats.profile.propagation.record(start);
+        res
+    }
+
+    fn unit_propagate_inner(&mut self) -> UnitPropagationResult {
         let mut num_props = 0;
         while let Some(clause_idx) = self.ready_for_unit_prop.pop_first_set() {
             match self.clauses[clause_idx]
@@ -524,7 +1007,9 @@ impl<Config: ConfigT> State<Config> {
             {
                 None => continue,
                 Some(literal) => {
-                    if let Some(clause_idx) = self.with_unit_clause(literal, ClauseIdx(clause_idx))
+                    if let Some(clause_idx) = self
+                        .with_unit_clause(literal, ClauseIdx(clause_idx))
+                        .or_else(|| self.replay_saved_trail(literal))
                     {
                         return UnitPropagationResult::Contradiction(clause_idx);
                     };
@@ -539,6 +1024,62 @@ impl<Config: ConfigT> State<Config> {
         }
     }
 
+    /// If `literal` was on the trail undone by the last backjump, assign the
+    /// literals it implied then again, as long as their reasons are still
+    /// unit. This finds them without waiting for their clauses to come up
+    /// in `ready_for_unit_prop`. Stops at the next decision, and at any
+    /// entry whose reason no longer applies, leaving the rest to ordinary
+    /// propagation.
+    fn replay_saved_trail(&mut self, literal: Literal) -> Option<ClauseIdx> {
+        let start = *self.saved_trail_idx_by_var.get(&literal.variable())?;
+        if self.saved_trail[start].0 != literal {
+            return None;
+        }
+        for idx in start + 1..self.saved_trail.len() {
+            let (saved, reason, generation) = self.saved_trail[idx];
+            let Reason::ClauseIdx(clause_idx) = reason else {
+                break;
+            };
+            let Some(clause) = self.clauses.get_checked(clause_idx, generation) else {
+                break;
+            };
+            if !self.unassigned_variables.contains(saved.variable()) {
+                if self.assignments.contains(saved.variable()) == saved.value() {
+                    continue;
+                }
+                break;
+            }
+            if self.is_satisfied(clause) || self.try_get_unit_literal(clause) != Some(saved) {
+                break;
+            }
+            self.stats.replayed_propagations += 1;
+            if let Some(conflict) = self.with_unit_clause(saved, ClauseIdx(clause_idx)) {
+                return Some(conflict);
+            }
+        }
+        None
+    }
+
+    fn save_trail_above(&mut self, decision_level: usize) {
+        self.saved_trail.clear();
+        self.saved_trail_idx_by_var.clear();
+        let first = self
+            .trail
+            .iter()
+            .position(|entry| entry.decision_level > decision_level)
+            .unwrap_or(self.trail.len());
+        for entry in &self.trail[first..] {
+            let generation = match entry.reason {
+                Reason::Decision(_) => 0,
+                Reason::ClauseIdx(clause_idx) => self.clauses.generation(clause_idx),
+            };
+            self.saved_trail_idx_by_var
+                .insert(entry.literal.variable(), self.saved_trail.len());
+            self.saved_trail
+                .push((entry.literal, entry.reason, generation));
+        }
+    }
+
     fn only_one_at_level(&self, clause: &Clause<Config::BitSet>) -> bool {
         clause
             .iter_literals()
@@ -574,16 +1115,16 @@ impl<Config: ConfigT> State<Config> {
     }
 
     fn rescale_clause_activities(&mut self) {
-        for clause in self.clauses.iter_mut().filter_map(|x| x.value_mut()) {
+        for (_, clause) in self.clauses.iter_mut() {
             clause.score /= self.cla_activity_rescale;
         }
         self.cla_inc /= self.cla_activity_rescale;
     }
 
     fn add_clause_activity(&mut self, clause_idx: usize) -> bool {
-        self.clauses[clause_idx].value_mut_exn().score += self.cla_inc;
+        self.clauses.get_mut_exn(clause_idx).score += self.cla_inc;
         // should rescale
-        self.clauses[clause_idx].value_mut_exn().score > self.cla_activity_rescale
+        self.clauses.get_mut_exn(clause_idx).score > self.cla_activity_rescale
     }
 
     fn add_clause_activity_and_maybe_rescale(&mut self, clause_idx: usize) {
@@ -619,11 +1160,15 @@ impl<Config: ConfigT> State<Config> {
     }
 
     fn add_vsids_activity(&mut self, literal: Literal) {
+        self.add_vsids_score(literal, self.vsids_inc);
+    }
+
+    fn add_vsids_score(&mut self, literal: Literal, amount: f64) {
         let score = &mut self.score_for_literal[literal.variable()][literal.value()];
         let rem = self
             .literal_by_score
             .remove(&(OrderedFloat(*score), literal));
-        *score += self.vsids_inc;
+        *score += amount;
         if rem {
             self.literal_by_score
                 .insert((OrderedFloat(*score), literal.clone()));
@@ -640,6 +1185,16 @@ impl<Config: ConfigT> State<Config> {
     fn learn_clause_from_failure(
         &mut self,
         failed_clause_idx: ClauseIdx,
+    ) -> Clause<Config::BitSet> {
+        let start = Instant::now();
+        let learned = self.learn_clause_from_failure_inner(failed_clause_idx);
+        self.stats.profile.analysis.record(start);
+        learned
+    }
+
+    fn learn_clause_from_failure_inner(
+        &mut self,
+        failed_clause_idx: ClauseIdx,
     ) -> Clause<Config::BitSet> {
         let mut learned = self.clauses[failed_clause_idx.0]
             .value_exn()
@@ -712,17 +1267,29 @@ impl<Config: ConfigT> State<Config> {
     }
 
     fn restart(&mut self) {
+        let start = Instant::now();
+        self.restart_inner();
+        self.stats.profile.restarts.record(start);
+        if let Some(interval) = self.ate_interval {
+            if self.stats.restarts.is_multiple_of(interval) {
+                self.eliminate_asymmetric_tautologies();
+            }
+        }
+    }
+
+    fn restart_inner(&mut self) {
         debug!(self.debug_writer, "Restarting");
         self.ready_for_unit_prop.clear_all();
+        self.saved_trail.clear();
+        self.saved_trail_idx_by_var.clear();
         while let Some(mut trail_entry) = self.trail.pop() {
             self.undo_entry(&mut trail_entry);
         }
-        for (clause_idx, clause) in self
-            .clauses
-            .iter()
-            .enumerate()
-            .filter_map(|(i, x)| x.value().map(|v| (i, v)))
-        {
+        self.queue_unit_clauses();
+    }
+
+    fn queue_unit_clauses(&mut self) {
+        for (clause_idx, clause) in self.clauses.iter() {
             if let Some(_) = self.try_get_unit_literal(clause) {
                 debug!(
                     self.debug_writer,
@@ -760,21 +1327,127 @@ impl<Config: ConfigT> State<Config> {
         };
     }
 
-    fn backtrack(&mut self, failed_clause_idx: ClauseIdx) {
-        let learned_clause = self.learn_clause_from_failure(failed_clause_idx);
+    fn backtrack(&mut self, failed_clause_idx: ClauseIdx) -> LearnedClause {
+        let mut learned_clause = self.learn_clause_from_failure(failed_clause_idx);
+        learned_clause.lbd = learned_clause
+            .iter_literals()
+            .filter_map(|lit| self.trail_entry_idx_by_var[lit.variable()])
+            .map(|idx| self.trail[idx].decision_level)
+            .unique()
+            .count();
         learned_clause
             .iter_literals()
             .for_each(|lit| self.add_vsids_activity(lit));
         let remove_greater_than = self.second_highest_decision_level(&learned_clause);
-        for lit in learned_clause.iter_literals() {
-            let len = self.clauses.len();
-            self.clauses_mut(lit).set(len);
-        }
         self.decay_vsids_activities();
+        if self.trail_saving {
+            self.save_trail_above(remove_greater_than);
+        }
         self.remove_from_trail_helper(Some(remove_greater_than));
-        let clause_idx = self.push_clause(learned_clause);
+        let literals = learned_clause.iter_literals().collect::<Vec<_>>();
+        let learned = LearnedClause {
+            literals: literals.clone(),
+            backjump_level: remove_greater_than,
+        };
+        let existing_idx = self
+            .learned_clause_idx(&literals)
+            .or_else(|| self.asserting_clause_subsuming(&learned_clause));
         self.ready_for_unit_prop.clear_all();
-        self.update_watch_literals_for_new_clause(clause_idx);
+        match existing_idx {
+            Some(existing_idx) => {
+                // Already learned (or subsumed by a clause that asserts the
+                // same literal) and still watched by its latest literals, so
+                // it only has to be propagated again.
+                self.stats.duplicate_learned_clauses += 1;
+                self.bitset_pool.release(learned_clause.variables);
+                self.bitset_pool.release(learned_clause.negatives);
+                self.ready_for_unit_prop.set(existing_idx);
+            }
+            None => {
+                // logged before the clauses it subsumes are deleted, as it
+                // may only follow from them
+                if let Some(proof) = self.proof.as_mut() {
+                    proof.push(ProofStep::Add(ints_of_literals(&literals)));
+                }
+                self.delete_learned_clauses_subsumed_by(&literals);
+                self.export_clause(&learned_clause);
+                self.stats.learned_clauses += 1;
+                self.reduce_schedule.learned();
+                let clause_idx = self.push_clause(learned_clause);
+                for &lit in &literals {
+                    self.clauses_mut(lit).set(clause_idx);
+                }
+                if let Some(occurrences) = self.occurrences.as_mut() {
+                    occurrences.add(clause_idx, literals.iter().copied());
+                }
+                let generation = self.clauses.generation(clause_idx);
+                self.learned_clause_set
+                    .insert(literals, (ClauseIdx(clause_idx), generation));
+                self.update_watch_literals_for_new_clause(clause_idx);
+            }
+        }
+        learned
+    }
+
+    /// A live clause whose literals are all in `learned` and which is unit
+    /// under the current trail. That makes it assert the same literal as
+    /// `learned` after the backjump, so it must contain that literal.
+    fn asserting_clause_subsuming(&mut self, learned: &Clause<Config::BitSet>) -> Option<usize> {
+        let asserted = self.try_get_unit_literal(learned)?;
+        let mut candidates = self.acquire_bitset();
+        candidates.union_with(self.clauses(asserted));
+        let contains = |lit: Literal| {
+            learned.variables.contains(lit.variable())
+                && learned.negatives.contains(lit.variable()) != lit.value()
+        };
+        let found = candidates.iter().find(|&idx| {
+            match self.clauses.get(idx) {
+                Some(clause) => {
+                    clause.iter_literals().all(contains) && self.try_get_unit_literal(clause).is_some()
+                }
+                None => false,
+            }
+        });
+        self.free_bitset(candidates);
+        found
+    }
+
+    /// Delete the learned clauses that contain every literal in `literals`,
+    /// unless they are the reason for an assignment.
+    fn delete_learned_clauses_subsumed_by(&mut self, literals: &[Literal]) {
+        let Some((&first, rest)) = literals.split_first() else {
+            return;
+        };
+        let mut candidates = self.acquire_bitset();
+        candidates.union_with(self.clauses(first));
+        for &lit in rest {
+            candidates.intersect_with(self.clauses(lit));
+        }
+        let subsumed = candidates
+            .iter()
+            .filter(|&idx| match self.clauses.get(idx) {
+                Some(clause) => {
+                    clause.from_conflict
+                        && clause.num_units == 0
+                        && literals.iter().all(|&lit| {
+                            clause.variables.contains(lit.variable())
+                                && clause.negatives.contains(lit.variable()) != lit.value()
+                        })
+                }
+                None => false,
+            })
+            .collect::<Vec<_>>();
+        self.free_bitset(candidates);
+        for idx in subsumed {
+            self.delete_clause(idx);
+            self.stats.subsumed_clauses += 1;
+        }
+    }
+
+    /// The index of a live learned clause with exactly these literals.
+    fn learned_clause_idx(&self, literals: &[Literal]) -> Option<usize> {
+        let &(ClauseIdx(idx), generation) = self.learned_clause_set.get(literals)?;
+        self.clauses.is_live(idx, generation).then_some(idx)
     }
 
     fn react(&mut self, action: Action) -> StepResult {
@@ -794,46 +1467,615 @@ impl<Config: ConfigT> State<Config> {
                     decision_level: self.decision_level,
                     reason: Reason::Decision(literal),
                 };
-                self.add_to_trail(trail_entry);
-                StepResult::Continue
+                match self
+                    .add_to_trail(trail_entry)
+                    .or_else(|| self.replay_saved_trail(literal))
+                {
+                    None => StepResult::Continue,
+                    Some(ClauseIdx(failed_idx)) => self.react(Action::Contradiction(failed_idx)),
+                }
             }
-            Action::Contradiction(failed_clause_idx) if self.decision_level == 0 => 
-            {
+            Action::Contradiction(failed_clause_idx) if self.decision_level == 0 => {
+                self.stats.conflicts += 1;
+                self.record_refutation(failed_clause_idx);
                 let learned_clause = self.learn_clause_from_failure(ClauseIdx(failed_clause_idx));
                 let core = self.extract_unsat_core_of_learned(Some(&learned_clause));
                 StepResult::Done(SatResult::UnsatCore(core))
             }
             Action::Contradiction(failed_idx) => {
                 self.conflicts += 1;
-                self.backtrack(ClauseIdx(failed_idx));
-                if self.conflicts >= self.luby.value() {
+                self.stats.conflicts += 1;
+                let learned = self.backtrack(ClauseIdx(failed_idx));
+                self.emit(SearchEvent::Conflict(learned.clone()));
+                self.report_stats();
+                if self.over_learned_memory_limit() {
+                    return StepResult::Done(SatResult::Unknown);
+                }
+                if self.conflicts >= self.restart_schedule.limit() {
                     self.conflicts = 0;
+                    self.restart_schedule.restarted();
+                    self.stats.restarts += 1;
+                    self.trace_event(TraceEvent::Restart);
                     self.restart();
+                    self.emit(SearchEvent::Restart);
                 }
-                StepResult::Continue
+                StepResult::Learned(learned)
             }
         }
     }
 
-    fn make_decision(&mut self, literal_override: Option<Literal>) -> StepResult {
-        match literal_override.or_else(|| Config::choose_literal(self)) {
-            None => {
-                let assignments = self.assignments();
-                let res = SatResult::Sat(assignments);
-                StepResult::Done(res)
-            }
-            Some(literal) => {
-                self.decision_level += 1;
-                self.react(Action::Continue(literal))
-            }
+    pub fn decision_level(&self) -> usize {
+        self.decision_level
+    }
+
+    /// The assigned literals with their decision levels, oldest first.
+    /// Empty after a `run` answered by a fast path (see `set_fast_paths`).
+    pub fn trail_literals(&self) -> Vec<(Literal, usize)> {
+        self.trail
+            .iter()
+            .map(|entry| (entry.literal, entry.decision_level))
+            .collect()
+    }
+
+    /// The clauses currently watching `literal`.
+    pub fn watched_clause_literals(&self, literal: Literal) -> Vec<Vec<Literal>> {
+        match self.watched_clauses.get(literal.variable()) {
+            None => vec![],
+            Some(watched) => watched[literal.value()]
+                .iter()
+                .filter_map(|(idx, &gen)| self.clauses.get_checked(idx.0, gen))
+                .map(|clause| clause.iter_literals().collect())
+                .collect(),
         }
     }
 
-    fn can_trim_clause(&self, clause: &Clause<Config::BitSet>) -> bool {
-        clause.num_units == 0
-            && clause
-                .iter_literals()
-                .filter_map(|x| self.trail_entry_idx_by_var[x.variable()])
+    /// Undo the most recent decision and everything implied after it.
+    pub fn undo_decision(&mut self) {
+        if self.decision_level == 0 {
+            return;
+        }
+        self.remove_from_trail_helper(Some(self.decision_level - 1));
+        self.ready_for_unit_prop.clear_all();
+        self.queue_unit_clauses();
+    }
+
+    /// Removed clauses and their witnesses, applied to every model before it
+    /// is returned.
+    pub fn reconstruction(&self) -> &Reconstruction {
+        &self.reconstruction
+    }
+
+    /// `None` if `var` is currently unassigned, as every variable is after
+    /// a fast path answer (see `set_fast_paths`).
+    pub fn reason(&self, var: usize) -> Option<ReasonView> {
+        let idx = (*self.trail_entry_idx_by_var.get(var)?)?;
+        Some(match self.trail[idx].reason {
+            Reason::Decision(_) => ReasonView::Decision,
+            Reason::ClauseIdx(clause_idx) => ReasonView::Implied(
+                self.clauses[clause_idx].value_exn().iter_literals().collect(),
+            ),
+        })
+    }
+
+    /// The decisions that `literal` follows from on the current trail and
+    /// the propagations in between, found by walking the reasons back from
+    /// it. `None` unless `literal` is currently true on the trail, so always
+    /// `None` after a fast path answer (see `set_fast_paths`).
+    pub fn explain(&self, literal: Literal) -> Option<Explanation> {
+        let idx = (*self.trail_entry_idx_by_var.get(literal.variable())?)?;
+        if self.trail[idx].literal != literal {
+            return None;
+        }
+        let (decisions, steps) = self.trace_reasons(BTreeSet::from([literal.variable()]), idx + 1);
+        Some(Explanation {
+            literal,
+            decisions,
+            steps,
+        })
+    }
+
+    /// Walk the first `len` trail entries backwards from the variables in
+    /// `seen`, collecting the decisions and propagations they depend on, in
+    /// trail order.
+    fn trace_reasons(
+        &self,
+        mut seen: BTreeSet<usize>,
+        len: usize,
+    ) -> (Vec<(Literal, usize)>, Vec<ExplanationStep>) {
+        let mut decisions = vec![];
+        let mut steps = vec![];
+        for entry in self.trail[..len].iter().rev() {
+            if !seen.contains(&entry.literal.variable()) {
+                continue;
+            }
+            match entry.reason {
+                Reason::Decision(_) => decisions.push((entry.literal, entry.decision_level)),
+                Reason::ClauseIdx(clause_idx) => {
+                    let clause = self.clauses[clause_idx].value_exn();
+                    seen.extend(clause.variables.iter());
+                    steps.push(ExplanationStep {
+                        literal: entry.literal,
+                        level: entry.decision_level,
+                        clause: clause.iter_literals().collect(),
+                        learned: clause.from_conflict,
+                    });
+                }
+            }
+        }
+        decisions.reverse();
+        steps.reverse();
+        (decisions, steps)
+    }
+
+    /// How the last solve refuted the formula, if it answered unsat from a
+    /// conflict at level 0. `None` after any other answer, including unsat
+    /// from the 2-SAT and Horn fast paths or from assumptions.
+    pub fn refutation(&self) -> Option<&Refutation> {
+        self.refutation.as_ref()
+    }
+
+    fn record_refutation(&mut self, conflict_idx: usize) {
+        if let Some(proof) = self.proof.as_mut() {
+            proof.push(ProofStep::Add(vec![]));
+        }
+        let conflict = self.clauses[conflict_idx].value_exn();
+        let (_, steps) = self.trace_reasons(conflict.variables.iter().collect(), self.trail.len());
+        self.refutation = Some(Refutation {
+            conflict: conflict.iter_literals().collect(),
+            steps,
+        });
+    }
+
+    /// Log a DRUP proof from now on: every learned clause, every deleted
+    /// clause and the empty clause on a refutation. Clauses the solver is
+    /// handed rather than derives (imports and theory lemmas) aren't
+    /// justified, so the proof only checks without them.
+    pub fn start_proof(&mut self) {
+        self.proof = Some(vec![]);
+    }
+
+    /// The proof logged since `start_proof`, which stops logging.
+    pub fn take_proof(&mut self) -> Vec<ProofStep> {
+        self.proof.take().unwrap_or_default()
+    }
+
+    /// Keep occurrence lists of every live clause, original and learned,
+    /// from now on, for passes that need the clauses of a literal.
+    pub fn track_occurrences(&mut self) {
+        if self.occurrences.is_some() {
+            return;
+        }
+        let mut occurrences = OccurrenceLists::new();
+        for (idx, clause) in self.clauses.iter() {
+            occurrences.add(idx, clause.iter_literals());
+        }
+        self.occurrences = Some(occurrences);
+    }
+
+    pub fn stop_tracking_occurrences(&mut self) {
+        self.occurrences = None;
+    }
+
+    /// `None` unless `track_occurrences` was called.
+    pub fn occurrence_lists(&self) -> Option<&OccurrenceLists> {
+        self.occurrences.as_ref()
+    }
+
+    /// Give up with `SatResult::Unknown` (or `Some` of it from `run_until`)
+    /// at the next step after `flag` is set, say from another thread.
+    /// Asymmetric tautology elimination stops at the next clause it tries;
+    /// the linear-time 2-SAT and Horn fast paths don't look at the flag
+    /// once started. The flag stays set, and every solve gives up, until
+    /// it's cleared.
+    pub fn set_cancel_flag(&mut self, flag: Option<Arc<AtomicBool>>) {
+        self.cancel = flag;
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+    }
+
+    /// Run `eliminate_asymmetric_tautologies` after every `restarts`
+    /// restarts; off by default.
+    pub fn set_ate_interval(&mut self, restarts: Option<u64>) {
+        self.ate_interval = restarts.map(|restarts| restarts.max(1));
+    }
+
+    /// Delete the problem clauses that are asymmetric tautologies: those
+    /// for which assigning every literal false and unit propagating over
+    /// the other problem clauses gives a conflict, so the rest imply them.
+    /// Learned clauses take no part, and clauses that are the reason for an
+    /// assignment are kept. Stops early once the cancel flag is set. Returns
+    /// the number of clauses deleted.
+    pub fn eliminate_asymmetric_tautologies(&mut self) -> usize {
+        let candidates = self
+            .clauses
+            .iter()
+            .filter(|(_, clause)| !clause.from_conflict && clause.num_units == 0)
+            .map(|(idx, _)| idx)
+            .collect::<Vec<_>>();
+        let mut eliminated = 0;
+        for idx in candidates {
+            if self.cancelled() {
+                break;
+            }
+            if self.is_asymmetric_tautology(idx) {
+                debug!(
+                    self.debug_writer,
+                    "Eliminating asymmetric tautology {}",
+                    self.clause_string(ClauseIdx(idx))
+                );
+                self.delete_clause(idx);
+                eliminated += 1;
+            }
+        }
+        self.stats.eliminated_clauses += eliminated as u64;
+        eliminated
+    }
+
+    /// Gives up (and keeps the clause) after `ATE_BUDGET` clause visits.
+    fn is_asymmetric_tautology(&self, idx: usize) -> bool {
+        const ATE_BUDGET: usize = 10_000;
+        let mut assigned = HashMap::new();
+        let mut queue = vec![];
+        for lit in self.clauses[idx].value_exn().iter_literals() {
+            assigned.insert(lit.variable(), !lit.value());
+            queue.push(lit.negate());
+        }
+        let mut budget = ATE_BUDGET;
+        while let Some(true_lit) = queue.pop() {
+            for other in self.clauses(true_lit.negate()).iter() {
+                let Some(clause) = self.clauses.get(other) else {
+                    continue;
+                };
+                if other == idx || clause.from_conflict {
+                    continue;
+                }
+                if budget == 0 {
+                    return false;
+                }
+                budget -= 1;
+                let mut unassigned = None;
+                let mut num_unassigned = 0;
+                let mut satisfied = false;
+                for lit in clause.iter_literals() {
+                    match assigned.get(&lit.variable()) {
+                        Some(&value) if value == lit.value() => {
+                            satisfied = true;
+                            break;
+                        }
+                        Some(_) => (),
+                        None => {
+                            num_unassigned += 1;
+                            unassigned = Some(lit);
+                        }
+                    }
+                }
+                match (satisfied, num_unassigned, unassigned) {
+                    (true, _, _) => (),
+                    (false, 0, _) => return true,
+                    (false, 1, Some(lit)) => {
+                        assigned.insert(lit.variable(), lit.value());
+                        queue.push(lit);
+                    }
+                    _ => (),
+                }
+            }
+        }
+        false
+    }
+
+    /// Every live clause, original and learned, in database order.
+    pub fn clauses_iter(&self) -> impl Iterator<Item = ClauseInfo> + '_ {
+        self.clauses.values().map(|clause| ClauseInfo {
+            literals: clause.iter_literals().collect(),
+            activity: clause.score,
+            lbd: clause.lbd,
+            origin: if clause.from_conflict {
+                ClauseOrigin::Learned
+            } else {
+                ClauseOrigin::Original
+            },
+        })
+    }
+
+    /// Write the learned clauses currently in the database as DIMACS.
+    pub fn export_learned<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        let learned = self
+            .clauses_iter()
+            .filter(|info| info.origin == ClauseOrigin::Learned)
+            .map(|info| info.literals.into_iter().map(|lit| lit.into()).collect())
+            .collect::<Vec<Vec<isize>>>();
+        writeln!(writer, "{}", dimacs::of_int_array_array(&learned))
+    }
+
+    /// Add clauses written by `export_learned` as learned clauses, so they can
+    /// be deleted again like any other. They are trusted as is, which is only
+    /// sound if they were learned from a subset of this solver's clauses.
+    /// Each is imported as by `import_clause`, so this can be done between
+    /// steps; returns `Done` and stops if one makes the problem unsatisfiable
+    /// outright.
+    pub fn import_learned<R: std::io::Read>(
+        &mut self,
+        mut reader: R,
+    ) -> std::io::Result<StepResult> {
+        let mut contents = String::new();
+        reader.read_to_string(&mut contents)?;
+        for clause in dimacs::read_string(&contents)? {
+            if let res @ StepResult::Done(_) = self.import_clause(&clause) {
+                return Ok(res);
+            }
+        }
+        Ok(StepResult::Continue)
+    }
+
+    /// Add the clauses of a DIMACS CNF as they are parsed, without reading
+    /// the whole input into memory first. Returns the number of clauses
+    /// added.
+    pub fn load_dimacs<R: std::io::Read>(&mut self, reader: R) -> std::io::Result<usize> {
+        let mut num_clauses = 0;
+        for clause in dimacs::ClauseReader::new(std::io::BufReader::new(reader)) {
+            self.add_clause(clause?);
+            num_clauses += 1;
+        }
+        Ok(num_clauses)
+    }
+
+    /// The configuration and parameters this solver runs with.
+    pub fn describe(&self) -> Description {
+        Description {
+            version: env!("CARGO_PKG_VERSION"),
+            config: std::any::type_name::<Config>(),
+            heuristic: Config::HEURISTIC,
+            bitset: std::any::type_name::<Config::BitSet>(),
+            score_order: std::any::type_name::<Config::ScoreOrder>(),
+            debug: Config::DEBUG,
+            restarts: self.restart_schedule.describe(),
+            reduction: if self.bump_reasons {
+                format!("{} bumping reasons", self.reduction_policy.describe())
+            } else {
+                self.reduction_policy.describe()
+            },
+            vsids_decay: self.vsids_decay_factor,
+            clause_decay: self.cla_decay_factor,
+        }
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats {
+            clauses: self.clauses.len(),
+            variables: self.all_variables.count(),
+            ..self.stats
+        }
+    }
+
+    pub fn stats_json(&self) -> String {
+        self.stats().to_json()
+    }
+
+    /// Write a `c ...` progress line to `writer` every `every_conflicts`
+    /// conflicts.
+    pub fn set_stats_reporter<Writer: std::fmt::Write + Send + 'static>(
+        &mut self,
+        every_conflicts: u64,
+        writer: Writer,
+    ) {
+        self.reporter = Some(Reporter {
+            every_conflicts: every_conflicts.max(1),
+            writer: Box::new(writer),
+        });
+    }
+
+    fn report_stats(&mut self) {
+        let Some(every_conflicts) = self.reporter.as_ref().map(|r| r.every_conflicts) else {
+            return;
+        };
+        if self.stats.conflicts.is_multiple_of(every_conflicts) {
+            let line = self.stats().to_progress_line();
+            let reporter = self.reporter.as_mut().unwrap();
+            let _ = writeln!(reporter.writer, "{}", line);
+        }
+    }
+
+    /// Prefer the values in `model` whenever one of its variables is picked
+    /// for a decision, e.g. to re-solve from a previous solution after a small
+    /// change to the formula.
+    pub fn set_initial_model(&mut self, model: &BTreeMap<usize, bool>) {
+        for (&var, &value) in model {
+            self.suggest_phase(var, value);
+        }
+    }
+
+    /// Raise the branching priority of `var` by `weight` conflicts' worth of
+    /// VSIDS activity. Variables the solver has not seen yet are ignored.
+    pub fn bump_priority(&mut self, var: usize, weight: f64) {
+        self.cached_model = None;
+        if !self.all_variables.contains(var) {
+            return;
+        }
+        let amount = weight * self.vsids_inc;
+        self.add_vsids_score(Literal::new(var, true), amount);
+        self.add_vsids_score(Literal::new(var, false), amount);
+    }
+
+    /// Prefer `value` whenever `var` is picked for a decision.
+    pub fn suggest_phase(&mut self, var: usize, value: bool) {
+        self.cached_model = None;
+        if var >= self.phases.len() {
+            self.phases.resize(var + 1, None);
+        }
+        self.phases[var] = Some(value);
+    }
+
+    /// Prefer `value` (if `Some`) for every variable without a phase of its
+    /// own from `suggest_phase`, instead of the polarity with the higher
+    /// score.
+    pub fn set_default_phase(&mut self, value: Option<bool>) {
+        self.cached_model = None;
+        self.default_phase = value;
+    }
+
+    /// `literal`, flipped to the preferred phase of its variable if it has one.
+    fn with_phase(&self, literal: Literal) -> Literal {
+        let phase = self.phases.get(literal.variable()).copied().flatten();
+        match phase.or(self.default_phase) {
+            Some(value) => Literal::new(literal.variable(), value),
+            None => literal,
+        }
+    }
+
+    /// Install `brancher` as the decision procedure. Whenever it returns
+    /// `None`, or a literal whose variable is not unassigned, the decision
+    /// falls back to `Config::choose_literal`.
+    pub fn set_brancher(&mut self, brancher: Brancher<Config>) {
+        self.cached_model = None;
+        self.brancher = Some(brancher);
+    }
+
+    /// Call `exporter` with every clause learned from now on whose LBD is at
+    /// most `max_lbd`. Clauses that were already learned are not exported
+    /// again.
+    pub fn set_clause_exporter(&mut self, max_lbd: usize, exporter: ClauseExporter) {
+        self.clause_exporter = Some((max_lbd, exporter));
+    }
+
+    fn export_clause(&mut self, clause: &Clause<Config::BitSet>) {
+        if let Some((max_lbd, exporter)) = self.clause_exporter.as_mut() {
+            if clause.lbd <= *max_lbd {
+                let lits = clause
+                    .iter_literals()
+                    .map(|lit| lit.into())
+                    .collect::<Vec<isize>>();
+                exporter(&lits);
+            }
+        }
+    }
+
+    /// Call `hook` with every decision, conflict and restart from now on, and
+    /// with the progress of any enumeration of models.
+    pub fn set_event_hook(&mut self, hook: EventHook<Config>) {
+        self.event_hook = Some(hook);
+    }
+
+    fn emit(&mut self, event: SearchEvent) {
+        if let Some(mut hook) = self.event_hook.take() {
+            hook(&event, &BranchingView { state: self });
+            self.event_hook = Some(hook);
+        }
+    }
+
+    fn choose_literal_from_brancher(&mut self) -> Option<Literal> {
+        let mut brancher = self.brancher.take()?;
+        let literal = brancher(&BranchingView { state: self });
+        self.brancher = Some(brancher);
+        literal.filter(|lit| self.unassigned_variables.contains(lit.variable()))
+    }
+
+    /// Record the heuristic decisions and restarts from now on, until
+    /// `take_trace`. Decisions passed in as overrides are not recorded.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(vec![]);
+    }
+
+    pub fn take_trace(&mut self) -> Trace {
+        Trace(self.recording.take().unwrap_or_default())
+    }
+
+    /// Solve, taking decisions from `trace` instead of the heuristic until it
+    /// runs out. Panics as soon as the run diverges from the trace, i.e. a
+    /// traced decision is already assigned or a restart happens out of turn.
+    pub fn replay(&mut self, trace: &Trace) -> SatResult {
+        self.replaying = trace.0.iter().copied().collect();
+        let res = self.run();
+        self.replaying.clear();
+        res
+    }
+
+    fn trace_event(&mut self, event: TraceEvent) {
+        if let Some(recording) = self.recording.as_mut() {
+            recording.push(event);
+        }
+        if let Some(expected) = self.replaying.pop_front() {
+            if expected != event {
+                panic!("replay diverged: expected {:?}, got {:?}", expected, event);
+            }
+        }
+    }
+
+    fn replayed_decision(&mut self) -> Option<Literal> {
+        match self.replaying.front().copied()? {
+            TraceEvent::Decision(literal) => {
+                if !self.unassigned_variables.contains(literal.variable()) {
+                    panic!("replay diverged: {:?} is already assigned", literal);
+                }
+                Some(literal)
+            }
+            TraceEvent::Restart => panic!("replay diverged: expected a restart, got a decision"),
+        }
+    }
+
+    /// The first assumption that isn't on the trail. Backjumps and restarts
+    /// can undo assumptions, so these are decided again before anything
+    /// else. An assumption that has become false ends the solve instead,
+    /// with its core.
+    fn next_assumption(&mut self) -> Result<Option<Literal>, Vec<Literal>> {
+        for i in 0..self.current_assumptions.len() {
+            let lit = self.current_assumptions[i];
+            if self.unassigned_variables.contains(lit.variable()) {
+                return Ok(Some(lit));
+            }
+            if self.assignments.contains(lit.variable()) != lit.value() {
+                return Err(self.extract_unsat_core_of_falsified_assumption(lit));
+            }
+        }
+        Ok(None)
+    }
+
+    fn make_decision(&mut self, literal_override: Option<Literal>) -> StepResult {
+        let assumption = match literal_override {
+            Some(_) => None,
+            None => match self.next_assumption() {
+                Ok(assumption) => assumption,
+                Err(core) => return StepResult::Done(SatResult::UnsatCore(core)),
+            },
+        };
+        let chosen = match literal_override.or(assumption) {
+            Some(literal) => Some(literal),
+            None => {
+                let chosen = self
+                    .replayed_decision()
+                    .or_else(|| self.choose_literal_from_brancher())
+                    .or_else(|| Config::choose_literal(self));
+                if let Some(literal) = chosen {
+                    self.trace_event(TraceEvent::Decision(literal));
+                }
+                chosen
+            }
+        };
+        match chosen {
+            None => {
+                let mut assignments = self.assignments();
+                self.reconstruction.extend(&mut assignments);
+                self.complete_model(&mut assignments);
+                let res = SatResult::Sat(assignments);
+                StepResult::Done(res)
+            }
+            Some(literal) => {
+                self.stats.decisions += 1;
+                self.decision_level += 1;
+                self.emit(SearchEvent::Decision(literal));
+                self.react(Action::Continue(literal))
+            }
+        }
+    }
+
+    fn can_trim_clause(&self, clause: &Clause<Config::BitSet>) -> bool {
+        clause.num_units == 0
+            && clause
+                .iter_literals()
+                .filter_map(|x| self.trail_entry_idx_by_var[x.variable()])
                 .map(|x| self.trail[x].decision_level)
                 .unique()
                 .collect::<Vec<_>>()
@@ -843,69 +2085,590 @@ impl<Config: ConfigT> State<Config> {
     }
 
     fn simplify_clauses(&mut self) {
+        let start = Instant::now();
+        self.simplify_clauses_inner();
+        self.stats.profile.reduction.record(start);
+    }
+
+    fn simplify_clauses_inner(&mut self) {
         let mut sorting_buckets = vec![];
         std::mem::swap(&mut sorting_buckets, &mut self.clause_sorting_buckets);
         sorting_buckets.clear();
         for (idx, clause) in self
             .clauses
             .iter()
-            .enumerate()
-            .skip(self.num_initial_clauses)
-            .filter_map(|(i, x)| x.value().map(|x| (i, x)))
+            .filter(|&(idx, _)| idx >= self.num_initial_clauses)
             .filter(|(_, x)| x.from_conflict && x.num_units == 0 && self.can_trim_clause(x))
         {
             sorting_buckets.push(ClauseIdx(idx));
         }
-        sorting_buckets.sort_by(|ClauseIdx(a), ClauseIdx(b)| {
-            f64::total_cmp(
-                &self.clauses[*a].value_exn().score,
-                &self.clauses[*b].value_exn().score,
-            )
-        });
-        for x in &sorting_buckets {
-            debug!(
-                self.debug_writer,
-                "Clause {x:?} {}",
-                self.clause_string(x.clone())
-            );
+        for x in &sorting_buckets {
+            debug!(
+                self.debug_writer,
+                "Clause {x:?} {}",
+                self.clause_string(x.clone())
+            );
+        }
+        let candidates = sorting_buckets
+            .iter()
+            .map(|ClauseIdx(idx)| {
+                let clause = self.clauses[*idx].value_exn();
+                ReductionCandidate {
+                    activity: clause.score,
+                    lbd: clause.lbd,
+                    len: clause.variables.count(),
+                }
+            })
+            .collect::<Vec<_>>();
+        for candidate in self.reduction_policy.select(&candidates) {
+            let ClauseIdx(clause_idx) = &sorting_buckets[candidate];
+            debug!(
+                self.debug_writer,
+                "Deleting clause {clause_idx} (score {}), {}",
+                self.clauses[*clause_idx].value_exn().score,
+                self.clause_string(ClauseIdx(*clause_idx))
+            );
+            self.delete_clause(*clause_idx);
+        }
+        self.prune_learned_clause_set();
+        std::mem::swap(&mut sorting_buckets, &mut self.clause_sorting_buckets);
+    }
+
+    fn prune_learned_clause_set(&mut self) {
+        let clauses = &self.clauses;
+        self.learned_clause_set
+            .retain(|_, &mut (ClauseIdx(idx), generation)| clauses.is_live(idx, generation));
+    }
+
+    /// Cap the memory used by learned clauses at about `bytes`. Going over
+    /// it deletes learned clauses by LBD, worst first; if that isn't
+    /// enough (say, when most are reasons on the trail) the solve gives up
+    /// with `SatResult::Unknown`.
+    pub fn set_learned_memory_limit(&mut self, bytes: Option<usize>) {
+        self.learned_memory_limit = bytes;
+    }
+
+    /// Keep at most `max` released bitsets around for reuse, so that a long
+    /// incremental session doesn't hold on to every bitset it ever freed.
+    pub fn set_bitset_pool_limit(&mut self, max: Option<usize>) {
+        self.bitset_pool.set_max_retained(max);
+    }
+
+    pub fn bitset_pool_stats(&self) -> PoolStats {
+        self.bitset_pool.stats()
+    }
+
+    /// Estimated memory used by the live learned clauses.
+    pub fn learned_clause_bytes(&self) -> usize {
+        self.clauses
+            .values()
+            .filter(|clause| clause.from_conflict)
+            .map(Self::clause_bytes)
+            .sum()
+    }
+
+    fn clause_bytes(clause: &Clause<Config::BitSet>) -> usize {
+        std::mem::size_of::<Clause<Config::BitSet>>()
+            + (clause.variables.capacity() + clause.negatives.capacity()) / 8
+    }
+
+    /// Checked every so often rather than on every conflict, as it walks the
+    /// clause database.
+    fn over_learned_memory_limit(&mut self) -> bool {
+        let Some(limit) = self.learned_memory_limit else {
+            return false;
+        };
+        if !self.stats.conflicts.is_multiple_of(64) || self.learned_clause_bytes() <= limit {
+            return false;
+        }
+        let mut deletable = self
+            .clauses
+            .iter()
+            .filter(|(_, clause)| clause.from_conflict && clause.num_units == 0)
+            .map(|(idx, clause)| (clause.lbd, Self::clause_bytes(clause), idx))
+            .collect::<Vec<_>>();
+        deletable.sort_by(|a, b| b.cmp(a));
+        let mut bytes = self.learned_clause_bytes();
+        for (_, clause_bytes, idx) in deletable {
+            if bytes <= limit {
+                break;
+            }
+            self.delete_clause(idx);
+            bytes -= clause_bytes;
+        }
+        self.prune_learned_clause_set();
+        bytes > limit
+    }
+
+    /// Reduce the learned clause database after `first` new learned clauses,
+    /// then after `growth` times more each time.
+    pub fn set_reduce_schedule(&mut self, first: u64, growth: f64) {
+        self.reduce_schedule = ReduceSchedule::new(first, growth);
+    }
+
+    /// Decay VSIDS and clause activities by these factors at every
+    /// conflict: the closer to 1, the longer past conflicts count.
+    pub fn set_decay_factors(&mut self, vsids: f64, clause: f64) {
+        for factor in [vsids, clause] {
+            assert!(
+                factor > 0.0 && factor <= 1.0,
+                "decay factor {} isn't in (0, 1]",
+                factor
+            );
+        }
+        self.vsids_decay_factor = vsids;
+        self.cla_decay_factor = clause;
+    }
+
+    /// Pick the learned clauses to delete at each reduction with `policy`.
+    pub fn set_reduction_policy(&mut self, policy: Box<dyn ReductionPolicy>) {
+        self.reduction_policy = policy;
+    }
+
+    /// Also bump a learned clause's activity each time it's the reason for
+    /// a propagation, not just when it takes part in a conflict, so clauses
+    /// that keep propagating survive reductions ranked by activity.
+    pub fn set_bump_reasons(&mut self, bump: bool) {
+        self.bump_reasons = bump;
+    }
+
+    /// Restart whenever `schedule`'s limit of conflicts is reached,
+    /// starting from its current limit.
+    pub fn set_restart_schedule(&mut self, schedule: Box<dyn RestartSchedule>) {
+        self.restart_schedule = schedule;
+        self.conflicts = 0;
+    }
+
+    /// An independent solver over the same problem clauses and at-most-one
+    /// groups, with the same settings: schedules (back at their first
+    /// limits), policies, decay factors, suggested phases and the model
+    /// reconstruction stack. The clause database is rebuilt from the
+    /// problem clauses, so clauses deleted by inprocessing come back; only
+    /// the list of problem clauses itself is shared with `self` until
+    /// either side adds to it. The fork's RNG is seeded with `seed`, which
+    /// also jitters its initial VSIDS scores so the two searches diverge.
+    /// Learned clauses, callbacks and any theory are not carried over.
+    pub fn fork_with_seed(&self, seed: u64) -> Self {
+        let mut fork = Self::from_clauses(
+            self.problem_clauses
+                .iter()
+                .map(|clause| clause.iter().copied()),
+        );
+        fork.problem_clauses = Arc::clone(&self.problem_clauses);
+        for group in &self.at_most_ones {
+            fork.add_at_most_one(group.iter().map(|&lit| lit.into()).collect());
+        }
+        fork.restart_schedule = self.restart_schedule.boxed_clone();
+        fork.restart_schedule.reset();
+        fork.reduce_schedule = self.reduce_schedule.clone();
+        fork.reduce_schedule.reset();
+        fork.reduction_policy = self.reduction_policy.boxed_clone();
+        fork.bump_reasons = self.bump_reasons;
+        fork.cla_decay_factor = self.cla_decay_factor;
+        fork.vsids_decay_factor = self.vsids_decay_factor;
+        fork.phases = self.phases.clone();
+        fork.default_phase = self.default_phase;
+        fork.reconstruction = self.reconstruction.clone();
+        fork.fast_paths = self.fast_paths;
+        fork.trail_saving = self.trail_saving;
+        fork.learned_memory_limit = self.learned_memory_limit;
+        fork.ate_interval = self.ate_interval;
+        fork.assumption_order = self.assumption_order;
+        fork.rng = Pcg64::seed_from_u64(seed);
+        for var in fork.all_variables.iter().collect::<Vec<_>>() {
+            for value in [true, false] {
+                let jitter = fork.rng.random::<f64>();
+                fork.add_vsids_score(Literal::new(var, value), jitter);
+            }
+        }
+        fork
+    }
+
+    /// Forget everything learned about the problem, as if the solver had
+    /// just been built from it: learned clauses, clause and VSIDS
+    /// activities, saved phases, the trail, unsat core history and stats.
+    /// The problem clauses and at-most-one groups stay, and so do the
+    /// settings: schedules (back at their first limits), policies,
+    /// callbacks, the theory and the cancel flag. Bitsets go back to the
+    /// pool, so rebuilding doesn't allocate them again.
+    pub fn reset(&mut self) {
+        let problem_clauses = self.problem_clauses.to_vec();
+        let at_most_ones = std::mem::take(&mut self.at_most_ones);
+        self.rebuild(problem_clauses, at_most_ones);
+    }
+
+    /// Like `reset`, but drop the problem as well, leaving an empty solver
+    /// with the same settings to load an unrelated one into.
+    pub fn clear(&mut self) {
+        self.rebuild(vec![], vec![]);
+        if let Some(proof) = self.proof.as_mut() {
+            proof.clear();
+        }
+    }
+
+    fn rebuild(&mut self, problem_clauses: Vec<Vec<isize>>, at_most_ones: Vec<Vec<Literal>>) {
+        let mut bitset_pool = std::mem::take(&mut self.bitset_pool);
+        let idxs = self.clauses.iter().map(|(idx, _)| idx).collect::<Vec<_>>();
+        for idx in idxs {
+            if let Some(clause) = self.clauses.remove(idx) {
+                bitset_pool.release(clause.variables);
+                bitset_pool.release(clause.negatives);
+            }
+        }
+        for TfPair { first, second } in self.clauses_by_var.drain(..) {
+            bitset_pool.release(first);
+            bitset_pool.release(second);
+        }
+        let formula = Formula::new(problem_clauses, &mut bitset_pool);
+        let mut fresh = Self::new_with_pool_and_debug_writer::<String>(formula, bitset_pool, None);
+        for group in at_most_ones {
+            fresh.add_at_most_one(group.into_iter().map(|lit| lit.into()).collect());
+        }
+
+        std::mem::swap(&mut fresh.restart_schedule, &mut self.restart_schedule);
+        self.restart_schedule.reset();
+        std::mem::swap(&mut fresh.reduce_schedule, &mut self.reduce_schedule);
+        self.reduce_schedule.reset();
+        std::mem::swap(&mut fresh.reduction_policy, &mut self.reduction_policy);
+        fresh.bump_reasons = self.bump_reasons;
+        fresh.cla_decay_factor = self.cla_decay_factor;
+        fresh.vsids_decay_factor = self.vsids_decay_factor;
+        fresh.rng = self.rng.clone();
+        fresh.default_phase = self.default_phase;
+        fresh.debug_writer = self.debug_writer.take();
+        fresh.theory = self.theory.take();
+        fresh.brancher = self.brancher.take();
+        fresh.event_hook = self.event_hook.take();
+        fresh.clause_exporter = self.clause_exporter.take();
+        fresh.reporter = self.reporter.take();
+        fresh.fast_paths = self.fast_paths;
+        fresh.trail_saving = self.trail_saving;
+        fresh.learned_memory_limit = self.learned_memory_limit;
+        fresh.proof = self.proof.take();
+        fresh.ate_interval = self.ate_interval;
+        if self.occurrences.is_some() {
+            fresh.track_occurrences();
+        }
+        fresh.cancel = self.cancel.take();
+        fresh.assumption_order = self.assumption_order;
+        *self = fresh;
+    }
+
+    /// Send debug output to `writer` from now on, instead of the `log` facade.
+    pub fn set_debug_writer(&mut self, writer: Box<dyn std::fmt::Write + Send>) {
+        self.debug_writer = Some(Mutex::new(writer));
+    }
+
+    pub fn set_theory(&mut self, theory: Box<dyn Theory>) {
+        self.cached_model = None;
+        self.theory = Some(theory);
+    }
+
+    /// Index a new (non-tautological) clause and watch it as if it had been
+    /// there all along: by literals the trail doesn't falsify, else by the
+    /// ones it falsified last, so backjumps free the watches first. Returns
+    /// the clause's literals in that order, each with the level it was
+    /// falsified at.
+    fn push_clause_watched_by_trail(
+        &mut self,
+        clause_vec: &[isize],
+    ) -> (usize, Vec<(Literal, Option<usize>)>) {
+        let idx = self.push_clause_vec(clause_vec);
+        self.index_clause_occurrences(idx, clause_vec);
+        let mut literals = self.clauses[idx]
+            .value_exn()
+            .iter_literals()
+            .map(|lit| {
+                let falsified_at = self.trail_entry_idx_by_var[lit.variable()]
+                    .filter(|&trail_idx| self.trail[trail_idx].literal != lit);
+                (lit, falsified_at)
+            })
+            .collect::<Vec<_>>();
+        literals.sort_by_key(|&(_, falsified_at)| falsified_at.map(std::cmp::Reverse));
+        let generation = self.clauses.generation(idx);
+        for &(lit, _) in literals.iter().take(2) {
+            self.watched_clauses[lit.variable()][lit.value()].insert(ClauseIdx(idx), generation);
+        }
+        let literals = literals
+            .into_iter()
+            .map(|(lit, falsified_at)| {
+                let level = falsified_at.map(|trail_idx| self.trail[trail_idx].decision_level);
+                (lit, level)
+            })
+            .collect();
+        (idx, literals)
+    }
+
+    /// Add `clause` in the middle of a search. If the trail falsifies it, jump
+    /// back to the level of its latest literal and analyse the conflict there.
+    /// If it leaves just one literal unassigned, jump back to the level the
+    /// clause became unit at and propagate now.
+    fn add_clause_under_trail(&mut self, clause: Vec<isize>, learned: bool) -> Option<StepResult> {
+        if is_tautology(&clause) {
+            for lit in clause {
+                self.maybe_add_var(lit.unsigned_abs());
+            }
+            return None;
+        }
+        let (idx, literals) = self.push_clause_watched_by_trail(&clause);
+        self.clauses.get_mut_exn(idx).from_conflict = learned;
+        let unit = match literals[..] {
+            [] | [(_, Some(_)), ..] => false,
+            [(lit, None)] | [(lit, None), (_, Some(_)), ..]
+                if self.unassigned_variables.contains(lit.variable()) =>
+            {
+                true
+            }
+            _ => return None,
+        };
+        // conflict analysis wants a literal at the current level, and the
+        // propagated literal belongs at the level of the others
+        let level = literals.iter().find_map(|&(_, level)| level).unwrap_or(0);
+        if level < self.decision_level {
+            self.remove_from_trail_helper(Some(level));
+        }
+        if !unit {
+            return Some(self.react(Action::Contradiction(idx)));
+        }
+        self.ready_for_unit_prop.set(idx);
+        match self.unit_propagate() {
+            UnitPropagationResult::Contradiction(ClauseIdx(failed_idx)) => {
+                Some(self.react(Action::Contradiction(failed_idx)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Add a clause derived elsewhere (another solver, a theory engine) between
+    /// calls to `step`. It is kept as a learned clause, so it must be implied
+    /// by the clauses already added. A clause the trail falsifies is analysed
+    /// as a conflict, returning `Learned`, and one it makes unit is propagated
+    /// straight away. Returns `Done` if it makes the problem unsatisfiable
+    /// outright.
+    pub fn import_clause(&mut self, clause: &[isize]) -> StepResult {
+        self.add_clause_under_trail(clause.to_vec(), true)
+            .unwrap_or(StepResult::Continue)
+    }
+
+    fn theory_propagate(&mut self) -> Option<StepResult> {
+        let mut theory = self.theory.take()?;
+        let trail = self.trail.iter().map(|entry| entry.literal).collect::<Vec<_>>();
+        let lemmas = theory.propagate(&trail);
+        self.theory = Some(theory);
+        let mut added = false;
+        for mut lemma in lemmas {
+            lemma.sort();
+            if !self.theory_lemmas.insert(lemma.clone()) {
+                continue;
+            }
+            added = true;
+            if let Some(res) = self.add_clause_under_trail(lemma, false) {
+                return Some(res);
+            }
+        }
+        if added {
+            Some(StepResult::Continue)
+        } else {
+            None
+        }
+    }
+
+    /// Everything wrong with the solver's bookkeeping, one line each: the
+    /// trail against the assignment, reasons against their clauses, and the
+    /// watches. Between steps every clause is watched by two of its literals
+    /// (or its only one), and an unsatisfied clause with a false watch is
+    /// unit, queued for propagation and watched by its unassigned literal and
+    /// its latest falsified one, unless the problem is already known to be
+    /// unsatisfiable.
+    pub fn invariant_violations(&self) -> Vec<String> {
+        let mut violations = vec![];
+        let mut reasons = HashMap::new();
+        let mut last_level = 0;
+        for (idx, entry) in self.trail.iter().enumerate() {
+            let (literal, var) = (entry.literal, entry.literal.variable());
+            if self.trail_entry_idx_by_var[var] != Some(idx) {
+                violations.push(format!(
+                    "trail entry {} ({:?}) is indexed as {:?}",
+                    idx, literal, self.trail_entry_idx_by_var[var]
+                ));
+            }
+            if self.unassigned_variables.contains(var)
+                || self.assignments.contains(var) != literal.value()
+            {
+                violations.push(format!(
+                    "trail entry {} ({:?}) disagrees with the assignment",
+                    idx, literal
+                ));
+            }
+            if entry.decision_level < last_level || entry.decision_level > self.decision_level {
+                violations.push(format!(
+                    "trail entry {} ({:?}) is at level {} after level {}, the solver at {}",
+                    idx, literal, entry.decision_level, last_level, self.decision_level
+                ));
+            }
+            last_level = entry.decision_level;
+            let Reason::ClauseIdx(clause_idx) = entry.reason else {
+                continue;
+            };
+            *reasons.entry(clause_idx).or_insert(0) += 1;
+            let Some(clause) = self.clauses.get(clause_idx) else {
+                violations.push(format!(
+                    "trail entry {} ({:?}) has deleted reason {}",
+                    idx, literal, clause_idx
+                ));
+                continue;
+            };
+            // every other literal was falsified earlier on the trail
+            let implied = clause.iter_literals().any(|lit| lit == literal)
+                && clause.iter_literals().all(|lit| {
+                    lit == literal
+                        || self.trail_entry_idx_by_var[lit.variable()]
+                            .is_some_and(|other| other < idx && self.trail[other].literal != lit)
+                });
+            if !implied {
+                violations.push(format!(
+                    "trail entry {} ({:?}) isn't implied by its reason {} ({})",
+                    idx,
+                    literal,
+                    clause_idx,
+                    clause.to_string()
+                ));
+            }
+        }
+        for var in self.all_variables.iter() {
+            if !self.unassigned_variables.contains(var)
+                && self.trail_entry_idx_by_var[var].is_none()
+            {
+                violations.push(format!("variable {} is assigned but not on the trail", var));
+            }
         }
-        let num_to_drop = sorting_buckets.len() / 2;
-        // not bothered to sort out ownership so just iterating over i
-        for ClauseIdx(clause_idx) in sorting_buckets.iter().take(num_to_drop) {
-            debug!(
-                self.debug_writer,
-                "Deleting clause {clause_idx} (score {}), {}",
-                self.clauses[*clause_idx].value_exn().score,
-                self.clause_string(ClauseIdx(*clause_idx))
-            );
-            self.delete_clause(*clause_idx);
+
+        let mut watchers: HashMap<usize, Vec<Literal>> = HashMap::new();
+        for var in self.all_variables.iter() {
+            for literal in [Literal::new(var, false), Literal::new(var, true)] {
+                for (&ClauseIdx(clause_idx), &generation) in self.watched_clauses(literal) {
+                    // stale watches are dropped lazily
+                    if self.clauses.is_live(clause_idx, generation) {
+                        watchers.entry(clause_idx).or_default().push(literal);
+                    }
+                }
+            }
         }
-        std::mem::swap(&mut sorting_buckets, &mut self.clause_sorting_buckets);
+        for (clause_idx, clause) in self.clauses.iter() {
+            let units = reasons.get(&clause_idx).copied().unwrap_or(0);
+            if clause.num_units != units {
+                violations.push(format!(
+                    "clause {} ({}) counts {} units but is the reason for {}",
+                    clause_idx,
+                    clause.to_string(),
+                    clause.num_units,
+                    units
+                ));
+            }
+            if clause.tautology {
+                continue;
+            }
+            let watching = watchers.remove(&clause_idx).unwrap_or_default();
+            let in_clause = |lit: &Literal| {
+                clause.variables.contains(lit.variable())
+                    && clause.negatives.contains(lit.variable()) != lit.value()
+            };
+            if watching.len() != clause.variables.count().min(2) || !watching.iter().all(in_clause)
+            {
+                violations.push(format!(
+                    "clause {} ({}) is watched by {:?}",
+                    clause_idx,
+                    clause.to_string(),
+                    watching
+                ));
+            }
+            let falsified = |lit: &Literal| {
+                !self.unassigned_variables.contains(lit.variable())
+                    && self.assignments.contains(lit.variable()) != lit.value()
+            };
+            // a clause falsified at level 0 is left for the next step to report
+            if self.instantly_unsat || self.is_satisfied(clause) || !watching.iter().any(falsified)
+            {
+                continue;
+            }
+            let num_unassigned = clause
+                .variables
+                .iter_intersection(&self.unassigned_variables)
+                .count();
+            let latest_falsified = clause
+                .iter_literals()
+                .filter(falsified)
+                .max_by_key(|lit| self.trail_entry_idx_by_var[lit.variable()]);
+            if num_unassigned != 1
+                || !self.ready_for_unit_prop.contains(clause_idx)
+                || watching
+                    .iter()
+                    .any(|lit| falsified(lit) && Some(*lit) != latest_falsified)
+            {
+                violations.push(format!(
+                    "clause {} ({}) has false watches {:?} with {} unassigned literals{}",
+                    clause_idx,
+                    clause.to_string(),
+                    watching,
+                    num_unassigned,
+                    if self.ready_for_unit_prop.contains(clause_idx) {
+                        ""
+                    } else {
+                        ", not queued"
+                    }
+                ));
+            }
+        }
+        violations
     }
 
     pub fn step(&mut self, literal_override: Option<Literal>) -> StepResult {
+        let res = self.step_unchecked(literal_override);
+        if Config::CHECK_INVARIANTS && matches!(res, StepResult::Continue | StepResult::Learned(_))
+        {
+            let violations = self.invariant_violations();
+            if !violations.is_empty() {
+                panic!(
+                    "invariants broken at step {}, decision level {}:\n{}\ntrail: {:?}",
+                    self.iterations,
+                    self.decision_level,
+                    violations.join("\n"),
+                    self.trail_literals()
+                );
+            }
+        }
+        res
+    }
+
+    fn step_unchecked(&mut self, literal_override: Option<Literal>) -> StepResult {
         self.iterations += 1;
-        if self.iterations % self.simplify_clauses_every == 0 {
+        if self.reduce_schedule.due() {
             debug!(
                 self.debug_writer,
                 "simplifying clauses at iteration {}, num clauses {}, level {}",
                 self.iterations,
-                self.clauses
-                    .iter()
-                    .filter_map(|x| x.value())
-                    .collect::<Vec<_>>()
-                    .len(),
+                self.clauses.len(),
                 self.decision_level
             );
             self.simplify_clauses();
             self.decay_clause_activities();
+            self.reduce_schedule.reduced();
         };
         if self.instantly_unsat {
-            // should do a real thing...
+            if let Some(proof) = self.proof.as_mut() {
+                proof.push(ProofStep::Add(vec![]));
+            }
+            self.refutation = Some(Refutation {
+                conflict: vec![],
+                steps: vec![],
+            });
             return StepResult::Done(SatResult::UnsatCore(vec![]));
         }
         match self.unit_propagate() {
-            UnitPropagationResult::NothingToPropagate => self.make_decision(literal_override),
+            UnitPropagationResult::NothingToPropagate => match self.theory_propagate() {
+                Some(res) => res,
+                None => self.make_decision(literal_override),
+            },
             UnitPropagationResult::FinishedUnitPropagation => StepResult::Continue,
             UnitPropagationResult::Contradiction(ClauseIdx(idx)) => {
                 self.react(Action::Contradiction(idx))
@@ -915,30 +2678,184 @@ impl<Config: ConfigT> State<Config> {
 
     fn run_inner(&mut self) -> SatResult {
         loop {
+            if self.cancelled() {
+                return SatResult::Unknown;
+            }
             match self.step(None) {
-                StepResult::Done(res@SatResult::UnsatCore(_)) => return res,
                 StepResult::Done(SatResult::Sat(res)) => {
                     if Config::CHECK_RESULTS {
-                        assert!(satisfies(&self.clauses, &res));
+                        assert!(satisfies(self.clauses.values(), &res));
+                        assert!(self.satisfies_at_most_ones(&res));
                     }
                     return SatResult::Sat(res);
                 }
-                StepResult::Continue => continue,
+                StepResult::Done(res) => return res,
+                StepResult::Continue | StepResult::Learned(_) => continue,
             }
         }
     }
 
+    /// Solve the problem. If the last call found a model and no clauses,
+    /// assumptions or branching hints have been given since, that model is
+    /// checked against the problem and returned again without searching.
     pub fn run(&mut self) -> SatResult {
+        if let Some(model) = self.take_cached_model() {
+            self.stats.cached_models += 1;
+            self.cached_model = Some(model.clone());
+            return SatResult::Sat(model);
+        }
         self.restart();
-        self.run_inner()
+        self.current_assumptions.clear();
+        self.refutation = None;
+        let res = match self.try_fast_path() {
+            Some(res) => res,
+            None => self.run_inner(),
+        };
+        // a theory can reject a model the clauses allow
+        if let (SatResult::Sat(model), None) = (&res, &self.theory) {
+            self.cached_model = Some(model.clone());
+        }
+        res
+    }
+
+    /// Make `model` assign every variable from 1 to the largest one in it
+    /// or the problem, so that models of a formula that skips variable
+    /// numbers list the same variables however they were found. Variables
+    /// in no clause are false.
+    fn complete_model(&self, model: &mut BTreeMap<usize, bool>) {
+        let max_var = model
+            .keys()
+            .next_back()
+            .copied()
+            .into_iter()
+            .chain(self.all_variables.iter().last())
+            .max()
+            .unwrap_or(0);
+        for var in 1..=max_var {
+            model.entry(var).or_insert(false);
+        }
+    }
+
+    fn take_cached_model(&mut self) -> Option<BTreeMap<usize, bool>> {
+        let model = self.cached_model.take()?;
+        // a trace has to see the search
+        if self.recording.is_some() || !self.replaying.is_empty() {
+            return None;
+        }
+        let satisfied = self.problem_clauses.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|&lit| model.get(&lit.unsigned_abs()) == Some(&(lit > 0)))
+        });
+        (satisfied && self.satisfies_at_most_ones(&model)).then_some(model)
+    }
+
+    /// Keep the part of the trail a backjump undoes, and when one of its
+    /// literals is assigned again (by a decision or a propagation), reassign
+    /// the literals it implied straight from the saved trail. Cuts the work
+    /// of finding the same implications again after deep backjumps. Off by
+    /// default.
+    pub fn set_trail_saving(&mut self, enabled: bool) {
+        self.trail_saving = enabled;
+        self.saved_trail.clear();
+        self.saved_trail_idx_by_var.clear();
+    }
+
+    /// Whether `run` may answer 2-SAT and (renamable) Horn formulas without
+    /// searching. The model from a fast path is not put on the trail, since
+    /// level 0 assignments would outlive the call, so `trail_literals`,
+    /// `reason` and `explain` see an empty trail after one. On by default;
+    /// turn it off to inspect how a model was reached.
+    pub fn set_fast_paths(&mut self, enabled: bool) {
+        self.fast_paths = enabled;
+    }
+
+    /// Solve without search if the problem clauses are 2-SAT, Horn or
+    /// renamable Horn and nothing else (a theory, at-most-one groups, a
+    /// brancher or a trace) has a say in the search. Not while a proof is
+    /// being logged, since neither fast path writes the clauses a DRUP
+    /// proof needs.
+    fn try_fast_path(&mut self) -> Option<SatResult> {
+        if !self.fast_paths
+            || self.not_renamable_horn
+            || self.theory.is_some()
+            || !self.at_most_ones.is_empty()
+            || self.brancher.is_some()
+            || self.recording.is_some()
+            || !self.replaying.is_empty()
+            || self.proof.is_some()
+            || self.cancelled()
+        {
+            return None;
+        }
+        let res = if two_sat::is_two_sat(&self.problem_clauses) {
+            self.stats.two_sat_solves += 1;
+            match two_sat::solve(&self.problem_clauses) {
+                Some(model) => SatResult::Sat(model),
+                None => SatResult::UnsatCore(vec![]),
+            }
+        } else if let Some(res) = horn::try_solve(&self.problem_clauses) {
+            self.stats.horn_solves += 1;
+            res
+        } else {
+            // adding clauses can't make it renamable Horn again
+            self.not_renamable_horn = true;
+            return None;
+        };
+        let res = match res {
+            SatResult::Sat(model) => {
+                let mut assignments = self
+                    .all_variables
+                    .iter()
+                    .map(|var| (var, model.get(&var).copied().unwrap_or(false)))
+                    .collect();
+                self.reconstruction.extend(&mut assignments);
+                self.complete_model(&mut assignments);
+                SatResult::Sat(assignments)
+            }
+            res => res,
+        };
+        Some(res)
+    }
+
+    /// Like [`State::run`], but gives up with `None` once `deadline` has
+    /// passed.
+    pub fn run_until(&mut self, deadline: Instant) -> Option<SatResult> {
+        self.restart();
+        self.current_assumptions.clear();
+        self.refutation = None;
+        loop {
+            match self.step(None) {
+                StepResult::Done(res) => return Some(res),
+                StepResult::Continue | StepResult::Learned(_) if Instant::now() >= deadline => {
+                    return None
+                }
+                StepResult::Continue | StepResult::Learned(_) if self.cancelled() => {
+                    return Some(SatResult::Unknown)
+                }
+                StepResult::Continue | StepResult::Learned(_) => continue,
+            }
+        }
     }
 
     fn stabilize_assumption(&mut self) -> Option<SatResult> {
         match self.unit_propagate() {
-            UnitPropagationResult::Contradiction(failed_clause_idx) => 
-            {
-                let learned_clause = self.learn_clause_from_failure(failed_clause_idx);
-                let core = self.extract_unsat_core_of_learned(Some(&learned_clause));
+            UnitPropagationResult::Contradiction(ClauseIdx(failed_clause_idx)) => {
+                if self.decision_level == 0 {
+                    self.record_refutation(failed_clause_idx);
+                }
+                let vars = self.clauses[failed_clause_idx]
+                    .value_exn()
+                    .variables
+                    .iter()
+                    .collect::<Vec<_>>();
+                let decisions = self.decisions_implying(&vars);
+                let core = self
+                    .current_assumptions
+                    .iter()
+                    .copied()
+                    .filter(|lit| decisions.contains(lit))
+                    .collect();
                 Some(SatResult::UnsatCore(core))
             }
             UnitPropagationResult::NothingToPropagate
@@ -947,31 +2864,87 @@ impl<Config: ConfigT> State<Config> {
     }
 
     pub fn run_with_assumptions(&mut self, assumptions: &[isize]) -> SatResult {
+        self.run_with_assumptions_inner(&literals_of_ints(assumptions))
+    }
+
+    /// Like [`State::run_with_assumptions`], but rejects assumptions on
+    /// variables that don't occur in the formula instead of treating them
+    /// as unsatisfiable.
+    pub fn run_with_literal_assumptions(
+        &mut self,
+        assumptions: &[Literal],
+    ) -> Result<SatResult, AssumptionError> {
+        if let Some(lit) = assumptions
+            .iter()
+            .find(|lit| !self.all_variables.contains(lit.variable()))
+        {
+            return Err(AssumptionError::UnknownVariable(lit.variable()));
+        }
+        Ok(self.run_with_assumptions_inner(assumptions))
+    }
+
+    /// Decide assumptions in the order `policy` gives rather than as
+    /// passed in. Deciding the ones likely to conflict first finds a core
+    /// sooner, which adds up over loops that extract core after core.
+    pub fn set_assumption_order_policy(&mut self, policy: AssumptionOrder) {
+        self.assumption_order = policy;
+    }
+
+    fn order_assumptions(&self, assumptions: &[Literal]) -> Vec<Literal> {
+        let activity = |lit: &Literal| match self.score_for_literal.get(lit.variable()) {
+            Some(scores) => OrderedFloat(scores[true] + scores[false]),
+            None => OrderedFloat(0.0),
+        };
+        let mut ordered = assumptions.to_vec();
+        match self.assumption_order {
+            AssumptionOrder::AsGiven => (),
+            AssumptionOrder::Activity => {
+                ordered.sort_by_key(|lit| std::cmp::Reverse(activity(lit)))
+            }
+            AssumptionOrder::LastFailure => ordered.sort_by_key(|lit| {
+                let last_failure = self.last_failure.get(&lit.variable()).copied();
+                std::cmp::Reverse((last_failure, activity(lit)))
+            }),
+        }
+        ordered
+    }
+
+    fn run_with_assumptions_inner(&mut self, assumptions: &[Literal]) -> SatResult {
+        self.cached_model = None;
+        let result = self.run_with_ordered_assumptions(&self.order_assumptions(assumptions));
+        if let SatResult::UnsatCore(core) = &result {
+            self.num_cores += 1;
+            for lit in core {
+                self.last_failure.insert(lit.variable(), self.num_cores);
+            }
+        }
+        result
+    }
+
+    fn run_with_ordered_assumptions(&mut self, assumptions: &[Literal]) -> SatResult {
         self.restart();
 
         self.current_assumptions.clear();
-        for &lit_val in assumptions {
-            self.current_assumptions.push(lit_val.into());
-        }
+        self.current_assumptions.extend_from_slice(assumptions);
+        self.refutation = None;
 
         match self.stabilize_assumption() {
             Some(res) => return res,
             None => (),
         }
-        for &lit_val in assumptions {
-            let var = lit_val.abs() as usize;
-            let value = lit_val > 0;
-            let lit = Literal::new(var, value);
+        for &lit in assumptions {
+            let var = lit.variable();
+            let value = lit.value();
             if !self.unassigned_variables.contains(var) {
                 if self.assignments.contains(var) != value {
-                    let core = self.extract_unsat_core();
+                    let core = self.extract_unsat_core_of_falsified_assumption(lit);
                     return SatResult::UnsatCore(core);
                 } else {
                     continue;
                 }
             }
             match self.make_decision(Some(lit)) {
-                StepResult::Continue => (),
+                StepResult::Continue | StepResult::Learned(_) => (),
                 StepResult::Done(res) => return res,
             }
             match self.stabilize_assumption() {
@@ -982,6 +2955,98 @@ impl<Config: ConfigT> State<Config> {
         self.run_inner()
     }
 
+    /// Replay an incremental CNF: clauses are added as they come and every
+    /// assumption line triggers a solve, whose results are returned in order.
+    pub fn run_inccnf(&mut self, lines: Vec<IncCnfLine>) -> Vec<SatResult> {
+        let mut results = vec![];
+        for line in lines {
+            match line {
+                IncCnfLine::Clause(clause) => self.add_clause(clause),
+                IncCnfLine::Assume(assumptions) => {
+                    results.push(self.run_with_assumptions(&assumptions))
+                }
+            }
+        }
+        results
+    }
+
+    /// Enumerate up to `limit` models that differ on `vars`, returning each
+    /// projected onto `vars`. A blocking clause over the projection is added
+    /// after every model, so the solver stays constrained afterwards.
+    pub fn enumerate_models_projected(
+        &mut self,
+        vars: &[usize],
+        limit: usize,
+    ) -> Vec<BTreeMap<usize, bool>> {
+        self.models().project(vars).take(limit).collect()
+    }
+
+    /// The exact number of assignments to `vars` that extend to a model of
+    /// the problem clauses and at-most-one groups. Each connected component
+    /// of the problem is counted on its own, by enumerating models projected
+    /// onto its variables in `vars` with a fresh solver, and the counts are
+    /// multiplied; variables of `vars` occurring nowhere double the count.
+    /// `self` is left as it is, and learned clauses and any theory take no
+    /// part. `None` if the count doesn't fit in a `ModelCount`, which only
+    /// happens without the `bigint` feature.
+    pub fn count_models_projected(&self, vars: &[usize]) -> Option<ModelCount> {
+        let vars = vars.iter().copied().collect::<BTreeSet<_>>();
+        let groups = self
+            .at_most_ones
+            .iter()
+            .map(|group| group.iter().map(|&lit| lit.into()).collect::<Vec<isize>>());
+        let constraints = self
+            .problem_clauses
+            .iter()
+            .cloned()
+            .chain(groups)
+            .collect::<Vec<_>>();
+        let mut count = model_count::one();
+        let mut constrained = BTreeSet::new();
+        for component in connected_components(&constraints) {
+            let (clauses, groups): (Vec<_>, Vec<_>) = component
+                .into_iter()
+                .partition(|&idx| idx < self.problem_clauses.len());
+            let mut solver = Self::new_from_vec(
+                clauses
+                    .iter()
+                    .map(|&idx| constraints[idx].clone())
+                    .collect(),
+            );
+            for idx in groups {
+                solver.add_at_most_one(constraints[idx].clone());
+            }
+            let component_vars = solver.all_variables.iter().collect::<BTreeSet<_>>();
+            let projection = component_vars
+                .intersection(&vars)
+                .copied()
+                .collect::<Vec<_>>();
+            constrained.extend(component_vars);
+            // with nothing to project onto, one empty model if it's sat
+            let models = solver.models().project(&projection).count();
+            count = model_count::times(count, models)?;
+            if model_count::is_zero(&count) {
+                return Some(count);
+            }
+        }
+        for _ in vars.iter().filter(|var| !constrained.contains(var)) {
+            count = model_count::times(count, 2)?;
+        }
+        Some(count)
+    }
+
+    /// Iterate over the models of the formula, each differing from the ones
+    /// before it. See [`Models`].
+    pub fn models(&mut self) -> Models<'_, Config> {
+        Models {
+            solver: self,
+            projection: None,
+            blocking_clause: None,
+            done: false,
+            exhausted: false,
+        }
+    }
+
     fn extract_unsat_core_of_learned(&self, last_learned: Option<&Clause<Config::BitSet>>) -> Vec<Literal> {
         let mut core = Vec::new();
         if self.current_assumptions.is_empty() {
@@ -998,34 +3063,78 @@ impl<Config: ConfigT> State<Config> {
         core
     }
 
+    /// Decisions in the implication graph leading to the assignments of
+    /// `vars`.
+    fn decisions_implying(&mut self, vars: &[usize]) -> Vec<Literal> {
+        let mut seen = self.acquire_bitset();
+        for &var in vars {
+            seen.set(var);
+        }
+        let mut decisions = vec![];
+        for entry in self.trail.iter().rev() {
+            let entry_var = entry.literal.variable();
+            if !seen.contains(entry_var) {
+                continue;
+            }
+            match entry.reason {
+                Reason::Decision(literal) => decisions.push(literal),
+                Reason::ClauseIdx(clause_idx) => {
+                    for lit in self.clauses[clause_idx].value_exn().iter_literals() {
+                        seen.set(lit.variable());
+                    }
+                }
+            }
+        }
+        self.free_bitset(seen);
+        decisions
+    }
+
+    /// `assumption` was already false when we came to decide it, so the core
+    /// is it plus the earlier assumptions that implied its negation.
+    fn extract_unsat_core_of_falsified_assumption(&mut self, assumption: Literal) -> Vec<Literal> {
+        let decisions = self.decisions_implying(&[assumption.variable()]);
+        self.current_assumptions
+            .iter()
+            .copied()
+            .filter(|&lit| lit == assumption || decisions.contains(&lit))
+            .collect()
+    }
+
     fn extract_unsat_core(&self) -> Vec<Literal> {
-        let last_learned = self.clauses.last().and_then(|c| c.value());
+        let last_learned = self.clauses.slots().last().and_then(|c| c.value());
         self.extract_unsat_core_of_learned(last_learned)
     }
 
-    fn update_watch_literals_for_new_clause_helper(
-        debug_writer: &Option<RefCell<Box<dyn std::fmt::Write>>>,
-        clause: &Clause<Config::BitSet>,
-        clause_idx: usize,
-        generation: Generation,
-        watched_clauses: &mut Vec<TfPair<BTreeMap<ClauseIdx, Generation>>>,
-        ready_for_unit_prop: &mut Config::BitSet,
-        unassigned_variables: &Config::BitSet,
-    ) {
+    /// Watch two literals of the new clause `clause_idx`, preferring
+    /// unassigned ones, and queue it for propagation if it's unit.
+    fn update_watch_literals_for_new_clause(&mut self, clause_idx: usize) {
+        let clause = self.clauses[clause_idx].value_exn();
+        let generation = self.clauses.generation(clause_idx);
+        let (watched_clauses, ready_for_unit_prop) =
+            (&mut self.watched_clauses, &mut self.ready_for_unit_prop);
         let mut unassigned_lits = clause
             .variables
-            .iter_intersection(unassigned_variables)
+            .iter_intersection(&self.unassigned_variables)
             .map(|var| Literal::new(var, !clause.negatives.contains(var)));
         let mut assigned_lits = clause
             .variables
-            .iter_difference(unassigned_variables)
-            .map(|var| Literal::new(var, !clause.negatives.contains(var)));
+            .iter_difference(&self.unassigned_variables)
+            .map(|var| Literal::new(var, !clause.negatives.contains(var)))
+            .collect::<Vec<_>>();
+        // true literals first, then the latest falsified, so that a backjump
+        // unassigns a false watch before the rest of the clause
+        assigned_lits.sort_by_key(|&lit| {
+            let idx = self.trail_entry_idx_by_var[lit.variable()].unwrap();
+            std::cmp::Reverse((self.trail[idx].literal == lit, idx))
+        });
+        let mut assigned_lits = assigned_lits.into_iter();
         match (
             unassigned_lits.next(),
             unassigned_lits.next(),
             assigned_lits.next(),
             assigned_lits.next(),
         ) {
+            // the empty clause, see `instantly_unsat`
             (None, None, None, None) => (),
             (None, None, Some(lit), None) => {
                 watched_clauses[lit.variable()][lit.value()]
@@ -1043,7 +3152,7 @@ impl<Config: ConfigT> State<Config> {
                 watched_clauses[lit2.variable()][lit2.value()]
                     .insert(ClauseIdx(clause_idx), generation);
                 debug!(
-                    debug_writer,
+                    self.debug_writer,
                     "adding watched literal {} for unit clause ({:?})",
                     lit.to_string(),
                     clause.to_string()
@@ -1054,7 +3163,7 @@ impl<Config: ConfigT> State<Config> {
                 watched_clauses[lit.variable()][lit.value()]
                     .insert(ClauseIdx(clause_idx), generation);
                 debug!(
-                    debug_writer,
+                    self.debug_writer,
                     "adding watched literal {} for unit clause ({:?})",
                     lit.to_string(),
                     clause.to_string()
@@ -1063,7 +3172,7 @@ impl<Config: ConfigT> State<Config> {
             }
             (Some(a), Some(b), _, _) => {
                 debug!(
-                    debug_writer,
+                    self.debug_writer,
                     "adding watched literals {} and {} for clause ({:?})",
                     a.to_string(),
                     b.to_string(),
@@ -1076,19 +3185,7 @@ impl<Config: ConfigT> State<Config> {
         };
     }
 
-    fn update_watch_literals_for_new_clause(&mut self, clause_idx: usize) {
-        Self::update_watch_literals_for_new_clause_helper(
-            &self.debug_writer,
-            &self.clauses[clause_idx].value_exn(),
-            clause_idx,
-            self.clauses[clause_idx].generation().clone(),
-            &mut self.watched_clauses,
-            &mut self.ready_for_unit_prop,
-            &self.unassigned_variables,
-        )
-    }
-
-    pub fn new_with_pool_and_debug_writer<Writer: std::fmt::Write + 'static>(
+    pub fn new_with_pool_and_debug_writer<Writer: std::fmt::Write + Send + 'static>(
         formula: Formula<Config::BitSet>,
         mut bitset_pool: Pool<Config::BitSet>,
         debug_writer: Option<Writer>,
@@ -1099,29 +3196,34 @@ impl<Config: ConfigT> State<Config> {
             clauses,
             literal_counts: _,
         } = formula;
+        // a tautology is always satisfied, and its bitsets can't represent it
         let clauses = clauses
             .into_iter()
-            .map(|x| TombStone::new(0, x))
-            .collect::<Vec<_>>();
+            .filter_map(|clause| {
+                if !clause.tautology {
+                    return Some(clause);
+                }
+                bitset_pool.release(clause.variables);
+                bitset_pool.release(clause.negatives);
+                None
+            })
+            .collect::<GenVec<_>>();
         let num_vars = max_var + 1;
         let mut variables_bitset = Config::BitSet::create();
         variables_bitset.clear_all();
         let mut clauses_by_var = vec![];
         let mut watched_clauses = vec![];
-        let mut ready_for_unit_prop = Config::BitSet::create();
+        let ready_for_unit_prop = Config::BitSet::create();
 
         for var in vars {
             variables_bitset.set(var);
         }
 
         for _ in 0..num_vars {
-            let mut bs = TfPair {
-                first: bitset_pool.acquire(|| Config::BitSet::create()),
-                second: bitset_pool.acquire(|| Config::BitSet::create()),
-            };
-            bs.first.clear_all();
-            bs.second.clear_all();
-            clauses_by_var.push(bs);
+            clauses_by_var.push(TfPair {
+                first: bitset_pool.acquire_reset(Config::BitSet::create),
+                second: bitset_pool.acquire_reset(Config::BitSet::create),
+            });
             watched_clauses.push(TfPair {
                 first: BTreeMap::new(),
                 second: BTreeMap::new(),
@@ -1133,33 +3235,30 @@ impl<Config: ConfigT> State<Config> {
         let debug_writer = match debug_writer {
             None => None,
             Some(w) => {
-                let b: Box<dyn std::fmt::Write> = Box::new(w);
-                Some(RefCell::new(b))
+                let b: Box<dyn std::fmt::Write + Send> = Box::new(w);
+                Some(Mutex::new(b))
             }
         };
 
-        for (idx, clause) in clauses.iter().filter_map(|x| x.value()).enumerate() {
-            // all things aren't tombstones rn so enumerate after filter map is ifne
+        for (idx, clause) in clauses.iter() {
             if clause.variables.is_empty() {
                 instantly_unsat = true;
             }
             clause.iter_literals().for_each(|lit| {
                 clauses_by_var[lit.variable()][lit.value()].set(idx);
             });
-            Self::update_watch_literals_for_new_clause_helper(
-                &debug_writer,
-                clause,
-                idx,
-                0,
-                &mut watched_clauses,
-                &mut ready_for_unit_prop,
-                &variables_bitset,
-            );
         }
 
         let num_initial_clauses = clauses.len();
+        let problem_clauses = clauses
+            .values()
+            .filter(|clause| !clause.tautology)
+            .map(|clause| clause.iter_literals().map(|lit| lit.into()).collect())
+            .collect::<Vec<_>>();
         let all_variables = variables_bitset.clone();
-        let unassigned_variables = variables_bitset;
+        let mut unassigned_variables = variables_bitset;
+        // counted on every random decision
+        unassigned_variables.cache_count();
         let rng = Pcg64::seed_from_u64(5);
 
         let score_for_literal = (0..num_vars)
@@ -1170,20 +3269,15 @@ impl<Config: ConfigT> State<Config> {
             })
             .collect::<Vec<_>>();
 
-        let literal_by_score = all_variables
-            .iter()
-            .flat_map(|i| {
-                let score = &score_for_literal[i];
-                [
-                    (OrderedFloat(score[true]), Literal::new(i, true)),
-                    (OrderedFloat(score[false]), Literal::new(i, false)),
-                ]
-                .into_iter()
-            })
-            .collect::<BTreeSet<_>>();
+        let mut literal_by_score = Config::ScoreOrder::default();
+        for i in all_variables.iter() {
+            let score = &score_for_literal[i];
+            literal_by_score.insert((OrderedFloat(score[true]), Literal::new(i, true)));
+            literal_by_score.insert((OrderedFloat(score[false]), Literal::new(i, false)));
+        }
 
-        State {
-            luby: Luby::new(32),
+        let mut state = State {
+            restart_schedule: Box::new(Luby::new(32)),
             conflicts: 0,
             score_for_literal,
             literal_by_score,
@@ -1193,9 +3287,10 @@ impl<Config: ConfigT> State<Config> {
             vsids_decay_factor: 0.95,
             vsids_activity_rescale: 1e20,
             vsids_inc: 1.0,
-            clauses_first_tombstone: None,
             clause_sorting_buckets: vec![],
-            simplify_clauses_every: 2500,
+            reduce_schedule: ReduceSchedule::new(2000, 1.1),
+            reduction_policy: Box::new(ByActivity::new(0.5)),
+            bump_reasons: false,
             ready_for_unit_prop,
             all_variables,
             assignments: Config::BitSet::create(),
@@ -1213,10 +3308,47 @@ impl<Config: ConfigT> State<Config> {
             debug_writer,
             instantly_unsat,
             current_assumptions: Vec::new(),
+            refutation: None,
+            at_most_ones: vec![],
+            at_most_ones_by_literal: BTreeMap::new(),
+            at_most_one_pairs: HashSet::new(),
+            theory: None,
+            theory_lemmas: HashSet::new(),
+            learned_clause_set: HashMap::new(),
+            problem_clauses: Arc::new(problem_clauses),
+            brancher: None,
+            event_hook: None,
+            clause_exporter: None,
+            stats: Stats::default(),
+            reporter: None,
+            phases: vec![],
+            default_phase: None,
+            recording: None,
+            replaying: VecDeque::new(),
+            reconstruction: Reconstruction::new(),
+            fast_paths: true,
+            trail_saving: false,
+            saved_trail: vec![],
+            saved_trail_idx_by_var: HashMap::new(),
+            learned_memory_limit: None,
+            not_renamable_horn: false,
+            proof: None,
+            ate_interval: None,
+            occurrences: None,
+            cancel: None,
+            assumption_order: AssumptionOrder::AsGiven,
+            last_failure: HashMap::new(),
+            num_cores: 0,
+            cached_model: None,
+        };
+        let idxs = state.clauses.iter().map(|(idx, _)| idx).collect::<Vec<_>>();
+        for idx in idxs {
+            state.update_watch_literals_for_new_clause(idx);
         }
+        state
     }
 
-    pub fn new_with_debug_writer<Writer: std::fmt::Write + 'static>(
+    pub fn new_with_debug_writer<Writer: std::fmt::Write + Send + 'static>(
         formula: Formula<Config::BitSet>,
         debug_writer: Option<Writer>,
     ) -> Self {
@@ -1235,7 +3367,17 @@ impl<Config: ConfigT> State<Config> {
         Self::new_from_vec_with_debug_writer::<String>(formula, None)
     }
 
-    pub fn new_from_vec_with_debug_writer<Writer: std::fmt::Write + 'static>(
+    /// Build a solver straight from an iterator of clauses, e.g. an encoding
+    /// generated on the fly.
+    pub fn from_clauses<Lits: IntoIterator<Item = isize>>(
+        clauses: impl IntoIterator<Item = Lits>,
+    ) -> Self {
+        let mut bitset_pool = Pool::new();
+        let formula = Formula::new(clauses, &mut bitset_pool);
+        Self::new_with_pool_and_debug_writer::<String>(formula, bitset_pool, None)
+    }
+
+    pub fn new_from_vec_with_debug_writer<Writer: std::fmt::Write + Send + 'static>(
         formula: Vec<Vec<isize>>,
         debug_writer: Option<Writer>,
     ) -> Self {
@@ -1244,7 +3386,7 @@ impl<Config: ConfigT> State<Config> {
         Self::new_with_pool_and_debug_writer(formula, bitset_pool, debug_writer)
     }
 
-    pub fn solve_with_debug_writer_and_assumptions<Writer: std::fmt::Write + 'static>(
+    pub fn solve_with_debug_writer_and_assumptions<Writer: std::fmt::Write + Send + 'static>(
         formula: Vec<Vec<isize>>,
         assumptions: &[isize],
         debug_writer: Option<Writer>,
@@ -1257,7 +3399,7 @@ impl<Config: ConfigT> State<Config> {
         Self::solve_with_debug_writer_and_assumptions::<String>(formula, assumptions, None)
     }
 
-    pub fn solve_with_debug_writer<Writer: std::fmt::Write + 'static>(
+    pub fn solve_with_debug_writer<Writer: std::fmt::Write + Send + 'static>(
         formula: Vec<Vec<isize>>,
         debug_writer: Option<Writer>,
     ) -> SatResult {
@@ -1268,6 +3410,195 @@ impl<Config: ConfigT> State<Config> {
     pub fn solve(formula: Vec<Vec<isize>>) -> SatResult {
         Self::solve_with_debug_writer::<String>(formula, None)
     }
+
+    /// Solve `formula` on a worker thread, cancelling it if it takes longer
+    /// than `timeout`. The worker is always joined: it notices the
+    /// cancellation within a step of the search, or once the 2-SAT or Horn
+    /// fast path finishes if the formula takes one (see `set_cancel_flag`).
+    pub fn solve_with_timeout(formula: Vec<Vec<isize>>, timeout: Duration) -> SolveOutcome
+    where
+        Self: Send + 'static,
+    {
+        let start = Instant::now();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let worker = {
+            let cancel = Arc::clone(&cancel);
+            std::thread::spawn(move || {
+                let mut state = Self::new_from_vec(formula);
+                state.set_cancel_flag(Some(cancel));
+                let result = state.run();
+                // the receiver is gone if it already timed out
+                let _ = sender.send(());
+                (result, state.stats())
+            })
+        };
+        let timed_out = receiver.recv_timeout(timeout).is_err();
+        cancel.store(true, Ordering::Relaxed);
+        let (result, stats) = match worker.join() {
+            Ok((_, stats)) if timed_out => (SatResult::Unknown, stats),
+            Ok(joined) => joined,
+            Err(panic) => std::panic::resume_unwind(panic),
+        };
+        SolveOutcome {
+            result,
+            timed_out,
+            elapsed: start.elapsed(),
+            stats,
+        }
+    }
+
+    /// Solve `formula` under each of `cubes`, given as assumptions, on
+    /// rayon's thread pool with a fresh solver per cube. Once one cube is
+    /// satisfiable the rest are cancelled. If the cubes cover every
+    /// assignment, say all the sign combinations of a few variables, the
+    /// formula is unsatisfiable exactly when every cube is.
+    #[cfg(feature = "rayon")]
+    pub fn solve_cubes(formula: &[Vec<isize>], cubes: &[Vec<isize>]) -> CubeOutcome {
+        use rayon::prelude::*;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let solved = cubes
+            .par_iter()
+            .map(|cube| {
+                if cancel.load(Ordering::Relaxed) {
+                    return (SatResult::Unknown, Stats::default());
+                }
+                let mut state =
+                    Self::from_clauses(formula.iter().map(|clause| clause.iter().copied()));
+                state.set_cancel_flag(Some(Arc::clone(&cancel)));
+                let result = state.run_with_assumptions(cube);
+                if let SatResult::Sat(_) = result {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+                (result, state.stats())
+            })
+            .collect::<Vec<_>>();
+        let mut result = SatResult::UnsatCore(vec![]);
+        let mut stats = Stats::default();
+        for (cube_result, cube_stats) in solved {
+            stats += cube_stats;
+            result = match (result, cube_result) {
+                (res @ SatResult::Sat(_), _) | (_, res @ SatResult::Sat(_)) => res,
+                (SatResult::Unknown, _) | (_, SatResult::Unknown) => SatResult::Unknown,
+                (res, SatResult::UnsatCore(_)) => res,
+            };
+        }
+        CubeOutcome { result, stats }
+    }
+}
+
+/// The order assumptions are decided in, see
+/// `State::set_assumption_order_policy`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AssumptionOrder {
+    /// As passed in.
+    #[default]
+    AsGiven,
+    /// Highest VSIDS activity first.
+    Activity,
+    /// Those in the most recent unsat core first, then by activity.
+    LastFailure,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AssumptionError {
+    /// The variable doesn't occur in any clause of the solver.
+    UnknownVariable(usize),
+}
+
+/// Iterator over the models of a [`State`]. The blocking clause for a model
+/// is only added when the next one is asked for (or when the iterator is
+/// dropped), so the solver can be inspected in between. Once the iterator is
+/// dropped every model it yielded stays blocked. Progress shows in the
+/// solver's stats and as `SearchEvent`s.
+pub struct Models<'a, Config: ConfigT> {
+    solver: &'a mut State<Config>,
+    projection: Option<Vec<usize>>,
+    blocking_clause: Option<Vec<isize>>,
+    done: bool,
+    exhausted: bool,
+}
+
+impl<'a, Config: ConfigT> Models<'a, Config> {
+    /// Only tell models apart by `vars`, yielding them projected onto
+    /// `vars`.
+    pub fn project(mut self, vars: &[usize]) -> Self {
+        self.projection = Some(vars.to_vec());
+        self
+    }
+
+    /// Whether every model has been yielded, as opposed to the enumeration
+    /// not having got that far or having given up on a memory limit.
+    pub fn is_exhausted(&self) -> bool {
+        self.exhausted
+    }
+
+    fn add_blocking_clause(&mut self) {
+        if let Some(clause) = self.blocking_clause.take() {
+            self.solver.add_clause(clause);
+            self.solver.stats.blocking_clauses += 1;
+        }
+    }
+
+    fn exhaust(&mut self) {
+        self.done = true;
+        self.exhausted = true;
+        self.solver.emit(SearchEvent::Exhausted);
+    }
+}
+
+impl<'a, Config: ConfigT> Iterator for Models<'a, Config> {
+    type Item = BTreeMap<usize, bool>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        self.add_blocking_clause();
+        let model = match self.solver.run() {
+            SatResult::UnsatCore(_) => {
+                self.exhaust();
+                return None;
+            }
+            SatResult::Unknown => {
+                self.done = true;
+                return None;
+            }
+            SatResult::Sat(model) => model,
+        };
+        self.solver.stats.models_found += 1;
+        self.solver.emit(SearchEvent::Model);
+        let model = match &self.projection {
+            None => model,
+            Some(vars) => vars
+                .iter()
+                .map(|&var| (var, model.get(&var).copied().unwrap_or(false)))
+                .collect(),
+        };
+        if model.is_empty() {
+            // the only model there is
+            self.exhaust();
+        } else {
+            // variables in no clause are always false, so blocking on them
+            // would only bring them into the problem
+            let known =
+                |var: &usize| self.projection.is_some() || self.solver.all_variables.contains(*var);
+            self.blocking_clause = Some(
+                model
+                    .iter()
+                    .filter(|(var, _)| known(var))
+                    .map(|(&var, &value)| Literal::new(var, !value).into())
+                    .collect(),
+            );
+        }
+        Some(model)
+    }
+}
+
+impl<'a, Config: ConfigT> Drop for Models<'a, Config> {
+    fn drop(&mut self) {
+        self.add_blocking_clause();
+    }
 }
 
 pub struct RandomConfig {}
@@ -1275,6 +3606,13 @@ pub struct RandomConfigDebug {}
 
 pub struct VsidsConfig {}
 pub struct VsidsConfigDebug {}
+/// `VsidsConfig` with the scores kept in an `AvlTree` instead of a
+/// `BTreeSet`.
+pub struct VsidsAvlConfig {}
+/// `VsidsConfig` with bitsets that spill to memory-mapped files, for
+/// instances whose occurrence bitsets don't fit in RAM.
+#[cfg(all(feature = "mmap", unix))]
+pub struct VsidsMmapConfig {}
 
 fn choose_random_literal<T: ConfigT>(state: &mut State<T>) -> Option<Literal> {
     let len = state.unassigned_variables.count();
@@ -1286,7 +3624,7 @@ fn choose_random_literal<T: ConfigT>(state: &mut State<T>) -> Option<Literal> {
             None => panic!("unassigned_variables should have been non-empty, but was empty"),
             Some(var) => {
                 let value = state.rng.random_ratio(1, 2);
-                Some(Literal::new(var, value))
+                Some(state.with_phase(Literal::new(var, value)))
             }
         }
     }
@@ -1296,38 +3634,45 @@ fn choose_vsids_literal<T: ConfigT>(state: &mut State<T>) -> Option<Literal> {
     state
         .literal_by_score
         .last()
-        .map(|(_, literal)| literal.clone())
+        .map(|(_, literal)| state.with_phase(*literal))
 }
 
 impl ConfigT for RandomConfig {
     type BitSet = fixed_bitset::BitSet;
+    type ScoreOrder = BTreeSet<(OrderedFloat<f64>, Literal)>;
 
     fn choose_literal(state: &mut State<Self>) -> Option<Literal> {
         choose_random_literal(state)
     }
 
+    const HEURISTIC: &'static str = "random";
     const DEBUG: bool = false;
     const CHECK_RESULTS: bool = false;
 }
 
 impl ConfigT for RandomConfigDebug {
     type BitSet = fixed_bitset::BitSet;
+    type ScoreOrder = BTreeSet<(OrderedFloat<f64>, Literal)>;
 
     fn choose_literal(state: &mut State<Self>) -> Option<Literal> {
         choose_random_literal(state)
     }
 
+    const HEURISTIC: &'static str = "random";
     const DEBUG: bool = true;
     const CHECK_RESULTS: bool = true;
+    const CHECK_INVARIANTS: bool = true;
 }
 
 impl ConfigT for VsidsConfig {
     type BitSet = fixed_bitset::BitSet;
+    type ScoreOrder = BTreeSet<(OrderedFloat<f64>, Literal)>;
 
     fn choose_literal(state: &mut State<Self>) -> Option<Literal> {
         choose_vsids_literal(state)
     }
 
+    const HEURISTIC: &'static str = "vsids";
     const DEBUG: bool = false;
     // const CHECK_RESULTS: bool = false;
     const CHECK_RESULTS: bool = true;
@@ -1335,13 +3680,43 @@ impl ConfigT for VsidsConfig {
 
 impl ConfigT for VsidsConfigDebug {
     type BitSet = fixed_bitset::BitSet;
+    type ScoreOrder = BTreeSet<(OrderedFloat<f64>, Literal)>;
 
     fn choose_literal(state: &mut State<Self>) -> Option<Literal> {
         choose_vsids_literal(state)
     }
     
+    const HEURISTIC: &'static str = "vsids";
     const DEBUG: bool = true;
     const CHECK_RESULTS: bool = true;
+    const CHECK_INVARIANTS: bool = true;
+}
+
+impl ConfigT for VsidsAvlConfig {
+    type BitSet = fixed_bitset::BitSet;
+    type ScoreOrder = AvlTree<(OrderedFloat<f64>, Literal), ()>;
+
+    fn choose_literal(state: &mut State<Self>) -> Option<Literal> {
+        choose_vsids_literal(state)
+    }
+
+    const HEURISTIC: &'static str = "vsids";
+    const DEBUG: bool = false;
+    const CHECK_RESULTS: bool = true;
+}
+
+#[cfg(all(feature = "mmap", unix))]
+impl ConfigT for VsidsMmapConfig {
+    type BitSet = crate::mmap_storage::MmapBitSet;
+    type ScoreOrder = BTreeSet<(OrderedFloat<f64>, Literal)>;
+
+    fn choose_literal(state: &mut State<Self>) -> Option<Literal> {
+        choose_vsids_literal(state)
+    }
+
+    const HEURISTIC: &'static str = "vsids";
+    const DEBUG: bool = false;
+    const CHECK_RESULTS: bool = true;
 }
 
 // pub type Default = State<RandomConfig>;
@@ -0,0 +1,109 @@
+use crate::cdcl::{ConfigT, State};
+use crate::sat::{Literal, SatResult};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ClauseGroup(usize);
+
+#[derive(Debug)]
+pub enum GroupResult {
+    Sat(BTreeMap<usize, bool>),
+    UnsatCore(Vec<ClauseGroup>),
+    Unknown,
+}
+
+/// Hands out activation literals for groups of clauses. Every clause added
+/// through a group `g` is stored as `clause ∨ ¬s_g`, so assuming `s_g` turns
+/// the group on and assuming `¬s_g` turns it off.
+///
+/// Selectors are allocated above `first_free_var`, so callers must not use
+/// variables at or above it for their own clauses.
+pub struct ClauseGroups {
+    first_free_var: usize,
+    non_empty: Vec<bool>,
+}
+
+impl ClauseGroups {
+    pub fn new(first_free_var: usize) -> Self {
+        ClauseGroups {
+            first_free_var,
+            non_empty: vec![],
+        }
+    }
+
+    pub fn new_group(&mut self) -> ClauseGroup {
+        self.non_empty.push(false);
+        ClauseGroup(self.non_empty.len() - 1)
+    }
+
+    pub fn selector(&self, group: ClauseGroup) -> Literal {
+        Literal::new(self.first_free_var + group.0, true)
+    }
+
+    fn group_of_selector(&self, literal: Literal) -> Option<ClauseGroup> {
+        let var = literal.variable();
+        if var >= self.first_free_var && var - self.first_free_var < self.non_empty.len() {
+            Some(ClauseGroup(var - self.first_free_var))
+        } else {
+            None
+        }
+    }
+
+    pub fn add_clause<Config: ConfigT>(
+        &mut self,
+        state: &mut State<Config>,
+        group: ClauseGroup,
+        mut clause: Vec<isize>,
+    ) {
+        self.non_empty[group.0] = true;
+        clause.push(self.selector(group).negate().into());
+        state.add_clause(clause);
+    }
+
+    /// Assumptions enabling exactly the groups in `enabled`. Groups that never
+    /// received a clause are left out, since the solver has not seen their
+    /// selectors.
+    pub fn assumptions(&self, enabled: &[ClauseGroup]) -> Vec<isize> {
+        (0..self.non_empty.len())
+            .filter(|&idx| self.non_empty[idx])
+            .map(|idx| {
+                let selector = self.selector(ClauseGroup(idx));
+                let selector = if enabled.contains(&ClauseGroup(idx)) {
+                    selector
+                } else {
+                    selector.negate()
+                };
+                selector.into()
+            })
+            .collect()
+    }
+
+    /// Solve with only the groups in `enabled` active (clauses added directly
+    /// to `state` are always active). Models have the selectors stripped and
+    /// cores are reported as the groups whose selectors were involved.
+    pub fn run<Config: ConfigT>(
+        &self,
+        state: &mut State<Config>,
+        enabled: &[ClauseGroup],
+    ) -> GroupResult {
+        match state.run_with_assumptions(&self.assumptions(enabled)) {
+            SatResult::Sat(model) => GroupResult::Sat(
+                model
+                    .into_iter()
+                    .filter(|&(var, _)| var < self.first_free_var)
+                    .collect(),
+            ),
+            SatResult::UnsatCore(core) => {
+                let mut groups = core
+                    .into_iter()
+                    .filter(|lit| lit.value())
+                    .filter_map(|lit| self.group_of_selector(lit))
+                    .collect::<Vec<_>>();
+                groups.sort();
+                groups.dedup();
+                GroupResult::UnsatCore(groups)
+            }
+            SatResult::Unknown => GroupResult::Unknown,
+        }
+    }
+}
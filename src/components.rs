@@ -0,0 +1,39 @@
+use std::collections::HashMap;
+
+/// Find the representative of `idx`, halving paths on the way up.
+fn find(parent: &mut [usize], mut idx: usize) -> usize {
+    while parent[idx] != idx {
+        parent[idx] = parent[parent[idx]];
+        idx = parent[idx];
+    }
+    idx
+}
+
+/// Group `clauses` into connected components, two clauses being connected
+/// when they share a variable. Each component is a list of clause indices
+/// in increasing order, and the components are ordered by their first
+/// clause. The components share no variables, so the models of the whole
+/// are exactly the combinations of models of each component.
+pub fn connected_components(clauses: &[Vec<isize>]) -> Vec<Vec<usize>> {
+    let mut parent = (0..clauses.len()).collect::<Vec<_>>();
+    let mut first_clause_by_var = HashMap::new();
+    for (idx, clause) in clauses.iter().enumerate() {
+        for lit in clause {
+            let other = *first_clause_by_var.entry(lit.unsigned_abs()).or_insert(idx);
+            let (a, b) = (find(&mut parent, idx), find(&mut parent, other));
+            // the smaller index stays the root, so roots are first clauses
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+    let mut components = vec![];
+    let mut component_by_root = HashMap::new();
+    for idx in 0..clauses.len() {
+        let root = find(&mut parent, idx);
+        let component = *component_by_root.entry(root).or_insert_with(|| {
+            components.push(vec![]);
+            components.len() - 1
+        });
+        components[component].push(idx);
+    }
+    components
+}
@@ -0,0 +1,56 @@
+/// What a solver is running with, from `State::describe`, for benchmark
+/// logs to record exactly what produced a result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Description {
+    /// The crate version.
+    pub version: &'static str,
+    /// The `ConfigT` type.
+    pub config: &'static str,
+    pub heuristic: &'static str,
+    pub bitset: &'static str,
+    pub score_order: &'static str,
+    pub debug: bool,
+    pub restarts: String,
+    pub reduction: String,
+    pub vsids_decay: f64,
+    pub clause_decay: f64,
+}
+
+impl Description {
+    fn fields(&self) -> [(&'static str, String); 10] {
+        [
+            ("version", self.version.to_string()),
+            ("config", self.config.to_string()),
+            ("heuristic", self.heuristic.to_string()),
+            ("bitset", self.bitset.to_string()),
+            ("score_order", self.score_order.to_string()),
+            ("debug", self.debug.to_string()),
+            ("restarts", self.restarts.clone()),
+            ("reduction", self.reduction.clone()),
+            ("vsids_decay", self.vsids_decay.to_string()),
+            ("clause_decay", self.clause_decay.to_string()),
+        ]
+    }
+
+    /// Every field as a JSON string.
+    pub fn to_json(&self) -> String {
+        let fields = self
+            .fields()
+            .iter()
+            .map(|(name, value)| format!("\"{}\":{:?}", name, value))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// One DIMACS comment line per field, for the header of the CLI's output.
+impl std::fmt::Display for Description {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let lines = self
+            .fields()
+            .iter()
+            .map(|(name, value)| format!("c {} {}", name, value))
+            .collect::<Vec<_>>();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
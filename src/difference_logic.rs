@@ -0,0 +1,201 @@
+use crate::cdcl::Theory;
+use crate::sat::Literal;
+use std::collections::BTreeMap;
+
+/// `x - y <= c` over integer-valued variables.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DifferenceConstraint {
+    pub x: usize,
+    pub y: usize,
+    pub c: i64,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    from: usize,
+    to: usize,
+    weight: i64,
+    reason: Literal,
+}
+
+struct ShortestPaths {
+    dist: Vec<Option<i64>>,
+    pred: Vec<Option<usize>>,
+}
+
+/// Difference logic over integers. Boolean variables are bound to atoms
+/// `x - y <= c`; a true atom is the edge `y → x` with weight `c` and a false
+/// one is `x - y >= c + 1`, the edge `x → y` with weight `-c - 1`. The trail is
+/// consistent iff this graph has no negative cycle.
+#[derive(Default)]
+pub struct DifferenceLogic {
+    atoms: BTreeMap<usize, DifferenceConstraint>,
+    num_int_vars: usize,
+}
+
+impl DifferenceLogic {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bind boolean variable `var` to the constraint `x - y <= c`.
+    pub fn add_atom(&mut self, var: usize, x: usize, y: usize, c: i64) {
+        self.num_int_vars = self.num_int_vars.max(x + 1).max(y + 1);
+        self.atoms.insert(var, DifferenceConstraint { x, y, c });
+    }
+
+    pub fn atom(&self, var: usize) -> Option<DifferenceConstraint> {
+        self.atoms.get(&var).copied()
+    }
+
+    fn edge(&self, literal: Literal) -> Option<Edge> {
+        let DifferenceConstraint { x, y, c } = self.atom(literal.variable())?;
+        Some(if literal.value() {
+            Edge {
+                from: y,
+                to: x,
+                weight: c,
+                reason: literal,
+            }
+        } else {
+            Edge {
+                from: x,
+                to: y,
+                weight: -c - 1,
+                reason: literal,
+            }
+        })
+    }
+
+    /// Bellman-Ford from `source`, or from a virtual source connected to every
+    /// node when `None`. Returns the first edge still relaxable after the full
+    /// number of rounds, if any, which lies on or leads to a negative cycle.
+    fn bellman_ford(
+        &self,
+        edges: &[Edge],
+        source: Option<usize>,
+    ) -> (ShortestPaths, Option<usize>) {
+        let n = self.num_int_vars;
+        let mut paths = ShortestPaths {
+            dist: match source {
+                None => vec![Some(0); n],
+                Some(source) => (0..n).map(|v| (v == source).then_some(0)).collect(),
+            },
+            pred: vec![None; n],
+        };
+        for _ in 0..n {
+            let mut changed = false;
+            for (idx, edge) in edges.iter().enumerate() {
+                if let Some(d) = paths.dist[edge.from] {
+                    if paths.dist[edge.to].is_none_or(|old| d + edge.weight < old) {
+                        paths.dist[edge.to] = Some(d + edge.weight);
+                        paths.pred[edge.to] = Some(idx);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                return (paths, None);
+            }
+        }
+        let relaxable = edges.iter().position(|edge| match paths.dist[edge.from] {
+            None => false,
+            Some(d) => paths.dist[edge.to].is_none_or(|old| d + edge.weight < old),
+        });
+        (paths, relaxable)
+    }
+
+    fn negative_cycle(
+        &self,
+        edges: &[Edge],
+        paths: &mut ShortestPaths,
+        relaxable: usize,
+    ) -> Vec<Edge> {
+        let edge = edges[relaxable];
+        paths.pred[edge.to] = Some(relaxable);
+        // walking back n steps is guaranteed to land on the cycle itself
+        let mut node = edge.to;
+        for _ in 0..self.num_int_vars {
+            node = edges[paths.pred[node].unwrap()].from;
+        }
+        let mut cycle = vec![];
+        let mut cur = node;
+        loop {
+            let edge = edges[paths.pred[cur].unwrap()];
+            cycle.push(edge);
+            cur = edge.from;
+            if cur == node {
+                return cycle;
+            }
+        }
+    }
+
+    fn path_reasons(
+        edges: &[Edge],
+        paths: &ShortestPaths,
+        source: usize,
+        target: usize,
+    ) -> Vec<Literal> {
+        let mut reasons = vec![];
+        let mut cur = target;
+        while cur != source {
+            let edge = edges[paths.pred[cur].unwrap()];
+            reasons.push(edge.reason);
+            cur = edge.from;
+        }
+        reasons
+    }
+
+    /// Integer values satisfying every atom assigned in `model`, if any.
+    pub fn values(&self, model: &BTreeMap<usize, bool>) -> Option<Vec<i64>> {
+        let edges = model
+            .iter()
+            .filter_map(|(&var, &value)| self.edge(Literal::new(var, value)))
+            .collect::<Vec<_>>();
+        match self.bellman_ford(&edges, None) {
+            (_, Some(_)) => None,
+            (paths, None) => Some(paths.dist.into_iter().map(|d| d.unwrap_or(0)).collect()),
+        }
+    }
+}
+
+impl Theory for DifferenceLogic {
+    fn propagate(&mut self, trail: &[Literal]) -> Vec<Vec<isize>> {
+        let edges = trail
+            .iter()
+            .filter_map(|&lit| self.edge(lit))
+            .collect::<Vec<_>>();
+        let (mut paths, relaxable) = self.bellman_ford(&edges, None);
+        if let Some(relaxable) = relaxable {
+            let cycle = self.negative_cycle(&edges, &mut paths, relaxable);
+            return vec![cycle
+                .iter()
+                .map(|edge| edge.reason.negate().into())
+                .collect()];
+        }
+
+        let assigned = trail.iter().map(|lit| lit.variable()).collect::<Vec<_>>();
+        let mut from_source: BTreeMap<usize, ShortestPaths> = BTreeMap::new();
+        let mut lemmas = vec![];
+        for (&var, &DifferenceConstraint { x, y, c }) in &self.atoms {
+            if assigned.contains(&var) {
+                continue;
+            }
+            for (implied, source, target, bound) in [(true, y, x, c), (false, x, y, -c - 1)] {
+                let paths = from_source
+                    .entry(source)
+                    .or_insert_with(|| self.bellman_ford(&edges, Some(source)).0);
+                if paths.dist[target].is_some_and(|d| d <= bound) {
+                    let mut lemma: Vec<isize> = Self::path_reasons(&edges, paths, source, target)
+                        .into_iter()
+                        .map(|lit| lit.negate().into())
+                        .collect();
+                    lemma.push(Literal::new(var, implied).into());
+                    lemmas.push(lemma);
+                    break;
+                }
+            }
+        }
+        lemmas
+    }
+}
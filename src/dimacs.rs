@@ -1,32 +1,206 @@
+use crate::sat::{check_literal, VariableTooLarge};
+use std::collections::{BTreeMap, VecDeque};
 use std::fs;
 use std::io;
 
+/// The nonzero literals of `line`, skipping unparsable tokens.
+fn parse_literals(line: &str) -> Result<Vec<isize>, VariableTooLarge> {
+    line.split_whitespace()
+        .filter_map(|tok| tok.parse::<isize>().ok())
+        .filter(|&lit| lit != 0)
+        .map(check_literal)
+        .collect()
+}
+
 /// Parse a DIMACS‐style string (with a leading header line) into a Vec of clauses,
-/// throwing away any zeros or unparsable tokens.
-pub fn read_string(s: &str) -> Vec<Vec<isize>> {
+/// throwing away any zeros or unparsable tokens. Fails on a variable too
+/// large for a `Literal`.
+pub fn read_string(s: &str) -> Result<Vec<Vec<isize>>, VariableTooLarge> {
     let mut lines = s.lines();
     // drop the header
     let _ = lines.next();
     let mut clauses = Vec::new();
 
     for line in lines {
-        let lits: Vec<isize> = line
-            .split_whitespace()
-            .filter_map(|tok| tok.parse::<isize>().ok())
-            .filter(|&lit| lit != 0)
-            .collect();
+        let lits = parse_literals(line)?;
         if !lits.is_empty() {
             clauses.push(lits);
         }
     }
 
-    clauses
+    Ok(clauses)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ModelError {
+    /// A token that isn't a literal.
+    BadToken(String),
+    /// Both polarities of this variable are given.
+    Contradiction(usize),
+    /// The `s` line says there's no model, e.g. `s UNSATISFIABLE`.
+    NoModel(String),
+}
+
+/// Parse a model as solvers print it: `v` lines of literals, or bare lines
+/// of them, skipping `c` comments and an `s SATISFIABLE` line. Variables
+/// that aren't mentioned are left out.
+pub fn read_model(s: &str) -> Result<BTreeMap<usize, bool>, ModelError> {
+    let mut model = BTreeMap::new();
+    for line in s.lines() {
+        let line = line.trim();
+        if line.starts_with('c') {
+            continue;
+        }
+        if line.starts_with('s') {
+            if line.split_whitespace().nth(1) != Some("SATISFIABLE") {
+                return Err(ModelError::NoModel(line.to_string()));
+            }
+            continue;
+        }
+        let line = line.strip_prefix('v').unwrap_or(line);
+        for tok in line.split_whitespace() {
+            let lit = tok
+                .parse::<isize>()
+                .map_err(|_| ModelError::BadToken(tok.to_string()))?;
+            if lit == 0 {
+                continue;
+            }
+            if model.insert(lit.unsigned_abs(), lit > 0) == Some(lit < 0) {
+                return Err(ModelError::Contradiction(lit.unsigned_abs()));
+            }
+        }
+    }
+    Ok(model)
 }
 
-/// Read an entire file and parse it as above.
+/// Open `path` for reading, decompressing `.gz` and `.xz` files. Each needs
+/// the matching `gzip`/`xz` feature; without it opening such a file fails.
+pub fn open(path: &str) -> io::Result<Box<dyn io::Read>> {
+    let file = fs::File::open(path)?;
+    if path.ends_with(".gz") {
+        #[cfg(feature = "gzip")]
+        return Ok(Box::new(flate2::read::MultiGzDecoder::new(file)));
+        #[cfg(not(feature = "gzip"))]
+        return Err(unsupported(path, "gzip"));
+    }
+    if path.ends_with(".xz") {
+        #[cfg(feature = "xz")]
+        return Ok(Box::new(xz2::read::XzDecoder::new_multi_decoder(file)));
+        #[cfg(not(feature = "xz"))]
+        return Err(unsupported(path, "xz"));
+    }
+    Ok(Box::new(file))
+}
+
+#[allow(dead_code)]
+fn unsupported(path: &str, feature: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{} is compressed, build with the {} feature to read it", path, feature),
+    )
+}
+
+fn read_to_string(path: &str) -> io::Result<String> {
+    let mut contents = String::new();
+    io::Read::read_to_string(&mut open(path)?, &mut contents)?;
+    Ok(contents)
+}
+
+/// Read an entire file (possibly compressed, see [`open`]) and parse it as
+/// above.
 pub fn read_file(path: &str) -> io::Result<Vec<Vec<isize>>> {
-    let contents = fs::read_to_string(path)?;
-    Ok(read_string(&contents))
+    let contents = read_to_string(path)?;
+    Ok(read_string(&contents)?)
+}
+
+/// Streams the clauses of a DIMACS file one at a time. Unlike
+/// [`read_string`], clauses are terminated by `0` and may span lines.
+pub struct ClauseReader<R: io::BufRead> {
+    reader: R,
+    line: String,
+    pending: Vec<isize>,
+    clauses: VecDeque<Vec<isize>>,
+}
+
+impl<R: io::BufRead> ClauseReader<R> {
+    pub fn new(reader: R) -> Self {
+        ClauseReader {
+            reader,
+            line: String::new(),
+            pending: Vec::new(),
+            clauses: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: io::BufRead> Iterator for ClauseReader<R> {
+    type Item = io::Result<Vec<isize>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.clauses.is_empty() {
+            self.line.clear();
+            match self.reader.read_line(&mut self.line) {
+                Err(err) => return Some(Err(err)),
+                Ok(0) if self.pending.is_empty() => return None,
+                Ok(0) => return Some(Ok(std::mem::take(&mut self.pending))),
+                Ok(_) => (),
+            }
+            let line = self.line.trim_start();
+            if line.starts_with('c') || line.starts_with('p') {
+                continue;
+            }
+            for lit in line
+                .split_whitespace()
+                .filter_map(|tok| tok.parse::<isize>().ok())
+            {
+                if let Err(err) = check_literal(lit) {
+                    return Some(Err(err.into()));
+                }
+                if lit == 0 {
+                    if !self.pending.is_empty() {
+                        self.clauses.push_back(std::mem::take(&mut self.pending));
+                    }
+                } else {
+                    self.pending.push(lit);
+                }
+            }
+        }
+        self.clauses.pop_front().map(Ok)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IncCnfLine {
+    Clause(Vec<isize>),
+    /// `a ... 0`: solve now under these assumptions.
+    Assume(Vec<isize>),
+}
+
+/// Parse a “p inccnf” string: clause lines interleaved with `a` assumption
+/// lines, each of which asks for a solve of the clauses seen so far.
+pub fn read_inccnf_string(s: &str) -> Result<Vec<IncCnfLine>, VariableTooLarge> {
+    let mut res = Vec::new();
+
+    for line in s.lines() {
+        let line = line.trim_start();
+        if line.starts_with('c') || line.starts_with('p') {
+            continue;
+        }
+        let lits = parse_literals(line)?;
+        if line.starts_with('a') {
+            res.push(IncCnfLine::Assume(lits));
+        } else if !lits.is_empty() {
+            res.push(IncCnfLine::Clause(lits));
+        }
+    }
+
+    Ok(res)
+}
+
+/// Read an entire file and parse it as an incremental CNF.
+pub fn read_inccnf_file(path: &str) -> io::Result<Vec<IncCnfLine>> {
+    let contents = read_to_string(path)?;
+    Ok(read_inccnf_string(&contents)?)
 }
 
 /// Given a slice of clauses (Vec<Vec<isize>>), emit a DIMACS “p cnf …” string.
@@ -52,6 +226,37 @@ pub fn of_int_array_array(arr: &[Vec<isize>]) -> String {
     lines.join("\n")
 }
 
+/// The bundled example instances, by name with their DIMACS text, for
+/// tests and benchmarks to run over.
+#[cfg(feature = "examples")]
+pub fn examples() -> impl Iterator<Item = (&'static str, &'static str)> {
+    [
+        ("sudoku", SUDOKU),
+        ("fail_eg", FAIL_EG),
+        ("succ_eg", SUCC_EG),
+        ("factor_1234321", FACTOR_1234321),
+        ("factor_1235321", FACTOR_1235321),
+        ("subsets_100", SUBSETS_100),
+    ]
+    .into_iter()
+}
+
+/// The DIMACS text of the bundled instance `name`.
+#[cfg(feature = "examples")]
+pub fn example(name: &str) -> Option<&'static str> {
+    examples()
+        .find(|&(other, _)| other == name)
+        .map(|(_, text)| text)
+}
+
+/// The clauses of the bundled instance `name`, which must exist.
+#[cfg(feature = "examples")]
+pub fn read_example(name: &str) -> Vec<Vec<isize>> {
+    let text = example(name).unwrap_or_else(|| panic!("no example instance {}", name));
+    read_string(text).expect("the bundled instances have small variables")
+}
+
+#[cfg(feature = "examples")]
 pub const SUDOKU: &str = "\
 p cnf 729 3270
 1 2 3 4 5 6 7 8 9 0
@@ -3325,6 +3530,7 @@ p cnf 729 3270
 718 0
 729 0";
 
+#[cfg(feature = "examples")]
 pub const FAIL_EG: &str = "\
 p cnf 112 245
 -1 -2 0
@@ -3573,6 +3779,7 @@ p cnf 112 245
 -84 112 0
 -112 82 83 84 0";
 
+#[cfg(feature = "examples")]
 pub const SUCC_EG: &str = "\
 p cnf 140 301
 -1 -2 0
@@ -3877,6 +4084,7 @@ p cnf 140 301
 -105 140 0
 -140 103 104 105 0";
 
+#[cfg(feature = "examples")]
 pub const FACTOR_1234321: &str = "\
 p cnf 1433 7585
 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 0
@@ -11465,6 +11673,7 @@ p cnf 1433 7585
 1433 -43 0
 -1433 43 0";
 
+#[cfg(feature = "examples")]
 pub const FACTOR_1235321: &str = "\
 p cnf 1433 7585
 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 0
@@ -19053,6 +19262,7 @@ p cnf 1433 7585
 1433 -43 0
 -1433 43 0 ";
 
+#[cfg(feature = "examples")]
 pub const SUBSETS_100: &str = "\
 p cnf 3357 15212
 101 0
@@ -34307,7 +34517,7 @@ p cnf 3 2
 1 -3 4 0
 -2 3 0
 ";
-        let clauses = read_string(dimacs);
+        let clauses = read_string(dimacs).unwrap();
         assert_eq!(clauses, vec![vec![1, -3, 4], vec![-2, 3]]);
         let round = of_int_array_array(&clauses);
         let expect = "\
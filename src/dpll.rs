@@ -0,0 +1,80 @@
+use std::collections::BTreeMap;
+
+/// A plain recursive DPLL with unit propagation, simple enough to trust as a
+/// reference when checking the CDCL solver on small formulas. Returns a
+/// model of `clauses` in which every literal of `assumptions` holds.
+pub fn solve(clauses: &[Vec<isize>], assumptions: &[isize]) -> Option<BTreeMap<usize, bool>> {
+    let mut assignment = BTreeMap::new();
+    for &lit in assumptions {
+        if assignment.insert(lit.unsigned_abs(), lit > 0) == Some(lit <= 0) {
+            return None;
+        }
+    }
+    search(clauses, assignment)
+}
+
+fn value(assignment: &BTreeMap<usize, bool>, lit: isize) -> Option<bool> {
+    assignment.get(&lit.unsigned_abs()).map(|&v| v == (lit > 0))
+}
+
+fn search(
+    clauses: &[Vec<isize>],
+    mut assignment: BTreeMap<usize, bool>,
+) -> Option<BTreeMap<usize, bool>> {
+    // propagate units until nothing changes
+    loop {
+        let mut changed = false;
+        for clause in clauses {
+            let mut unassigned = None;
+            let mut num_unassigned = 0;
+            let mut satisfied = false;
+            for &lit in clause {
+                match value(&assignment, lit) {
+                    Some(true) => {
+                        satisfied = true;
+                        break;
+                    }
+                    Some(false) => (),
+                    None => {
+                        num_unassigned += 1;
+                        unassigned = Some(lit);
+                    }
+                }
+            }
+            if satisfied {
+                continue;
+            }
+            match (num_unassigned, unassigned) {
+                (0, _) => return None,
+                (1, Some(lit)) => {
+                    assignment.insert(lit.unsigned_abs(), lit > 0);
+                    changed = true;
+                }
+                _ => (),
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    let branch = clauses
+        .iter()
+        .filter(|clause| {
+            !clause
+                .iter()
+                .any(|&lit| value(&assignment, lit) == Some(true))
+        })
+        .flatten()
+        .find(|&&lit| value(&assignment, lit).is_none());
+    let Some(&lit) = branch else {
+        return Some(assignment);
+    };
+    for choice in [lit > 0, lit <= 0] {
+        let mut assignment = assignment.clone();
+        assignment.insert(lit.unsigned_abs(), choice);
+        if let Some(model) = search(clauses, assignment) {
+            return Some(model);
+        }
+    }
+    None
+}
@@ -0,0 +1,130 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofStep {
+    Add(Vec<isize>),
+    Delete(Vec<isize>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CheckError {
+    /// The added clause at this index of the proof is not implied by unit
+    /// propagation.
+    NotRup(usize),
+    NoEmptyClause,
+}
+
+/// Parse a DRUP proof: one clause per line, deletions prefixed with `d`.
+pub fn read_string(s: &str) -> Vec<ProofStep> {
+    let mut steps = Vec::new();
+    for line in s.lines() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        let lits: Vec<isize> = line
+            .split_whitespace()
+            .filter_map(|tok| tok.parse::<isize>().ok())
+            .filter(|&lit| lit != 0)
+            .collect();
+        if line.starts_with('d') {
+            steps.push(ProofStep::Delete(lits));
+        } else {
+            steps.push(ProofStep::Add(lits));
+        }
+    }
+    steps
+}
+
+pub fn read_file(path: &str) -> io::Result<Vec<ProofStep>> {
+    let contents = fs::read_to_string(path)?;
+    Ok(read_string(&contents))
+}
+
+/// Whether assuming every literal in `assumptions` and unit propagating over
+/// `clauses` reaches a conflict.
+fn propagates_to_conflict(clauses: &[Option<Vec<isize>>], assumptions: &[isize]) -> bool {
+    let mut assignment: BTreeMap<usize, bool> = BTreeMap::new();
+    for &lit in assumptions {
+        if assignment.insert(lit.unsigned_abs(), lit > 0) == Some(lit <= 0) {
+            return true;
+        }
+    }
+    loop {
+        let mut changed = false;
+        for clause in clauses.iter().flatten() {
+            let mut unassigned = None;
+            let mut num_unassigned = 0;
+            let mut satisfied = false;
+            for &lit in clause {
+                match assignment.get(&lit.unsigned_abs()) {
+                    None => {
+                        unassigned = Some(lit);
+                        num_unassigned += 1;
+                    }
+                    Some(&value) if value == (lit > 0) => {
+                        satisfied = true;
+                        break;
+                    }
+                    Some(_) => (),
+                }
+            }
+            match (satisfied, num_unassigned, unassigned) {
+                (true, _, _) => (),
+                (false, 0, _) => return true,
+                (false, 1, Some(lit)) => {
+                    assignment.insert(lit.unsigned_abs(), lit > 0);
+                    changed = true;
+                }
+                _ => (),
+            }
+        }
+        if !changed {
+            return false;
+        }
+    }
+}
+
+fn normalize(clause: &[isize]) -> Vec<isize> {
+    let mut clause = clause.to_vec();
+    clause.sort();
+    clause.dedup();
+    clause
+}
+
+/// Check that `proof` refutes `formula`: every added clause must be a
+/// reverse unit propagation consequence of the clauses before it (minus the
+/// deleted ones), and the empty clause must be derived. This is plain
+/// propagation over all clauses, meant for checking small proofs.
+pub fn check(formula: &[Vec<isize>], proof: &[ProofStep]) -> Result<(), CheckError> {
+    let mut clauses = formula
+        .iter()
+        .map(|clause| Some(normalize(clause)))
+        .collect::<Vec<_>>();
+    if clauses.iter().flatten().any(|clause| clause.is_empty()) {
+        return Ok(());
+    }
+    for (idx, step) in proof.iter().enumerate() {
+        match step {
+            ProofStep::Add(clause) => {
+                let negated = clause.iter().map(|lit| -lit).collect::<Vec<_>>();
+                if !propagates_to_conflict(&clauses, &negated) {
+                    return Err(CheckError::NotRup(idx));
+                }
+                if clause.is_empty() {
+                    return Ok(());
+                }
+                clauses.push(Some(normalize(clause)));
+            }
+            ProofStep::Delete(clause) => {
+                let clause = normalize(clause);
+                if let Some(existing) = clauses.iter_mut().find(|c| c.as_ref() == Some(&clause)) {
+                    *existing = None;
+                }
+            }
+        }
+    }
+    Err(CheckError::NoEmptyClause)
+}
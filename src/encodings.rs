@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+
+/// A factoring instance: `clauses` are satisfiable exactly when `n` is the
+/// product of two factors that fit in `x` and `y`, neither of them 1.
+#[derive(Clone, Debug)]
+pub struct Factoring {
+    pub clauses: Vec<Vec<isize>>,
+    /// The variables of the first factor's bits, least significant first.
+    pub x: Vec<isize>,
+    /// The variables of the second factor's bits, least significant first.
+    pub y: Vec<isize>,
+}
+
+impl Factoring {
+    /// Read the two factors out of a model of `clauses`.
+    pub fn factors(&self, model: &BTreeMap<usize, bool>) -> (u64, u64) {
+        let decode = |bits: &[isize]| {
+            bits.iter().enumerate().fold(0u64, |acc, (idx, var)| {
+                if model.get(&(*var as usize)).copied().unwrap_or(false) {
+                    acc | 1 << idx
+                } else {
+                    acc
+                }
+            })
+        };
+        (decode(&self.x), decode(&self.y))
+    }
+}
+
+/// Tseitin-encodes gates, each output being a fresh variable.
+struct Circuit {
+    clauses: Vec<Vec<isize>>,
+    num_vars: isize,
+}
+
+impl Circuit {
+    fn fresh(&mut self) -> isize {
+        self.num_vars += 1;
+        self.num_vars
+    }
+
+    fn and(&mut self, a: isize, b: isize) -> isize {
+        let g = self.fresh();
+        self.clauses.push(vec![-g, a]);
+        self.clauses.push(vec![-g, b]);
+        self.clauses.push(vec![g, -a, -b]);
+        g
+    }
+
+    fn xor(&mut self, a: isize, b: isize) -> isize {
+        let g = self.fresh();
+        self.clauses.push(vec![-g, a, b]);
+        self.clauses.push(vec![-g, -a, -b]);
+        self.clauses.push(vec![g, -a, b]);
+        self.clauses.push(vec![g, a, -b]);
+        g
+    }
+
+    fn xor3(&mut self, a: isize, b: isize, c: isize) -> isize {
+        let g = self.fresh();
+        // g is set exactly when an odd number of the inputs are
+        for mask in 0..8 {
+            let signs = [mask & 1 != 0, mask & 2 != 0, mask & 4 != 0];
+            let odd = signs.iter().filter(|&&set| set).count() % 2 == 1;
+            let mut clause = [a, b, c]
+                .into_iter()
+                .zip(signs)
+                .map(|(lit, set)| if set { -lit } else { lit })
+                .collect::<Vec<_>>();
+            clause.push(if odd { g } else { -g });
+            self.clauses.push(clause);
+        }
+        g
+    }
+
+    fn majority(&mut self, a: isize, b: isize, c: isize) -> isize {
+        let g = self.fresh();
+        for (p, q) in [(a, b), (a, c), (b, c)] {
+            self.clauses.push(vec![-g, p, q]);
+            self.clauses.push(vec![g, -p, -q]);
+        }
+        g
+    }
+
+    /// The sum and carry bits of `a + b + c`. `None` bits are constantly
+    /// false, so the adder folds down to a half adder or a wire.
+    fn add(
+        &mut self,
+        a: Option<isize>,
+        b: Option<isize>,
+        c: Option<isize>,
+    ) -> (Option<isize>, Option<isize>) {
+        match [a, b, c].into_iter().flatten().collect::<Vec<_>>()[..] {
+            [] => (None, None),
+            [a] => (Some(a), None),
+            [a, b] => (Some(self.xor(a, b)), Some(self.and(a, b))),
+            [a, b, c] => (Some(self.xor3(a, b, c)), Some(self.majority(a, b, c))),
+            _ => unreachable!(),
+        }
+    }
+}
+
+/// Encode factoring `n` as a CNF: an array multiplier of two `bits`-bit
+/// factors whose product is constrained to `n`, with both factors at least
+/// 2. Satisfiable exactly when `n` has such a factorisation, so composites
+/// give instances like `dimacs`'s `factor_1234321` and primes unsatisfiable
+/// ones.
+pub fn factoring(n: u64, bits: usize) -> Factoring {
+    assert!(
+        (1..=32).contains(&bits),
+        "factors need 1 to 32 bits, not {}",
+        bits
+    );
+    let mut circuit = Circuit {
+        clauses: vec![],
+        num_vars: 0,
+    };
+    let x = (0..bits).map(|_| circuit.fresh()).collect::<Vec<_>>();
+    let y = (0..bits).map(|_| circuit.fresh()).collect::<Vec<_>>();
+
+    // shift-and-add: the running sum gains row i, x * y_i shifted by i
+    let mut product: Vec<Option<isize>> = vec![None; 2 * bits];
+    for (i, &y_i) in y.iter().enumerate() {
+        let mut carry = None;
+        for (k, bit) in product.iter_mut().enumerate().skip(i) {
+            let row = x.get(k - i).map(|&x_j| circuit.and(x_j, y_i));
+            let (sum, carry_out) = circuit.add(*bit, row, carry);
+            *bit = sum;
+            carry = carry_out;
+        }
+        // the product of two bits-bit numbers fits in 2 * bits bits
+        if let Some(carry) = carry {
+            circuit.clauses.push(vec![-carry]);
+        }
+    }
+
+    for (k, bit) in product.iter().enumerate() {
+        let set = k < 64 && n >> k & 1 == 1;
+        match bit {
+            Some(var) => circuit.clauses.push(vec![if set { *var } else { -var }]),
+            None if set => circuit.clauses.push(vec![]),
+            None => (),
+        }
+    }
+    if 2 * bits < 64 && n >> (2 * bits) != 0 {
+        circuit.clauses.push(vec![]);
+    }
+    // neither factor is 0 or 1: each has a bit set above its lowest
+    circuit.clauses.push(x[1..].to_vec());
+    circuit.clauses.push(y[1..].to_vec());
+
+    Factoring {
+        clauses: circuit.clauses,
+        x,
+        y,
+    }
+}
+
+/// An `n` queens instance: a queen on each row, and at most one on each
+/// row, column and diagonal. The at-most-ones are kept as groups for
+/// `State::add_at_most_one`; `to_cnf` spells them out as clauses.
+#[derive(Clone, Debug)]
+pub struct NQueens {
+    pub n: usize,
+    pub clauses: Vec<Vec<isize>>,
+    pub at_most_ones: Vec<Vec<isize>>,
+}
+
+impl NQueens {
+    /// The variable of a queen on `row` and `col`, both from 0.
+    pub fn var(&self, row: usize, col: usize) -> isize {
+        (row * self.n + col + 1) as isize
+    }
+
+    /// The clauses plus each at-most-one as pairwise binary clauses.
+    pub fn to_cnf(&self) -> Vec<Vec<isize>> {
+        let mut clauses = self.clauses.clone();
+        for group in &self.at_most_ones {
+            for (idx, &a) in group.iter().enumerate() {
+                for &b in &group[idx + 1..] {
+                    clauses.push(vec![-a, -b]);
+                }
+            }
+        }
+        clauses
+    }
+
+    /// Draw the board of a model, a line per row with `Q` for a queen and
+    /// `.` for an empty square.
+    pub fn board(&self, model: &BTreeMap<usize, bool>) -> String {
+        (0..self.n)
+            .map(|row| {
+                (0..self.n)
+                    .map(|col| {
+                        let var = self.var(row, col) as usize;
+                        if model.get(&var).copied().unwrap_or(false) {
+                            "Q"
+                        } else {
+                            "."
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Encode placing `n` mutually non-attacking queens on an `n` by `n` board.
+pub fn n_queens(n: usize) -> NQueens {
+    let mut queens = NQueens {
+        n,
+        clauses: vec![],
+        at_most_ones: vec![],
+    };
+    let squares = |pred: &dyn Fn(usize, usize) -> bool| {
+        (0..n * n)
+            .filter(|square| pred(square / n, square % n))
+            .map(|square| square as isize + 1)
+            .collect::<Vec<_>>()
+    };
+    for line in 0..n {
+        queens.clauses.push(squares(&|row, _| row == line));
+        queens.at_most_ones.push(squares(&|row, _| row == line));
+        queens.at_most_ones.push(squares(&|_, col| col == line));
+    }
+    // diagonals are constant in row - col, antidiagonals in row + col
+    for diagonal in 0..(2 * n).saturating_sub(1) {
+        let down = squares(&|row, col| row + n - 1 - col == diagonal);
+        let up = squares(&|row, col| row + col == diagonal);
+        queens
+            .at_most_ones
+            .extend([down, up].into_iter().filter(|group| group.len() > 1));
+    }
+    queens
+}
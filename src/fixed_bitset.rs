@@ -1,31 +1,249 @@
 use crate::bitset::BitSetT;
 use std::iter;
 
-/// Compact bitset backed by a flat vector of machine words.
+/// Loops over equal-length word slices, four words at a time with the
+/// `simd` feature.
+#[cfg(feature = "simd")]
+mod kernels {
+    use std::simd::num::SimdUint;
+    use std::simd::Simd;
+
+    type Block = Simd<usize, 4>;
+
+    fn zip_with(
+        dst: &mut [usize],
+        src: &[usize],
+        block: impl Fn(Block, Block) -> Block,
+        word: impl Fn(usize, usize) -> usize,
+    ) {
+        debug_assert_eq!(dst.len(), src.len());
+        let (dst_blocks, dst_rest) = dst.as_chunks_mut::<4>();
+        let (src_blocks, src_rest) = src.as_chunks::<4>();
+        for (d, s) in dst_blocks.iter_mut().zip(src_blocks) {
+            *d = block(Block::from_array(*d), Block::from_array(*s)).to_array();
+        }
+        for (d, s) in dst_rest.iter_mut().zip(src_rest) {
+            *d = word(*d, *s);
+        }
+    }
+
+    pub fn or_assign(dst: &mut [usize], src: &[usize]) {
+        zip_with(dst, src, |a, b| a | b, |a, b| a | b)
+    }
+
+    pub fn and_assign(dst: &mut [usize], src: &[usize]) {
+        zip_with(dst, src, |a, b| a & b, |a, b| a & b)
+    }
+
+    pub fn and_not_assign(dst: &mut [usize], src: &[usize]) {
+        zip_with(dst, src, |a, b| a & !b, |a, b| a & !b)
+    }
+
+    pub fn count_ones(words: &[usize]) -> usize {
+        let (blocks, rest) = words.as_chunks::<4>();
+        let head = blocks
+            .iter()
+            .map(|b| Block::from_array(*b).count_ones().reduce_sum())
+            .sum::<usize>();
+        head + rest.iter().map(|w| w.count_ones() as usize).sum::<usize>()
+    }
+
+    pub fn count_ones_and(a: &[usize], b: &[usize]) -> usize {
+        debug_assert_eq!(a.len(), b.len());
+        let (a_blocks, a_rest) = a.as_chunks::<4>();
+        let (b_blocks, b_rest) = b.as_chunks::<4>();
+        let head = a_blocks
+            .iter()
+            .zip(b_blocks)
+            .map(|(a, b)| {
+                (Block::from_array(*a) & Block::from_array(*b))
+                    .count_ones()
+                    .reduce_sum()
+            })
+            .sum::<usize>();
+        let tail = a_rest.iter().zip(b_rest);
+        head + tail
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum::<usize>()
+    }
+}
+
+#[cfg(not(feature = "simd"))]
+mod kernels {
+    pub fn or_assign(dst: &mut [usize], src: &[usize]) {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d |= s;
+        }
+    }
+
+    pub fn and_assign(dst: &mut [usize], src: &[usize]) {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d &= s;
+        }
+    }
+
+    pub fn and_not_assign(dst: &mut [usize], src: &[usize]) {
+        for (d, s) in dst.iter_mut().zip(src) {
+            *d &= !s;
+        }
+    }
+
+    pub fn count_ones(words: &[usize]) -> usize {
+        words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn count_ones_and(a: &[usize], b: &[usize]) -> usize {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+}
+
+/// Where a `BitSet` keeps its words: a slice that can be resized.
+pub trait BlockStorage:
+    std::ops::Deref<Target = [usize]> + std::ops::DerefMut + Clone + Default
+{
+    /// Resize to `words` words, zero-filling any new ones.
+    fn resize(&mut self, words: usize);
+
+    /// Give back memory beyond the current length, where that's possible.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Words of memory held, including any spare capacity.
+    fn memory_blocks(&self) -> usize {
+        self.len()
+    }
+}
+
+impl BlockStorage for Vec<usize> {
+    fn resize(&mut self, words: usize) {
+        Vec::resize(self, words, 0)
+    }
+
+    fn shrink_to_fit(&mut self) {
+        Vec::shrink_to_fit(self)
+    }
+
+    fn memory_blocks(&self) -> usize {
+        self.capacity()
+    }
+}
+
+/// `N` words inline, for small sets where allocating would dominate. The
+/// capacity is always `N` words; growing past it panics.
+#[derive(Clone, Copy, Debug)]
+pub struct ArrayStorage<const N: usize>([usize; N]);
+
+impl<const N: usize> std::default::Default for ArrayStorage<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+impl<const N: usize> std::ops::Deref for ArrayStorage<N> {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        &self.0
+    }
+}
+
+impl<const N: usize> std::ops::DerefMut for ArrayStorage<N> {
+    fn deref_mut(&mut self) -> &mut [usize] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> BlockStorage for ArrayStorage<N> {
+    fn resize(&mut self, words: usize) {
+        assert!(
+            words <= N,
+            "bitset of {} words can't grow to {} words",
+            N,
+            words
+        );
+    }
+}
+
+/// Compact bitset backed by a flat run of machine words, kept in `S`.
 #[derive(Clone, Default)]
-pub struct BitSet {
-    words: Vec<usize>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BlockBitSet<S> {
+    words: S,
+    /// Number of set bits, kept up to date once `cache_count` is called.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cached_count: Option<usize>,
 }
 
-impl BitSet {
-    /// Bits per machine word.
-    const BITS_PER_WORD: usize = usize::BITS as usize;
+/// The usual bitset, growing its words on the heap as bits are set.
+pub type BitSet = BlockBitSet<Vec<usize>>;
 
+/// A bitset of at most `N` words that lives on the stack.
+pub type SmallBitSet<const N: usize> = BlockBitSet<ArrayStorage<N>>;
+
+impl BitSet {
     /// Create a bitset with `words` zeroed words of capacity.
     pub fn new(words: usize) -> Self {
         Self {
             words: vec![0; words],
+            cached_count: None,
+        }
+    }
+
+    /// Wrap `words` as a bitset: bit `i` is bit `i % usize::BITS` of
+    /// `words[i / usize::BITS]`.
+    pub fn from_words(words: Vec<usize>) -> Self {
+        Self {
+            words,
+            cached_count: None,
         }
     }
 
+    /// Take back the underlying words.
+    pub fn into_words(self) -> Vec<usize> {
+        self.words
+    }
+}
+
+impl<S: BlockStorage> BlockBitSet<S> {
+    /// Bits per machine word.
+    const BITS_PER_WORD: usize = usize::BITS as usize;
+
+    /// The underlying words, in the layout taken by `from_words`.
+    pub fn as_words(&self) -> &[usize] {
+        &self.words
+    }
+
+    /// Mutable access to the underlying words. Capacity can't change. Stops
+    /// caching the count, since writes through the slice can't be tracked.
+    pub fn as_words_mut(&mut self) -> &mut [usize] {
+        self.cached_count = None;
+        &mut self.words
+    }
+
     /// Ensure capacity for at least `bits` bits. Does not shrink.
     pub fn grow(&mut self, bits: usize) {
         let needed_words = (bits + Self::BITS_PER_WORD - 1) / Self::BITS_PER_WORD;
         if needed_words > self.words.len() {
-            self.words.resize(needed_words, 0);
+            self.words.resize(needed_words);
         }
     }
 
+    /// Drop trailing all-zero words and release their memory, reducing the
+    /// capacity to just past the last set bit. `grow` and `set` never do
+    /// this, so a set that was briefly large keeps its words until now.
+    pub fn shrink_to_fit(&mut self) {
+        let len = self.significant_words().len();
+        self.words.resize(len);
+        self.words.shrink_to_fit();
+    }
+
+    /// Words of memory held by the set, including spare capacity.
+    pub fn memory_blocks(&self) -> usize {
+        self.words.memory_blocks()
+    }
+
     /// Total bits currently supported.
     pub fn capacity(&self) -> usize {
         self.words.len() * Self::BITS_PER_WORD
@@ -42,6 +260,9 @@ impl BitSet {
     pub fn set(&mut self, bit: usize) {
         self.grow(bit + 1);
         let (w, o) = Self::locate(bit);
+        if let Some(count) = self.cached_count.as_mut() {
+            *count += (!self.words[w] >> o) & 1;
+        }
         self.words[w] |= 1usize << o;
     }
 
@@ -51,14 +272,35 @@ impl BitSet {
             return;
         }
         let (w, o) = Self::locate(bit);
+        if let Some(count) = self.cached_count.as_mut() {
+            *count -= (self.words[w] >> o) & 1;
+        }
         self.words[w] &= !(1usize << o);
     }
 
-    /// Clear all bits to zero.
+    /// Clear all bits to zero. The words stay allocated, so a cleared set
+    /// can be refilled without reallocating; `shrink_to_fit` afterwards
+    /// releases them.
     pub fn clear_all(&mut self) {
-        for w in &mut self.words {
+        for w in self.words.iter_mut() {
             *w = 0;
         }
+        if self.cached_count.is_some() {
+            self.cached_count = Some(0);
+        }
+    }
+
+    /// Keep the number of set bits up to date from now on, so that `count`
+    /// doesn't scan the words. Single-bit updates adjust it in constant
+    /// time; whole-set operations recount.
+    pub fn cache_count(&mut self) {
+        self.cached_count = Some(kernels::count_ones(&self.words));
+    }
+
+    fn recount(&mut self) {
+        if self.cached_count.is_some() {
+            self.cache_count();
+        }
     }
 
     /// Test if a bit is set (no grow).
@@ -125,34 +367,86 @@ impl BitSet {
         None
     }
 
+    /// Find the last set bit, or `None`.
+    pub fn last_set(&self) -> Option<usize> {
+        self.first_set_le(usize::MAX)
+    }
+
+    /// Find last set ≤ `bit`.
+    pub fn first_set_le(&self, bit: usize) -> Option<usize> {
+        if self.words.is_empty() {
+            return None;
+        }
+        let (end_w, offset) = Self::locate(bit.min(self.capacity() - 1));
+
+        // Check within the ending word (mask out higher bits).
+        let w = self.words[end_w] & (!0usize >> (Self::BITS_PER_WORD - 1 - offset));
+        if w != 0 {
+            return Some(end_w * Self::BITS_PER_WORD + Self::highest_bit(w));
+        }
+
+        // Scan preceding words.
+        for (i, &word) in self.words[..end_w].iter().enumerate().rev() {
+            if word != 0 {
+                return Some(i * Self::BITS_PER_WORD + Self::highest_bit(word));
+            }
+        }
+        None
+    }
+
+    #[inline]
+    fn highest_bit(word: usize) -> usize {
+        Self::BITS_PER_WORD - 1 - word.leading_zeros() as usize
+    }
+
     /// In-place: `self |= other` (grows self if needed).
     pub fn union_with(&mut self, other: &Self) {
         if other.words.len() > self.words.len() {
-            self.words.resize(other.words.len(), 0);
-        }
-        for i in 0..other.words.len() {
-            self.words[i] |= other.words[i];
+            self.words.resize(other.words.len());
         }
+        let len = other.words.len();
+        kernels::or_assign(&mut self.words[..len], &other.words);
+        self.recount();
     }
 
     /// In-place: `self &= other` (no grow; clears extra words).
     pub fn intersect_with(&mut self, other: &Self) {
         let min = self.words.len().min(other.words.len());
-        for i in 0..min {
-            self.words[i] &= other.words[i];
-        }
+        kernels::and_assign(&mut self.words[..min], &other.words[..min]);
         for w in &mut self.words[min..] {
             *w = 0;
         }
+        self.recount();
     }
 
     /// In-place: `self &= !other` (no grow).
     pub fn difference_with(&mut self, other: &Self) {
         let min = self.words.len().min(other.words.len());
-        for i in 0..min {
-            self.words[i] &= !other.words[i];
-        }
+        kernels::and_not_assign(&mut self.words[..min], &other.words[..min]);
         // words beyond `other` remain as-is
+        self.recount();
+    }
+
+    /// In-place: `self = !self` up to capacity (no grow).
+    pub fn complement(&mut self) {
+        for w in self.words.iter_mut() {
+            *w = !*w;
+        }
+        self.recount();
+    }
+
+    /// Keep only the set bits for which `pred` holds, a word at a time.
+    pub fn retain(&mut self, mut pred: impl FnMut(usize) -> bool) {
+        for (i, w) in self.words.iter_mut().enumerate() {
+            let mut kept = *w;
+            for bit in Self::usize_iter_ones(*w) {
+                if !pred(i * Self::BITS_PER_WORD + bit) {
+                    kept &= !(1usize << bit);
+                }
+            }
+            *w = kept;
+        }
+        self.recount();
     }
 
     /// Set all bits in [start, end). Safe for any range; grows as needed.
@@ -174,6 +468,7 @@ impl BitSet {
                 (1usize << (e_o + 1)) - 1
             };
             self.words[s_w] |= left & right;
+            self.recount();
             return;
         }
 
@@ -190,11 +485,51 @@ impl BitSet {
             (1usize << (e_o + 1)) - 1
         };
         self.words[e_w] |= tail_mask;
+        self.recount();
     }
 
     /// Count number of set bits.
     pub fn count(&self) -> usize {
-        self.words.iter().map(|w| w.count_ones() as usize).sum()
+        self.cached_count
+            .unwrap_or_else(|| kernels::count_ones(&self.words))
+    }
+
+    /// Count bits of `self ∩ other`.
+    pub fn count_intersection(&self, other: &Self) -> usize {
+        let min = self.words.len().min(other.words.len());
+        kernels::count_ones_and(&self.words[..min], &other.words[..min])
+    }
+
+    /// Count bits of `self ∪ other`.
+    pub fn count_union(&self, other: &Self) -> usize {
+        let (longer, shorter) = if self.words.len() > other.words.len() {
+            (&self.words, &other.words)
+        } else {
+            (&other.words, &self.words)
+        };
+        let head = longer
+            .iter()
+            .zip(shorter.iter())
+            .map(|(a, b)| (a | b).count_ones() as usize)
+            .sum::<usize>();
+        let tail = longer[shorter.len()..]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum::<usize>();
+        head + tail
+    }
+
+    /// Count bits of `self \ other`.
+    pub fn count_difference(&self, other: &Self) -> usize {
+        let min = self.words.len().min(other.words.len());
+        let head = (0..min)
+            .map(|i| (self.words[i] & !other.words[i]).count_ones() as usize)
+            .sum::<usize>();
+        let tail = self.words[min..]
+            .iter()
+            .map(|w| w.count_ones() as usize)
+            .sum::<usize>();
+        head + tail
     }
 
     /// Return the index of the n-th set bit (0-based), or `None`.
@@ -238,6 +573,43 @@ impl BitSet {
         Self::usize_iter_ones(word).map(move |off| base_bit + off)
     }
 
+    #[inline]
+    fn usize_iter_ones_rev(mut x: usize) -> impl Iterator<Item = usize> {
+        iter::from_fn(move || {
+            if x == 0 {
+                return None;
+            }
+            let top = Self::highest_bit(x);
+            x &= !(1usize << top);
+            Some(top)
+        })
+    }
+
+    /// Iterate set bits in `range`, masking the boundary words.
+    pub fn iter_range(&self, range: std::ops::Range<usize>) -> impl Iterator<Item = usize> + '_ {
+        let end = range.end.min(self.capacity());
+        let start = range.start.min(end);
+        let start_word = start / Self::BITS_PER_WORD;
+        let end_word = end.div_ceil(Self::BITS_PER_WORD);
+        (start_word..end_word).flat_map(move |i| {
+            let mut w = self.words[i];
+            if i == start_word {
+                w &= !0usize << (start % Self::BITS_PER_WORD);
+            }
+            if i + 1 == end_word && !end.is_multiple_of(Self::BITS_PER_WORD) {
+                w &= (1usize << (end % Self::BITS_PER_WORD)) - 1;
+            }
+            Self::iter_word_bits(w, i * Self::BITS_PER_WORD)
+        })
+    }
+
+    /// Iterate set bits from the highest down.
+    pub fn iter_rev(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.words.len()).rev().flat_map(move |i| {
+            Self::usize_iter_ones_rev(self.words[i]).map(move |off| i * Self::BITS_PER_WORD + off)
+        })
+    }
+
     /// Iterate indices in `self ∪ other`.
     pub fn iter_union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = usize> + 'a {
         let min = self.words.len().min(other.words.len());
@@ -312,7 +684,7 @@ impl BitSet {
                 *w = 0;
             }
         } else if self.words.len() < max_words {
-            self.words.resize(max_words, 0);
+            self.words.resize(max_words);
         }
 
         let min = a.words.len().min(b.words.len());
@@ -322,6 +694,7 @@ impl BitSet {
         for w in &mut self.words[min..] {
             *w = 0;
         }
+        self.recount();
     }
 
     /// First bit of `self ∩ other`, or None.
@@ -335,79 +708,272 @@ impl BitSet {
     }
 }
 
-impl BitSetT for BitSet {
+impl<S: BlockStorage> BitSetT for BlockBitSet<S> {
     fn create() -> Self {
-        BitSet::new(0)
+        Self {
+            words: S::default(),
+            cached_count: None,
+        }
     }
 
     fn grow(&mut self, bits: usize) {
-        BitSet::grow(self, bits)
+        Self::grow(self, bits)
     }
     fn capacity(&self) -> usize {
-        BitSet::capacity(self)
+        Self::capacity(self)
     }
     fn clear_all(&mut self) {
-        BitSet::clear_all(self)
+        Self::clear_all(self)
     }
     fn set(&mut self, bit: usize) {
-        BitSet::set(self, bit)
+        Self::set(self, bit)
     }
     fn set_between(&mut self, start: usize, end: usize) {
-        BitSet::set_between(self, start, end)
+        Self::set_between(self, start, end)
     }
     fn clear(&mut self, bit: usize) {
-        BitSet::clear(self, bit)
+        Self::clear(self, bit)
     }
     fn contains(&self, bit: usize) -> bool {
-        BitSet::contains(self, bit)
+        Self::contains(self, bit)
     }
     fn first_set(&self) -> Option<usize> {
-        BitSet::first_set(self)
+        Self::first_set(self)
     }
     fn first_unset(&self) -> Option<usize> {
-        BitSet::first_unset(self)
+        Self::first_unset(self)
     }
     fn first_set_ge(&self, bit: usize) -> Option<usize> {
-        BitSet::first_set_ge(self, bit)
+        Self::first_set_ge(self, bit)
     }
     fn first_unset_ge(&self, bit: usize) -> Option<usize> {
-        BitSet::first_unset_ge(self, bit)
+        Self::first_unset_ge(self, bit)
+    }
+    fn last_set(&self) -> Option<usize> {
+        Self::last_set(self)
+    }
+    fn first_set_le(&self, bit: usize) -> Option<usize> {
+        Self::first_set_le(self, bit)
     }
     fn union_with(&mut self, other: &Self) {
-        BitSet::union_with(self, other)
+        Self::union_with(self, other)
     }
     fn intersect_with(&mut self, other: &Self) {
-        BitSet::intersect_with(self, other)
+        Self::intersect_with(self, other)
     }
     fn intersect(&mut self, a: &Self, b: &Self) {
-        BitSet::intersect(self, a, b)
+        Self::intersect(self, a, b)
     }
     fn difference_with(&mut self, other: &Self) {
-        BitSet::difference_with(self, other)
+        Self::difference_with(self, other)
+    }
+    fn complement(&mut self) {
+        Self::complement(self)
+    }
+    fn retain(&mut self, pred: impl FnMut(usize) -> bool) {
+        Self::retain(self, pred)
     }
     fn iter_union<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = usize> + 'a {
-        BitSet::iter_union(self, other)
+        Self::iter_union(self, other)
     }
     fn iter_intersection<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = usize> + 'a {
-        BitSet::iter_intersection(self, other)
+        Self::iter_intersection(self, other)
     }
     fn iter_difference<'a>(&'a self, other: &'a Self) -> impl Iterator<Item = usize> + 'a {
-        BitSet::iter_difference(self, other)
+        Self::iter_difference(self, other)
     }
     fn count(&self) -> usize {
-        BitSet::count(self)
+        Self::count(self)
+    }
+    fn cache_count(&mut self) {
+        Self::cache_count(self)
+    }
+    fn count_intersection(&self, other: &Self) -> usize {
+        Self::count_intersection(self, other)
+    }
+    fn count_union(&self, other: &Self) -> usize {
+        Self::count_union(self, other)
+    }
+    fn count_difference(&self, other: &Self) -> usize {
+        Self::count_difference(self, other)
     }
     fn nth(&self, n: usize) -> Option<usize> {
-        BitSet::nth(self, n)
+        Self::nth(self, n)
     }
     fn intersect_first_set(&self, other: &Self) -> Option<usize> {
-        BitSet::intersect_first_set(self, other)
+        Self::intersect_first_set(self, other)
     }
     fn intersect_first_set_ge(&self, other: &Self, ge: usize) -> Option<usize> {
-        BitSet::intersect_first_set_ge(self, other, ge)
+        Self::intersect_first_set_ge(self, other, ge)
     }
 
     fn iter<'a>(&'a self) -> impl Iterator<Item = usize> + 'a {
         self.iter()
     }
+
+    fn iter_rev(&self) -> impl Iterator<Item = usize> + '_ {
+        Self::iter_rev(self)
+    }
+
+    fn iter_range(&self, range: std::ops::Range<usize>) -> impl Iterator<Item = usize> + '_ {
+        Self::iter_range(self, range)
+    }
+}
+
+impl<S: BlockStorage> BlockBitSet<S> {
+    /// The words up to the last non-zero one, so that sets differing only
+    /// in capacity compare and hash the same.
+    fn significant_words(&self) -> &[usize] {
+        let len = self
+            .words
+            .iter()
+            .rposition(|&w| w != 0)
+            .map_or(0, |i| i + 1);
+        &self.words[..len]
+    }
+}
+
+impl<S: BlockStorage> std::fmt::Debug for BlockBitSet<S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl<S: BlockStorage> PartialEq for BlockBitSet<S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.significant_words() == other.significant_words()
+    }
+}
+
+impl<S: BlockStorage> Eq for BlockBitSet<S> {}
+
+impl<S: BlockStorage> std::hash::Hash for BlockBitSet<S> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.significant_words().hash(state)
+    }
+}
+
+impl FromIterator<usize> for BitSet {
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut bs = BitSet::new(0);
+        bs.extend(iter);
+        bs
+    }
+}
+
+impl<S: BlockStorage> Extend<usize> for BlockBitSet<S> {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        for bit in iter {
+            self.set(bit);
+        }
+    }
+}
+
+/// Set bits of a sequence of words, lowest first.
+pub struct Bits<Words> {
+    words: Words,
+    word: usize,
+    base_bit: usize,
+}
+
+impl<Words: Iterator<Item = usize>> Bits<Words> {
+    fn new(words: Words) -> Self {
+        Self {
+            words,
+            word: 0,
+            // wraps to 0 on the first word
+            base_bit: 0usize.wrapping_sub(usize::BITS as usize),
+        }
+    }
+}
+
+impl<Words: Iterator<Item = usize>> Iterator for Bits<Words> {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        while self.word == 0 {
+            self.word = self.words.next()?;
+            self.base_bit = self.base_bit.wrapping_add(usize::BITS as usize);
+        }
+        let tz = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+        Some(self.base_bit + tz)
+    }
 }
+
+pub type Iter<'a> = Bits<iter::Copied<std::slice::Iter<'a, usize>>>;
+pub type IntoIter = Bits<std::vec::IntoIter<usize>>;
+
+impl<'a, S: BlockStorage> IntoIterator for &'a BlockBitSet<S> {
+    type Item = usize;
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        Bits::new(self.words.iter().copied())
+    }
+}
+
+impl IntoIterator for BitSet {
+    type Item = usize;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        Bits::new(self.words.into_iter())
+    }
+}
+
+impl<S: BlockStorage> std::ops::BitOrAssign<&BlockBitSet<S>> for BlockBitSet<S> {
+    fn bitor_assign(&mut self, rhs: &BlockBitSet<S>) {
+        self.union_with(rhs)
+    }
+}
+
+impl<S: BlockStorage> std::ops::BitAndAssign<&BlockBitSet<S>> for BlockBitSet<S> {
+    fn bitand_assign(&mut self, rhs: &BlockBitSet<S>) {
+        self.intersect_with(rhs)
+    }
+}
+
+impl<S: BlockStorage> std::ops::SubAssign<&BlockBitSet<S>> for BlockBitSet<S> {
+    fn sub_assign(&mut self, rhs: &BlockBitSet<S>) {
+        self.difference_with(rhs)
+    }
+}
+
+impl<S: BlockStorage> std::ops::BitXorAssign<&BlockBitSet<S>> for BlockBitSet<S> {
+    fn bitxor_assign(&mut self, rhs: &BlockBitSet<S>) {
+        if rhs.words.len() > self.words.len() {
+            self.words.resize(rhs.words.len());
+        }
+        for (d, s) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *d ^= s;
+        }
+        self.recount();
+    }
+}
+
+macro_rules! binop_from_assign {
+    ($trait:ident, $method:ident, $assign_op:tt) => {
+        impl<S: BlockStorage> std::ops::$trait<&BlockBitSet<S>> for BlockBitSet<S> {
+            type Output = BlockBitSet<S>;
+
+            fn $method(mut self, rhs: &BlockBitSet<S>) -> BlockBitSet<S> {
+                self $assign_op rhs;
+                self
+            }
+        }
+
+        impl<S: BlockStorage> std::ops::$trait<&BlockBitSet<S>> for &BlockBitSet<S> {
+            type Output = BlockBitSet<S>;
+
+            fn $method(self, rhs: &BlockBitSet<S>) -> BlockBitSet<S> {
+                std::ops::$trait::$method(self.clone(), rhs)
+            }
+        }
+    };
+}
+
+binop_from_assign!(BitOr, bitor, |=);
+binop_from_assign!(BitAnd, bitand, &=);
+binop_from_assign!(BitXor, bitxor, ^=);
+binop_from_assign!(Sub, sub, -=);
@@ -0,0 +1,92 @@
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+
+/// A uniformly random `k`-CNF with `num_clauses` clauses over `num_vars`
+/// variables. Each clause has `k` distinct variables with random signs.
+pub fn random_k_cnf(num_vars: usize, num_clauses: usize, k: usize, seed: u64) -> Vec<Vec<isize>> {
+    assert!(
+        k <= num_vars,
+        "can't pick {} distinct variables out of {}",
+        k,
+        num_vars
+    );
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let vars = (1..=num_vars as isize).collect::<Vec<_>>();
+    (0..num_clauses)
+        .map(|_| {
+            let chosen = vars
+                .choose_multiple(&mut rng, k)
+                .copied()
+                .collect::<Vec<_>>();
+            chosen
+                .into_iter()
+                .map(|var| if rng.random_ratio(1, 2) { var } else { -var })
+                .collect()
+        })
+        .collect()
+}
+
+/// The pigeonhole principle PHP(`holes` + 1, `holes`): every pigeon sits in
+/// a hole, and no hole holds two. Unsatisfiable, and exponentially hard for
+/// resolution, so it stresses conflict analysis and proof logging. Pigeon
+/// `p` in hole `h` is variable `p * holes + h + 1`.
+pub fn pigeonhole(holes: usize) -> Vec<Vec<isize>> {
+    let (pigeons, holes) = (holes as isize + 1, holes as isize);
+    let var = |pigeon: isize, hole: isize| pigeon * holes + hole + 1;
+    let mut formula = (0..pigeons)
+        .map(|p| (0..holes).map(|h| var(p, h)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    for h in 0..holes {
+        for p1 in 0..pigeons {
+            for p2 in p1 + 1..pigeons {
+                formula.push(vec![-var(p1, h), -var(p2, h)]);
+            }
+        }
+    }
+    formula
+}
+
+/// A Tseitin parity formula on the `n` by `n` torus grid: a variable per
+/// edge, and at each vertex the XOR of its four edges equals the vertex's
+/// random charge. The charges sum to an odd number, but every edge counts
+/// at two vertices, so the formula is unsatisfiable; resolution needs
+/// exponentially long proofs in `n`. Vertex `(row, col)`'s edges to the
+/// right and below are variables `2 * (row * n + col) + 1` and `+ 2`.
+pub fn tseitin_parity(n: usize, seed: u64) -> Vec<Vec<isize>> {
+    assert!(n >= 3, "the torus needs at least 3 rows, not {}", n);
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let vertex = |row: usize, col: usize| ((row % n) * n + col % n) as isize;
+    let right = |row: usize, col: usize| 2 * vertex(row, col) + 1;
+    let down = |row: usize, col: usize| 2 * vertex(row, col) + 2;
+    let mut charges = (0..n * n)
+        .map(|_| rng.random_ratio(1, 2))
+        .collect::<Vec<_>>();
+    if charges.iter().filter(|&&charge| charge).count() % 2 == 0 {
+        charges[0] = !charges[0];
+    }
+    let mut formula = vec![];
+    for row in 0..n {
+        for col in 0..n {
+            let edges = [
+                right(row, col),
+                right(row, col + n - 1),
+                down(row, col),
+                down(row + n - 1, col),
+            ];
+            let charge = charges[vertex(row, col) as usize];
+            // rule out each assignment to the edges of the wrong parity
+            for mask in 0..1u32 << edges.len() {
+                if (mask.count_ones() % 2 == 1) != charge {
+                    formula.push(
+                        edges
+                            .iter()
+                            .enumerate()
+                            .map(|(idx, &edge)| if mask >> idx & 1 == 1 { -edge } else { edge })
+                            .collect(),
+                    );
+                }
+            }
+        }
+    }
+    formula
+}
@@ -0,0 +1,122 @@
+use crate::sat::SatResult;
+use crate::two_sat;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Every clause has at most one positive literal.
+pub fn is_horn(clauses: &[Vec<isize>]) -> bool {
+    clauses
+        .iter()
+        .all(|clause| clause.iter().filter(|&&lit| lit > 0).count() <= 1)
+}
+
+/// Variables whose flipping makes `clauses` Horn, if there are any. This is
+/// a 2-SAT problem over "is flipped": no two literals of a clause may both
+/// end up positive.
+pub fn horn_renaming(clauses: &[Vec<isize>]) -> Option<BTreeSet<usize>> {
+    // `lit` ends up positive iff `-lit` holds, reading variable `v` as "`v`
+    // is flipped". So each pair `a`, `b` in a clause needs `a ∨ b`.
+    let mut pairs = vec![];
+    for clause in clauses {
+        let mut clause = clause.clone();
+        clause.sort();
+        clause.dedup();
+        for (i, &a) in clause.iter().enumerate() {
+            for &b in &clause[i + 1..] {
+                pairs.push(vec![a, b]);
+            }
+        }
+    }
+    let model = two_sat::solve(&pairs)?;
+    Some(
+        model
+            .into_iter()
+            .filter(|&(_, flipped)| flipped)
+            .map(|(var, _)| var)
+            .collect(),
+    )
+}
+
+/// Solve a Horn formula by propagating forced positive literals from the
+/// all-false assignment, in time linear in the size of the formula. Returns
+/// the minimal model, or `None` if there is none.
+pub fn solve_horn(clauses: &[Vec<isize>]) -> Option<BTreeMap<usize, bool>> {
+    let max_var = clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.unsigned_abs())
+        .max()
+        .unwrap_or(0);
+    let mut value = vec![false; max_var + 1];
+    let mut positive = vec![None; clauses.len()];
+    // the number of negative literals per clause that aren't falsified yet
+    let mut remaining = vec![0; clauses.len()];
+    let mut clauses_by_negated_var = vec![vec![]; max_var + 1];
+    let mut queue = vec![];
+    for (idx, clause) in clauses.iter().enumerate() {
+        let mut clause = clause.clone();
+        clause.sort();
+        clause.dedup();
+        for lit in clause {
+            if lit > 0 {
+                assert!(positive[idx].is_none(), "clause {} is not Horn", idx);
+                positive[idx] = Some(lit as usize);
+            } else {
+                remaining[idx] += 1;
+                clauses_by_negated_var[lit.unsigned_abs()].push(idx);
+            }
+        }
+        if remaining[idx] == 0 {
+            queue.push(idx);
+        }
+    }
+    while let Some(idx) = queue.pop() {
+        let var = positive[idx]?;
+        if value[var] {
+            continue;
+        }
+        value[var] = true;
+        for &other in &clauses_by_negated_var[var] {
+            remaining[other] -= 1;
+            if remaining[other] == 0 {
+                queue.push(other);
+            }
+        }
+    }
+    Some(
+        clauses
+            .iter()
+            .flatten()
+            .map(|lit| (lit.unsigned_abs(), value[lit.unsigned_abs()]))
+            .collect(),
+    )
+}
+
+/// Solve `clauses` directly if they are Horn or renamable Horn, otherwise
+/// `None`.
+pub fn try_solve(clauses: &[Vec<isize>]) -> Option<SatResult> {
+    let renaming = if is_horn(clauses) {
+        BTreeSet::new()
+    } else {
+        horn_renaming(clauses)?
+    };
+    let rename = |lit: isize| {
+        if renaming.contains(&lit.unsigned_abs()) {
+            -lit
+        } else {
+            lit
+        }
+    };
+    let renamed = clauses
+        .iter()
+        .map(|clause| clause.iter().map(|&lit| rename(lit)).collect())
+        .collect::<Vec<_>>();
+    match solve_horn(&renamed) {
+        None => Some(SatResult::UnsatCore(vec![])),
+        Some(model) => Some(SatResult::Sat(
+            model
+                .into_iter()
+                .map(|(var, value)| (var, value != renaming.contains(&var)))
+                .collect(),
+        )),
+    }
+}
@@ -0,0 +1,537 @@
+use crate::bitset::BitSetT;
+use itertools::Either;
+
+/// Bits per chunk: a chunk holds the bits sharing everything above the low
+/// 16, so its members fit in a `u16`.
+const CHUNK_BITS: usize = 16;
+const CHUNK_SIZE: usize = 1 << CHUNK_BITS;
+const WORDS: usize = CHUNK_SIZE / 64;
+/// Past this many members a chunk is smaller as a bitmap.
+const ARRAY_MAX: usize = 4096;
+
+#[derive(Clone, Debug)]
+enum Container {
+    /// Sorted, without duplicates.
+    Array(Vec<u16>),
+    Bitmap { words: Box<[u64; WORDS]>, len: usize },
+}
+
+impl Container {
+    fn from_words(words: Box<[u64; WORDS]>) -> Self {
+        let len = words.iter().map(|w| w.count_ones() as usize).sum();
+        if len > ARRAY_MAX {
+            return Container::Bitmap { words, len };
+        }
+        let mut array = Vec::with_capacity(len);
+        for (i, &word) in words.iter().enumerate() {
+            let mut word = word;
+            while word != 0 {
+                array.push((i * 64 + word.trailing_zeros() as usize) as u16);
+                word &= word - 1;
+            }
+        }
+        Container::Array(array)
+    }
+
+    fn to_words(&self) -> Box<[u64; WORDS]> {
+        match self {
+            Container::Array(array) => {
+                let mut words = Box::new([0; WORDS]);
+                for &low in array {
+                    words[low as usize / 64] |= 1 << (low % 64);
+                }
+                words
+            }
+            Container::Bitmap { words, .. } => words.clone(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Container::Array(array) => array.len(),
+            Container::Bitmap { len, .. } => *len,
+        }
+    }
+
+    fn contains(&self, low: u16) -> bool {
+        match self {
+            Container::Array(array) => array.binary_search(&low).is_ok(),
+            Container::Bitmap { words, .. } => words[low as usize / 64] >> (low % 64) & 1 != 0,
+        }
+    }
+
+    fn insert(&mut self, low: u16) {
+        match self {
+            Container::Array(array) => {
+                if let Err(idx) = array.binary_search(&low) {
+                    array.insert(idx, low);
+                    if array.len() > ARRAY_MAX {
+                        *self = Container::from_words(self.to_words());
+                    }
+                }
+            }
+            Container::Bitmap { words, len } => {
+                let word = &mut words[low as usize / 64];
+                if *word >> (low % 64) & 1 == 0 {
+                    *word |= 1 << (low % 64);
+                    *len += 1;
+                }
+            }
+        }
+    }
+
+    fn remove(&mut self, low: u16) {
+        match self {
+            Container::Array(array) => {
+                if let Ok(idx) = array.binary_search(&low) {
+                    array.remove(idx);
+                }
+            }
+            Container::Bitmap { words, len } => {
+                let word = &mut words[low as usize / 64];
+                if *word >> (low % 64) & 1 != 0 {
+                    *word &= !(1 << (low % 64));
+                    *len -= 1;
+                    if *len <= ARRAY_MAX {
+                        *self = Container::from_words(self.to_words());
+                    }
+                }
+            }
+        }
+    }
+
+    fn first_ge(&self, low: usize) -> Option<u16> {
+        match self {
+            Container::Array(array) => {
+                let idx = array.partition_point(|&x| (x as usize) < low);
+                array.get(idx).copied()
+            }
+            Container::Bitmap { words, .. } => {
+                if low >= CHUNK_SIZE {
+                    return None;
+                }
+                let start = low / 64;
+                let masked = words[start] & (!0u64 << (low % 64));
+                if masked != 0 {
+                    return Some((start * 64 + masked.trailing_zeros() as usize) as u16);
+                }
+                (start + 1..WORDS)
+                    .find(|&i| words[i] != 0)
+                    .map(|i| (i * 64 + words[i].trailing_zeros() as usize) as u16)
+            }
+        }
+    }
+
+    fn last_le(&self, low: u16) -> Option<u16> {
+        match self {
+            Container::Array(array) => {
+                let idx = array.partition_point(|&x| x <= low);
+                idx.checked_sub(1).map(|idx| array[idx])
+            }
+            Container::Bitmap { words, .. } => {
+                let end = low as usize / 64;
+                let masked = words[end] & (!0u64 >> (63 - low % 64));
+                if masked != 0 {
+                    return Some((end * 64 + 63 - masked.leading_zeros() as usize) as u16);
+                }
+                (0..end)
+                    .rev()
+                    .find(|&i| words[i] != 0)
+                    .map(|i| (i * 64 + 63 - words[i].leading_zeros() as usize) as u16)
+            }
+        }
+    }
+
+    /// The first member of `low..CHUNK_SIZE` that isn't set.
+    fn first_unset_ge(&self, low: usize) -> Option<usize> {
+        match self {
+            Container::Array(array) => {
+                let mut expected = low;
+                for &x in &array[array.partition_point(|&x| (x as usize) < low)..] {
+                    if x as usize != expected {
+                        break;
+                    }
+                    expected += 1;
+                }
+                Some(expected).filter(|&x| x < CHUNK_SIZE)
+            }
+            Container::Bitmap { words, .. } => {
+                let start = low / 64;
+                let masked = !words[start] & (!0u64 << (low % 64));
+                if masked != 0 {
+                    return Some(start * 64 + masked.trailing_zeros() as usize);
+                }
+                (start + 1..WORDS)
+                    .find(|&i| words[i] != !0)
+                    .map(|i| i * 64 + (!words[i]).trailing_zeros() as usize)
+            }
+        }
+    }
+
+    fn retain(&mut self, mut pred: impl FnMut(u16) -> bool) {
+        match self {
+            Container::Array(array) => array.retain(|&low| pred(low)),
+            Container::Bitmap { words, .. } => {
+                for (i, w) in words.iter_mut().enumerate() {
+                    let mut word = *w;
+                    while word != 0 {
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        if !pred((i * 64 + bit) as u16) {
+                            *w &= !(1 << bit);
+                        }
+                    }
+                }
+                *self = Container::from_words(self.to_words());
+            }
+        }
+    }
+
+    fn nth(&self, n: usize) -> Option<u16> {
+        match self {
+            Container::Array(array) => array.get(n).copied(),
+            Container::Bitmap { .. } => self.iter().nth(n),
+        }
+    }
+
+    fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        match self {
+            Container::Array(array) => Either::Left(array.iter().copied()),
+            Container::Bitmap { words, .. } => Either::Right(words.iter().enumerate().flat_map(
+                |(i, &word)| {
+                    let mut word = word;
+                    std::iter::from_fn(move || {
+                        if word == 0 {
+                            return None;
+                        }
+                        let bit = word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        Some((i * 64 + bit) as u16)
+                    })
+                },
+            )),
+        }
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Container::Array(a), Container::Array(b)) if a.len() + b.len() <= ARRAY_MAX => {
+                let mut merged = Vec::with_capacity(a.len() + b.len());
+                let (mut i, mut j) = (0, 0);
+                while i < a.len() && j < b.len() {
+                    match a[i].cmp(&b[j]) {
+                        std::cmp::Ordering::Less => {
+                            merged.push(a[i]);
+                            i += 1;
+                        }
+                        std::cmp::Ordering::Greater => {
+                            merged.push(b[j]);
+                            j += 1;
+                        }
+                        std::cmp::Ordering::Equal => {
+                            merged.push(a[i]);
+                            i += 1;
+                            j += 1;
+                        }
+                    }
+                }
+                merged.extend_from_slice(&a[i..]);
+                merged.extend_from_slice(&b[j..]);
+                Container::Array(merged)
+            }
+            _ => {
+                let mut words = self.to_words();
+                for (w, o) in words.iter_mut().zip(other.to_words().iter()) {
+                    *w |= o;
+                }
+                Container::from_words(words)
+            }
+        }
+    }
+
+    fn intersection(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Container::Array(a), other) | (other, Container::Array(a)) => {
+                Container::Array(a.iter().copied().filter(|&x| other.contains(x)).collect())
+            }
+            (Container::Bitmap { words: a, .. }, Container::Bitmap { words: b, .. }) => {
+                let mut words = a.clone();
+                for (w, o) in words.iter_mut().zip(b.iter()) {
+                    *w &= o;
+                }
+                Container::from_words(words)
+            }
+        }
+    }
+
+    fn difference(&self, other: &Self) -> Self {
+        match self {
+            Container::Array(a) => {
+                Container::Array(a.iter().copied().filter(|&x| !other.contains(x)).collect())
+            }
+            Container::Bitmap { words, .. } => {
+                let mut words = words.clone();
+                for (w, o) in words.iter_mut().zip(other.to_words().iter()) {
+                    *w &= !o;
+                }
+                Container::from_words(words)
+            }
+        }
+    }
+}
+
+/// A bitset stored in chunks of 2^16 bits, like a Roaring bitmap: sparse
+/// chunks are sorted arrays of their low 16 bits and dense ones are bitmaps.
+/// Small sets over a large range of variables (say, a clause's) stay small.
+/// Like `BTreeBitSet`, the capacity is the highest set bit plus one.
+#[derive(Clone, Debug, Default)]
+pub struct HybridBitSet {
+    /// Sorted by key, with no empty containers.
+    chunks: Vec<(usize, Container)>,
+}
+
+impl HybridBitSet {
+    fn split(bit: usize) -> (usize, u16) {
+        (bit >> CHUNK_BITS, (bit & (CHUNK_SIZE - 1)) as u16)
+    }
+
+    fn chunk(&self, key: usize) -> Option<&Container> {
+        self.chunks
+            .binary_search_by_key(&key, |(k, _)| *k)
+            .ok()
+            .map(|idx| &self.chunks[idx].1)
+    }
+
+    /// Merge the chunks of `self` and `other` with `op`, where `keep_self`
+    /// and `keep_other` say whether chunks of only one side survive.
+    fn merge(
+        &mut self,
+        other: &Self,
+        keep_self: bool,
+        keep_other: bool,
+        op: impl Fn(&Container, &Container) -> Container,
+    ) {
+        let mut chunks = Vec::with_capacity(self.chunks.len().max(other.chunks.len()));
+        let mut mine = std::mem::take(&mut self.chunks).into_iter().peekable();
+        let mut theirs = other.chunks.iter().peekable();
+        loop {
+            let next = match (mine.peek(), theirs.peek()) {
+                (None, None) => break,
+                (Some(_), None) => mine.next().filter(|_| keep_self),
+                (None, Some(_)) => theirs.next().filter(|_| keep_other).cloned(),
+                (Some((a, _)), Some((b, _))) if a < b => mine.next().filter(|_| keep_self),
+                (Some((a, _)), Some((b, _))) if a > b => {
+                    theirs.next().filter(|_| keep_other).cloned()
+                }
+                (Some(_), Some(_)) => {
+                    let (key, a) = mine.next().unwrap();
+                    let (_, b) = theirs.next().unwrap();
+                    Some((key, op(&a, b)))
+                }
+            };
+            if let Some((key, container)) = next {
+                if container.len() > 0 {
+                    chunks.push((key, container));
+                }
+            }
+        }
+        self.chunks = chunks;
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.chunks.iter().flat_map(|(key, container)| {
+            container
+                .iter()
+                .map(move |low| key << CHUNK_BITS | low as usize)
+        })
+    }
+}
+
+impl BitSetT for HybridBitSet {
+    fn create() -> Self {
+        Self::default()
+    }
+
+    /// No-op: chunks are added as bits are set.
+    fn grow(&mut self, _bits: usize) {}
+
+    fn capacity(&self) -> usize {
+        self.last_set().map(|x| x + 1).unwrap_or(0)
+    }
+
+    fn clear_all(&mut self) {
+        self.chunks.clear();
+    }
+
+    fn set(&mut self, bit: usize) {
+        let (key, low) = Self::split(bit);
+        match self.chunks.binary_search_by_key(&key, |(k, _)| *k) {
+            Ok(idx) => self.chunks[idx].1.insert(low),
+            Err(idx) => self
+                .chunks
+                .insert(idx, (key, Container::Array(vec![low]))),
+        }
+    }
+
+    fn set_between(&mut self, start_bit_incl: usize, end_bit_excl: usize) {
+        let mut start = start_bit_incl;
+        while start < end_bit_excl {
+            let (key, low) = Self::split(start);
+            let end = end_bit_excl.min((key + 1) << CHUNK_BITS);
+            let mut words = self
+                .chunk(key)
+                .map(|container| container.to_words())
+                .unwrap_or_else(|| Box::new([0; WORDS]));
+            for bit in low as usize..low as usize + (end - start) {
+                words[bit / 64] |= 1 << (bit % 64);
+            }
+            let container = Container::from_words(words);
+            match self.chunks.binary_search_by_key(&key, |(k, _)| *k) {
+                Ok(idx) => self.chunks[idx].1 = container,
+                Err(idx) => self.chunks.insert(idx, (key, container)),
+            }
+            start = end;
+        }
+    }
+
+    fn clear(&mut self, bit: usize) {
+        let (key, low) = Self::split(bit);
+        if let Ok(idx) = self.chunks.binary_search_by_key(&key, |(k, _)| *k) {
+            self.chunks[idx].1.remove(low);
+            if self.chunks[idx].1.len() == 0 {
+                self.chunks.remove(idx);
+            }
+        }
+    }
+
+    fn contains(&self, bit: usize) -> bool {
+        let (key, low) = Self::split(bit);
+        self.chunk(key)
+            .is_some_and(|container| container.contains(low))
+    }
+
+    fn first_set(&self) -> Option<usize> {
+        self.first_set_ge(0)
+    }
+
+    fn first_unset(&self) -> Option<usize> {
+        self.first_unset_ge(0)
+    }
+
+    fn first_set_ge(&self, bit: usize) -> Option<usize> {
+        let (key, low) = Self::split(bit);
+        let idx = self.chunks.partition_point(|(k, _)| *k < key);
+        self.chunks[idx..].iter().find_map(|(k, container)| {
+            let low = if *k == key { low as usize } else { 0 };
+            container
+                .first_ge(low)
+                .map(|low| k << CHUNK_BITS | low as usize)
+        })
+    }
+
+    fn first_unset_ge(&self, bit: usize) -> Option<usize> {
+        let mut bit = bit;
+        loop {
+            let (key, low) = Self::split(bit);
+            match self.chunk(key) {
+                None => return Some(bit),
+                Some(container) => match container.first_unset_ge(low as usize) {
+                    Some(low) => return Some(key << CHUNK_BITS | low),
+                    None => bit = (key + 1) << CHUNK_BITS,
+                },
+            }
+        }
+    }
+
+    fn last_set(&self) -> Option<usize> {
+        self.first_set_le(usize::MAX)
+    }
+
+    fn first_set_le(&self, bit: usize) -> Option<usize> {
+        let (key, low) = Self::split(bit);
+        let idx = self.chunks.partition_point(|(k, _)| *k <= key);
+        self.chunks[..idx].iter().rev().find_map(|(k, container)| {
+            let low = if *k == key { low } else { u16::MAX };
+            container
+                .last_le(low)
+                .map(|low| k << CHUNK_BITS | low as usize)
+        })
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.merge(other, true, true, Container::union)
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        self.merge(other, false, false, Container::intersection)
+    }
+
+    fn difference_with(&mut self, other: &Self) {
+        self.merge(other, true, false, Container::difference)
+    }
+
+    fn complement(&mut self) {
+        let Some(last) = self.last_set() else {
+            return;
+        };
+        let (last_key, last_low) = Self::split(last);
+        let mut chunks = vec![];
+        for key in 0..=last_key {
+            let mut words = self
+                .chunk(key)
+                .map(|container| container.to_words())
+                .unwrap_or_else(|| Box::new([0; WORDS]));
+            for w in words.iter_mut() {
+                *w = !*w;
+            }
+            if key == last_key {
+                // only flip bits up to and including the last one
+                for bit in last_low as usize + 1..CHUNK_SIZE {
+                    words[bit / 64] &= !(1 << (bit % 64));
+                }
+            }
+            let container = Container::from_words(words);
+            if container.len() > 0 {
+                chunks.push((key, container));
+            }
+        }
+        self.chunks = chunks;
+    }
+
+    fn retain(&mut self, mut pred: impl FnMut(usize) -> bool) {
+        for (key, container) in self.chunks.iter_mut() {
+            let key = *key;
+            container.retain(|low| pred(key << CHUNK_BITS | low as usize));
+        }
+        self.chunks.retain(|(_, container)| container.len() > 0);
+    }
+
+    fn intersect(&mut self, a: &Self, b: &Self) {
+        *self = a.clone();
+        self.intersect_with(b);
+    }
+
+    fn nth(&self, n: usize) -> Option<usize> {
+        let mut n = n;
+        for (key, container) in &self.chunks {
+            if n < container.len() {
+                return container
+                    .nth(n)
+                    .map(|low| key << CHUNK_BITS | low as usize);
+            }
+            n -= container.len();
+        }
+        None
+    }
+
+    fn count(&self) -> usize {
+        self.chunks
+            .iter()
+            .map(|(_, container)| container.len())
+            .sum()
+    }
+
+    fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        HybridBitSet::iter(self)
+    }
+}
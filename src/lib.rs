@@ -1,6 +1,11 @@
+#![cfg_attr(feature = "simd", feature(portable_simd))]
+
 pub mod avl_tree;
 pub mod bitset;
 pub mod fixed_bitset;
+pub mod hybrid_bitset;
+#[cfg(all(feature = "mmap", unix))]
+pub mod mmap_storage;
 pub mod cdcl;
 pub mod pool;
 pub mod sat;
@@ -8,6 +13,36 @@ pub mod dimacs;
 pub mod shared_string_writer;
 pub mod tombstone;
 pub mod luby;
+pub mod occurrences;
+pub mod restart;
+pub mod qbf;
+pub mod optimize;
+pub mod clause_group;
+pub mod components;
+pub mod model_count;
+pub mod at_most_one;
+pub mod difference_logic;
+pub mod analysis;
+pub mod stats;
+pub mod description;
+pub mod shuffle;
+pub mod trace;
+pub mod drup;
+pub mod aiger;
+pub mod generate;
+pub mod encodings;
+pub mod plan;
+pub mod solver;
+pub mod reconstruction;
+pub mod reduce_schedule;
+pub mod reduction;
+pub mod horn;
+pub mod two_sat;
+pub mod dpll;
+#[cfg(feature = "arbitrary")]
+pub mod arbitrary;
+#[cfg(feature = "tui")]
+pub mod tui;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
@@ -1,30 +1,76 @@
-/// s(n): Luby sequence term, 1-based (1,1,2,1,1,2,4,...)
-pub fn luby_term(n: u64) -> u64 {
-    let mut k = 1u64;
-    while (1u64 << k) - 1 < n {
-        k += 1;
+/// The exponent of the n-th Luby term, so that `luby_term(n)` is
+/// `1 << luby_exponent(n)`. Iterative, so any `n` is fine.
+pub fn luby_exponent(n: u64) -> u32 {
+    assert!(n >= 1, "Luby terms are 1-based");
+    // find the smallest complete block 2^k - 1 holding term n, then walk
+    // down into the repeated prefix until n ends a block
+    let mut x = n - 1;
+    let mut size = 1u64;
+    let mut exponent = 0;
+    while size < x + 1 {
+        exponent += 1;
+        size = 2 * size + 1;
     }
-    if n == (1u64 << k) - 1 {
-        1u64 << (k - 1)
-    } else {
-        let prev_block = (1u64 << (k - 1)) - 1;
-        luby_term(n - prev_block)
+    while size - 1 != x {
+        size = (size - 1) >> 1;
+        exponent -= 1;
+        x %= size;
     }
+    exponent
 }
 
-/// Multiply Luby terms by a "unit run" `u` (e.g., conflicts per run)
+/// s(n): Luby sequence term, 1-based (1,1,2,1,1,2,4,...)
+pub fn luby_term(n: u64) -> u64 {
+    1u64 << luby_exponent(n)
+}
+
+/// Multiply Luby terms by a "unit run" `u` (e.g., conflicts per run). With
+/// a `factor` other than 2 the terms are powers of `factor` instead of 2,
+/// and a `cap` bounds every value.
 #[derive(Clone, Debug)]
 pub struct Luby {
     u: u64,
+    factor: f64,
+    cap: Option<u64>,
     i: u64, // number of terms produced so far
 }
 
 impl Luby {
     pub fn new(unit_run: u64) -> Self {
-        Self { u: unit_run, i: 1 }
+        Self::with_factor(unit_run, 2.0, None)
     }
+
+    pub fn with_factor(unit_run: u64, factor: f64, cap: Option<u64>) -> Self {
+        assert!(factor >= 1.0, "factor {} would shrink the runs", factor);
+        Self {
+            u: unit_run,
+            factor,
+            cap,
+            i: 1,
+        }
+    }
+
     pub fn value(&self) -> u64 {
-        self.u * luby_term(self.i)
+        let value = self.u as f64 * self.factor.powi(luby_exponent(self.i) as i32);
+        // `as` saturates, so huge runs become u64::MAX
+        let value = value as u64;
+        match self.cap {
+            Some(cap) => value.min(cap),
+            None => value,
+        }
+    }
+
+    /// `luby(unit run, factor)`, with the cap last if there is one.
+    pub fn describe(&self) -> String {
+        match self.cap {
+            Some(cap) => format!("luby({}, {}, {})", self.u, self.factor, cap),
+            None => format!("luby({}, {})", self.u, self.factor),
+        }
+    }
+
+    /// Go back to the first term.
+    pub fn reset(&mut self) {
+        self.i = 1;
     }
 }
 
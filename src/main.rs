@@ -1,273 +1,539 @@
-use pror::bitset::*;
+use pror::aiger;
 use pror::cdcl::*;
 use pror::dimacs;
-use pror::fixed_bitset::*;
+use pror::drup;
+use pror::generate;
 use pror::sat::*;
+use pror::solver::{build_solver, Heuristic, ReductionOptions, RestartOptions, SolverOptions};
+use pror::stats::Stats;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+const USAGE: &str = "\
+usage: pror <command> [args]
+
+commands:
+  solve <file.cnf> [--assume <lits>] [--no-model] [--stats] [--memory-limit <mb>]
+        [--tui] [--trail-saving] [restart flags] [reduction flags]
+      solve a DIMACS CNF, printing the solver's configuration as c lines
+      and then s/v lines; exits with 10 for sat and 20 for unsat. <lits> is
+      a comma separated list such as 1,-3. Past <mb> megabytes of learned
+      clauses it gives up with s UNKNOWN. --tui (with the tui feature)
+      draws the trail and activities on stderr while solving.
+      --trail-saving reassigns implications undone by a backjump from the
+      saved trail when they come up again
+  verify-model <file.cnf> <model>
+      check a model (v lines, or just literals) against a DIMACS CNF,
+      printing the first clause it doesn't satisfy, numbered from 1
+  check-proof <file.cnf> <proof.drup>
+      check a DRUP refutation of a DIMACS CNF
+  generate [--vars <n>] [--clauses <m>] [--k <k>] [--seed <s>]
+      print a random k-CNF
+  convert <file.aag>
+      print the CNF of an ASCII AIGER circuit with all outputs asserted
+  repl <file.cnf>
+      step through a solve interactively
+  bench <dir> [--timeout <secs>] [--heuristic vsids|random] [--json] [restart flags]
+        [reduction flags]
+      solve every CNF in <dir> with a time limit each, printing a CSV (or
+      JSON) row per instance with its PAR-2 score and stats
+
+restart flags:
+  --restarts luby|geometric|inner-outer
+  --restart-base <n> --restart-factor <f> --restart-cap <n>
+      restart after <n> (default 32) times the Luby terms in conflicts, the
+      terms being powers of <f> (default 2), and never wait past <n>. The
+      geometric and inner-outer schedules start at <n> conflicts and grow
+      by <f>, without a cap
+
+reduction flags:
+  --reduction activity|lbd|size|hybrid --reduce-keep <f>
+  --reduce-first <n> --reduce-growth <g> [--reduce-bump-reasons]
+      reduce the learned clauses after <n> (default 2000) of them, then
+      after <g> (default 1.1) times more each time, keeping the fraction
+      <f> (default 0.5) ranked best by the policy; hybrid never deletes
+      clauses of LBD 2 or less and otherwise goes by activity.
+      --reduce-bump-reasons also bumps a learned clause's activity whenever
+      it propagates, not only when it takes part in a conflict";
 
 fn step_and_print<Config: ConfigT>(solver: &mut State<Config>, literal_override: Option<Literal>) {
-    let result = solver.step(literal_override);
-    println!("\n{:?}", result);
+    match solver.step(literal_override) {
+        StepResult::Learned(learned) => {
+            let lits = learned
+                .literals
+                .iter()
+                .map(|lit| lit.to_string())
+                .collect::<Vec<_>>();
+            println!(
+                "\nlearned ({}), backjumped to level {}",
+                lits.join(" "),
+                learned.backjump_level
+            );
+        }
+        result => println!("\n{:?}", result),
+    }
 }
 
-fn long() {
-    let formula = vec![
-        vec![1, 2, 3],
-        vec![1, 2, -3],
-        vec![-2, 4],
-        vec![1, -2, -4],
-        vec![-1, 5, 6],
-        vec![-1, 5, -6],
-        vec![-5, -6],
-        vec![-1, -5, 6],
-    ];
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+/// Remove `--name <value>` from `args`, returning the value.
+fn take_flag_value(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    match args.iter().position(|arg| arg == name) {
+        None => Ok(None),
+        Some(idx) if idx + 1 < args.len() => {
+            let value = args.remove(idx + 1);
+            args.remove(idx);
+            Ok(Some(value))
+        }
+        Some(_) => Err(format!("{} needs a value", name)),
+    }
 }
 
-fn stepped1() {
-    let formula = vec![
-        vec![1, 2, 3],
-        vec![1, 2, -3],
-        vec![-2, 4],
-        vec![1, -2, -4],
-        vec![-1, 5, 6],
-        vec![-1, 5, -6],
-        vec![-5, -6],
-        vec![-1, -5, 6],
-    ];
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    match args.iter().position(|arg| arg == name) {
+        None => false,
+        Some(idx) => {
+            args.remove(idx);
+            true
+        }
+    }
+}
 
-    let mut solver = DefaultDebug::new_from_vec(formula);
-    step_and_print(&mut solver, Some(Literal::new(1, false)));
-    step_and_print(&mut solver, Some(Literal::new(2, false)));
-    step_and_print(&mut solver, Some(Literal::new(2, false)));
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, Some(Literal::new(5, false)));
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, None);
-    // let res = DefaultDebug::solve(formula);
-    // println! {"res: {:?}", res};
+fn take_parsed_flag<T: std::str::FromStr>(
+    args: &mut Vec<String>,
+    name: &str,
+    default: T,
+) -> Result<T, String> {
+    match take_flag_value(args, name)? {
+        None => Ok(default),
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("bad value for {}: {}", name, value)),
+    }
 }
 
-fn stepped3() {
-    let formula: Vec<Vec<isize>> = vec![
-        vec![3, -10, -13, 1, 12, 15, 9, -5, 6, 14, 4],
-        vec![-10, 14, 5, -3, -12, -6, 8, -4, 11, 9, -15, 1, -7, -13],
-        vec![-4, 10, 12, -5, 8, 15, -6, -13, -7],
-        vec![-13, -15, -12, -11, 14, 8, 5],
-        vec![13, 3, 8, 5, 10, 12, -14, -11],
-        vec![-4, -13],
-        vec![14, 11],
-        vec![-14, 13, -5, -6],
-        vec![-5, 4, -14],
-        vec![12, -6, 8, 2],
-        vec![-4, 8, 6, 15, -3, -13, 9, 12, 2, 1, 11, 7, 10, -5],
-        vec![-14, 9, 5, -11, -15, 1, -4, 12, 13, -2],
-        vec![15, -7, -12, 6],
-        vec![11, -8, -15, 13, 1, -3, 5, -12, 7, -14, -9, 10],
-        vec![-11, -2, -1, -3, -12, -13, -6, 14, -5, -10, -4, -9],
-        vec![-9, -10, 6, 14, -5, 11, 7, -2, 8, -4, -3],
-        vec![6, 5, -14, 12, 1, -13, 10, 9, 11, 7, -8, -2, -15, 3, -4],
-        vec![2, 3, -10, 8, 15, -4, -14, 1],
-        vec![9, 3],
-        vec![-8, 7, -4, -5, -2],
-        vec![-2, -15, -14, 3, -11, -7, 1, 12],
-        vec![-3, -5, 8],
-        vec![-15, -4, 3, -1, 12, -10, -14, -2, 13, -6, -8],
-        vec![-11, -14, -3, -9, 8, -1, -13, 7, 5],
-        vec![-3],
-        vec![14, -3, 15, 7, 4, -8, -13, 10, -12, 6, -5, 2, -9, -1, -11],
-        vec![12, 8, -2, -6, -5, -15, 10, 7, -9],
-        vec![15, 13],
-        vec![9, -1, -15, -3, 2, 12, 6, 14, 5],
-        vec![-1, 13, -4, 11],
-        vec![14, 6, -5, 12],
-        vec![13, -6, 3, 9, 7, 10, 1, -4, -15],
-        vec![-3, -8],
-        vec![-2, 8, -12, 14, 7],
-        vec![-9, 2, -12, -11, 3],
-        vec![4, -10],
-        vec![11, 9, -8, 7, 1, 5, 6, -4],
-        vec![7, -14, 6, 5, 15, -13, -1, -3, -11, 8],
-        vec![2, 9, 3, 5, 1, -7],
-        vec![9, -11, 3],
-        vec![-7, 1, 9, 12, 10, 4, 11, 6, 2, -15],
-        vec![9, -6],
-        vec![12, 5, -6, 14, 8, 10, 13, -7, -2, -11, 15, -3, 9, 1, -4],
-        vec![-10, -9, -8],
-        vec![12, -15, 8, -2, 6, 3, -14, 10],
-        vec![15, -9, 4, 6, -7],
-        vec![4, 10, -2, 8, -9, -14, -12],
-        vec![-10],
-        vec![-14, -3],
-        vec![1, 6, 5, -11, 12, 2, -9, 10, 4, 7],
-        vec![-6, -1, 11],
-        vec![-7, -10, -3, 15, 11, -14, 8],
-        vec![-14, -8, -12, -15, 10, 9, 6, -13, 3, 4, 5, 7, 1, 2],
-        vec![3, -12, -5, -1],
-        vec![6, -9, 10, 13, -4, 1, -15, 14, 2, -7, 5, 8, 11, 12],
-        vec![-10, 3],
-        vec![-5, 1, -4, 11, 12, 15, 3, -13, 9, 14, -10, -7, 2, 6, 8],
-        vec![3, -9, 6, 7, -5, -14, 15],
-        vec![-11, -5, -1, -7, -15, 12, -8, -3],
-        vec![-1, -9, -12, -2, 11, 3, -7, -5, 6, 14, 15, -13, -8],
-        vec![3, -12, 6, -15, -10, -8, 1, 13, -4, -9, 14, 2],
-        vec![13, 1, -3, -15, 2, 14],
-        vec![6, -4, -15, 7, 8, -5, 3, -2, 1, -11],
-        vec![4],
-        vec![4, -2, 12, -6, 13, -15],
-        vec![-1, 4, -8, 9, 13, -5, -14],
-        vec![-1, -7, 8, 10, 11, 6, 3, 2],
-        vec![6, 11, 3, -10, -13, -8, -14, -4],
-        vec![-4, -12, 5, 13, -10, -9, 7, 1, 11, -3, 8],
-        vec![-10, -2, 7, -3, 11, 1, -14, 12, 13],
-        vec![7, 14, -6, -10, -8],
-        vec![-5, -1, -7, -14, -11, 8],
-        vec![15, -3, 8, 7, 2, 14],
-        vec![-3],
-        vec![-13, -11, 10, -14, 9, -5, 15, 3, -1],
-        vec![4, -9, 11, 7, -3, -5, -2],
-        vec![8, -6, -3, -7],
-        vec![-8, 14, -5, -2, 10, -9, -11],
-        vec![-10, -14, 11],
-        vec![-13, -5, 11, 3, 8, 12, 15],
-        vec![2, 12, -14, 8, -13, -3],
-        vec![11, 2, -12, -3, -8, -14, 5, 10, 4, 15, -1],
-        vec![-11, 2, 1, 8, 4, 7, -10, -5],
-    ];
-    let solver = DefaultDebug::solve(formula);
-    println!("res: {:?}", solver);
+fn take_restart_options(args: &mut Vec<String>) -> Result<RestartOptions, String> {
+    let default = RestartOptions::default();
+    let kind = take_parsed_flag(args, "--restarts", default.kind)?;
+    let base = take_parsed_flag(args, "--restart-base", default.base)?;
+    let factor = take_parsed_flag(args, "--restart-factor", default.factor)?;
+    if factor < 1.0 {
+        return Err(format!("--restart-factor {} is less than 1", factor));
+    }
+    let cap = take_flag_value(args, "--restart-cap")?
+        .map(|cap| {
+            cap.parse::<u64>()
+                .map_err(|_| format!("bad value for --restart-cap: {}", cap))
+        })
+        .transpose()?;
+    Ok(RestartOptions {
+        kind,
+        base,
+        factor,
+        cap,
+    })
 }
 
-fn wikipedia() {
-    let formula = vec![
-        vec![1, 4],
-        vec![1, -3, -8],
-        vec![1, 8, 12],
-        vec![2, 11],
-        vec![-3, -7, 9],
-        vec![-7, 8, -9],
-        vec![7, 8, -10],
-        vec![7, 10, -12],
-    ];
-    let mut solver = Default::new_from_vec(formula);
-    step_and_print(&mut solver, Some(Literal::new(1, false)));
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, Some(Literal::new(3, true)));
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, Some(Literal::new(2, false)));
-    step_and_print(&mut solver, None);
-    step_and_print(&mut solver, Some(Literal::new(7, true)));
-    for _ in 1..12 {
-        step_and_print(&mut solver, None);
+fn take_reduction_options(args: &mut Vec<String>) -> Result<ReductionOptions, String> {
+    let default = ReductionOptions::default();
+    let kind = take_parsed_flag(args, "--reduction", default.kind)?;
+    let keep_fraction = take_parsed_flag(args, "--reduce-keep", default.keep_fraction)?;
+    if !(0.0..=1.0).contains(&keep_fraction) {
+        return Err(format!(
+            "--reduce-keep {} is not between 0 and 1",
+            keep_fraction
+        ));
     }
+    let first = take_parsed_flag(args, "--reduce-first", default.first)?;
+    let growth = take_parsed_flag(args, "--reduce-growth", default.growth)?;
+    if growth < 1.0 {
+        return Err(format!("--reduce-growth {} is less than 1", growth));
+    }
+    let bump_reasons = take_flag(args, "--reduce-bump-reasons");
+    Ok(ReductionOptions {
+        kind,
+        keep_fraction,
+        first,
+        growth,
+        bump_reasons,
+    })
 }
 
-fn sudoku() {
-    let formula = dimacs::read_string(dimacs::SUDOKU);
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+/// The positional arguments, once every flag has been taken.
+fn positional<const N: usize>(args: Vec<String>) -> Result<[String; N], String> {
+    if let Some(flag) = args.iter().find(|arg| arg.starts_with("--")) {
+        return Err(format!("unknown flag {}", flag));
+    }
+    let len = args.len();
+    args.try_into()
+        .map_err(|_| format!("expected {} arguments, got {}", N, len))
 }
 
-fn simple() {
-    let formula = vec![vec![1, 2], vec![-2, 3], vec![-1, -3]];
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+fn read_cnf(path: &str) -> Result<Vec<Vec<isize>>, String> {
+    dimacs::read_file(path).map_err(|err| format!("couldn't read {}: {}", path, err))
 }
 
-fn succ_eg() {
-    let formula = dimacs::read_string(dimacs::SUCC_EG);
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+/// The model as one v line, variables in increasing order, so the output
+/// of two runs can be diffed.
+fn print_model(model: &std::collections::BTreeMap<usize, bool>) {
+    let lits = model
+        .iter()
+        .map(|(&var, &value)| if value { var as isize } else { -(var as isize) })
+        .map(|lit| lit.to_string())
+        .collect::<Vec<_>>();
+    println!("v {} 0", lits.join(" "));
+}
+
+fn solve(mut args: Vec<String>) -> Result<ExitCode, String> {
+    let assumptions = match take_flag_value(&mut args, "--assume")? {
+        None => vec![],
+        Some(lits) => lits
+            .split(',')
+            .map(|lit| lit.trim().parse::<isize>().ok().filter(|&lit| lit != 0))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| format!("bad assumptions: {}", lits))?,
+    };
+    let no_model = take_flag(&mut args, "--no-model");
+    let stats = take_flag(&mut args, "--stats");
+    let trail_saving = take_flag(&mut args, "--trail-saving");
+    let memory_limit = take_flag_value(&mut args, "--memory-limit")?
+        .map(|mb| {
+            mb.parse::<usize>()
+                .map_err(|_| format!("bad value for --memory-limit: {}", mb))
+        })
+        .transpose()?;
+    #[cfg(feature = "tui")]
+    let tui = take_flag(&mut args, "--tui");
+    let restarts = take_restart_options(&mut args)?;
+    let reduction = take_reduction_options(&mut args)?;
+    let [path] = positional(args)?;
+    let mut solver = Default::new_from_vec(read_cnf(&path)?);
+    #[cfg(feature = "tui")]
+    if tui {
+        let every = Duration::from_millis(100);
+        pror::tui::attach(&mut solver, Box::new(std::io::stderr()), 80, every);
+    }
+    solver.set_learned_memory_limit(memory_limit.map(|mb| mb << 20));
+    solver.set_trail_saving(trail_saving);
+    solver.set_restart_schedule(restarts.schedule());
+    solver.set_reduction_policy(reduction.policy());
+    solver.set_reduce_schedule(reduction.first, reduction.growth);
+    solver.set_bump_reasons(reduction.bump_reasons);
+    println!("{}", solver.describe());
+    let result = solver
+        .run_with_literal_assumptions(&literals_of_ints(&assumptions))
+        .map_err(|AssumptionError::UnknownVariable(var)| {
+            format!("variable {} in --assume is not in {}", var, path)
+        })?;
+    if stats {
+        let stats = solver.stats();
+        println!("{}", stats.to_progress_line());
+        println!("{}", stats.profile.to_progress_line());
+    }
+    match result {
+        SatResult::Sat(model) => {
+            println!("s SATISFIABLE");
+            if !no_model {
+                print_model(&model);
+            }
+            Ok(ExitCode::from(10))
+        }
+        SatResult::UnsatCore(core) => {
+            println!("s UNSATISFIABLE");
+            if !assumptions.is_empty() {
+                let core = core.iter().map(|lit| lit.to_string()).collect::<Vec<_>>();
+                println!("c core {}", core.join(" "));
+            }
+            Ok(ExitCode::from(20))
+        }
+        SatResult::Unknown => {
+            println!("s UNKNOWN");
+            Ok(ExitCode::SUCCESS)
+        }
+    }
 }
 
-fn fail_eg() {
-    let formula = dimacs::read_string(dimacs::FAIL_EG);
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+fn verify_model(args: Vec<String>) -> Result<ExitCode, String> {
+    let [cnf_path, model_path] = positional(args)?;
+    let formula = read_cnf(&cnf_path)?;
+    let contents = std::fs::read_to_string(&model_path)
+        .map_err(|err| format!("couldn't read {}: {}", model_path, err))?;
+    let model = dimacs::read_model(&contents)
+        .map_err(|err| format!("bad model in {}: {:?}", model_path, err))?;
+    match first_unsatisfied(&formula, &model) {
+        None => {
+            println!("c model satisfies all {} clauses", formula.len());
+            Ok(ExitCode::SUCCESS)
+        }
+        Some(idx) => {
+            let lits = formula[idx]
+                .iter()
+                .map(|lit| lit.to_string())
+                .collect::<Vec<_>>();
+            println!(
+                "c model doesn't satisfy clause {}: {} 0",
+                idx + 1,
+                lits.join(" ")
+            );
+            Ok(ExitCode::FAILURE)
+        }
+    }
 }
 
-fn factor_sat_eg() {
-    let formula = dimacs::read_string(dimacs::FACTOR_1234321);
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+fn check_proof(args: Vec<String>) -> Result<ExitCode, String> {
+    let [cnf_path, proof_path] = positional(args)?;
+    let formula = read_cnf(&cnf_path)?;
+    let proof = drup::read_file(&proof_path)
+        .map_err(|err| format!("couldn't read {}: {}", proof_path, err))?;
+    match drup::check(&formula, &proof) {
+        Ok(()) => {
+            println!("s VERIFIED");
+            Ok(ExitCode::SUCCESS)
+        }
+        Err(err) => {
+            println!("s NOT VERIFIED");
+            println!("c {:?}", err);
+            Ok(ExitCode::FAILURE)
+        }
+    }
 }
 
-fn factor_unsat_eg() {
-    let formula = dimacs::read_string(dimacs::FACTOR_1235321);
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+fn generate(mut args: Vec<String>) -> Result<ExitCode, String> {
+    let vars = take_parsed_flag(&mut args, "--vars", 100)?;
+    let clauses = take_parsed_flag(&mut args, "--clauses", 426)?;
+    let k = take_parsed_flag(&mut args, "--k", 3)?;
+    let seed = take_parsed_flag(&mut args, "--seed", 0)?;
+    let [] = positional(args)?;
+    if k > vars {
+        return Err(format!("--k {} is more than --vars {}", k, vars));
+    }
+    let formula = generate::random_k_cnf(vars, clauses, k, seed);
+    println!("{}", dimacs::of_int_array_array(&formula));
+    Ok(ExitCode::SUCCESS)
 }
 
-fn subsets_100_eg() {
-    let formula = dimacs::read_string(dimacs::SUBSETS_100);
-    let res = Default::solve(formula);
-    println! {"res: {:?}", res};
+fn convert(args: Vec<String>) -> Result<ExitCode, String> {
+    let [path] = positional(args)?;
+    let aiger = aiger::read_file(&path)?;
+    println!("{}", dimacs::of_int_array_array(&aiger.to_cnf()));
+    Ok(ExitCode::SUCCESS)
 }
 
-fn useless_set_thing() {
-    let mut a = BitSet::create();
-    let mut b = BitSet::create();
-    a.set(1);
-    a.set(3);
-    a.set(101024);
-    a.set(323213123);
-    b.set(1);
-    b.set(2);
-    b.set(3);
-    b.set(10);
-    b.set(101024);
-    b.set(323213123);
-    a.iter_intersection_ge(&b, 101024).for_each(|x| {
-        println!("x: {}", x);
-    });
+struct BenchRow {
+    instance: String,
+    result: &'static str,
+    seconds: f64,
+    par2: f64,
+    stats: Stats,
 }
 
-// CR ewilliams: fails
-pub fn stepped4_incr() {
-    let formula = vec![
-        vec![3, -5, 6],
-        vec![-2, -5, -3, 6, -4],
-        vec![-5, 1, 4, -6],
-        vec![3, -4, 6, 1, 2, 5],
-        vec![-3, 4, -2, 6, -1, -5],
-        vec![3, -2, -6, 4],
-        vec![3, 2, -1],
-        vec![-6, -4, 5, -3],
-        vec![-3, 2, 5, 6, -1, -4],
-        vec![4, -2, -3, 5],
-        vec![3, -2, -1, -5, -6, -4],
-        vec![-2, -6],
-        vec![-1, -2, 4, 5],
-        vec![2, -4, 1, 3, -5, -6],
-    ];
-    let mut solver = Default::new_from_vec(vec![]);
+fn bench_instance(
+    path: &str,
+    options: SolverOptions,
+    timeout: Duration,
+) -> Result<BenchRow, String> {
+    let formula = read_cnf(path)?;
+    let mut solver = build_solver(options);
     for clause in formula {
         solver.add_clause(clause);
-        println!("{:?}", solver.run());
     }
-    println!("{:?}", solver.run());
+    let start = Instant::now();
+    let result = solver.solve_until(start + timeout);
+    let seconds = start.elapsed().as_secs_f64();
+    let (result, par2) = match result {
+        Some(SatResult::Sat(_)) => ("sat", seconds),
+        Some(SatResult::UnsatCore(_)) => ("unsat", seconds),
+        Some(SatResult::Unknown) => ("unknown", 2.0 * timeout.as_secs_f64()),
+        None => ("timeout", 2.0 * timeout.as_secs_f64()),
+    };
+    Ok(BenchRow {
+        instance: path.to_string(),
+        result,
+        seconds,
+        par2,
+        stats: solver.stats(),
+    })
 }
 
-pub fn main() {
-    // stepped1();
-    // stepped3();
-
-    // wikipedia();
-    // long();
-    // succ_eg();
-    // sudoku();
-    // simple();
-
-    // factor_sat_eg();
-    // factor_unsat_eg();
+fn bench(mut args: Vec<String>) -> Result<ExitCode, String> {
+    let timeout = take_parsed_flag(&mut args, "--timeout", 60.0)?;
+    let heuristic = take_parsed_flag(&mut args, "--heuristic", Heuristic::Vsids)?;
+    let json = take_flag(&mut args, "--json");
+    let restarts = take_restart_options(&mut args)?;
+    let reduction = take_reduction_options(&mut args)?;
+    let [dir] = positional(args)?;
+    let timeout = Duration::try_from_secs_f64(timeout)
+        .map_err(|_| format!("bad value for --timeout: {}", timeout))?;
+    let mut paths = std::fs::read_dir(&dir)
+        .map_err(|err| format!("couldn't read {}: {}", dir, err))?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path().to_string_lossy().into_owned())
+        .filter(|path| {
+            [".cnf", ".cnf.gz", ".cnf.xz"]
+                .iter()
+                .any(|ext| path.ends_with(ext))
+        })
+        .collect::<Vec<_>>();
+    paths.sort();
+    let options = SolverOptions {
+        heuristic,
+        restarts,
+        reduction,
+        ..SolverOptions::default()
+    };
+    let description = build_solver(options).describe();
+    if !json {
+        eprintln!("{}", description);
+        println!("instance,result,seconds,par2,decisions,propagations,conflicts,restarts,learned_clauses");
+    }
+    let mut rows = vec![];
+    for path in &paths {
+        let row = bench_instance(path, options, timeout)?;
+        if !json {
+            let stats = &row.stats;
+            println!(
+                "{},{},{:.3},{:.3},{},{},{},{},{}",
+                row.instance,
+                row.result,
+                row.seconds,
+                row.par2,
+                stats.decisions,
+                stats.propagations,
+                stats.conflicts,
+                stats.restarts,
+                stats.learned_clauses
+            );
+        }
+        rows.push(row);
+    }
+    let par2 = rows.iter().map(|row| row.par2).sum::<f64>();
+    let solved = rows
+        .iter()
+        .filter(|row| row.result == "sat" || row.result == "unsat")
+        .count();
+    if json {
+        let instances = rows
+            .iter()
+            .map(|row| {
+                format!(
+                    "{{\"instance\":{:?},\"result\":\"{}\",\"seconds\":{:.3},\"par2\":{:.3},\"stats\":{}}}",
+                    row.instance,
+                    row.result,
+                    row.seconds,
+                    row.par2,
+                    row.stats.to_json()
+                )
+            })
+            .collect::<Vec<_>>();
+        println!(
+            "{{\"solver\":{},\"timeout\":{},\"solved\":{},\"par2\":{:.3},\"instances\":[{}]}}",
+            description.to_json(),
+            timeout.as_secs_f64(),
+            solved,
+            par2,
+            instances.join(",")
+        );
+    } else {
+        eprintln!("c solved {} of {}, par2 {:.3}", solved, rows.len(), par2);
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
-    // subsets_100_eg();
+const REPL_HELP: &str = "\
+commands:
+  step            take one solver step
+  decide <lit>    step, deciding <lit> if a decision is due
+  trail           show the assigned literals with levels and reasons
+  watches <lit>   show the clauses watching <lit>
+  explain <lit>   show the decisions and propagations that made <lit> true
+  undo            undo the latest decision
+  run             solve from scratch
+  help
+  quit";
 
-    // fail_eg();
+fn repl(args: Vec<String>) -> Result<ExitCode, String> {
+    use std::io::BufRead;
+    let [path] = positional(args)?;
+    let mut solver = Default::new_from_vec(read_cnf(&path)?);
+    println!("{}", REPL_HELP);
+    for line in std::io::stdin().lock().lines() {
+        let Ok(line) = line else { break };
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let parse_literal = |tok: &str| tok.parse::<isize>().ok().filter(|&lit| lit != 0);
+        match words.as_slice() {
+            [] => (),
+            ["step"] => step_and_print(&mut solver, None),
+            ["decide", tok] => match parse_literal(tok) {
+                None => println!("not a literal: {}", tok),
+                Some(lit) if solver.reason(lit.unsigned_abs()).is_some() => {
+                    println!("{} is already assigned", lit.unsigned_abs())
+                }
+                Some(lit) => step_and_print(&mut solver, Some(lit.into())),
+            },
+            ["trail"] => {
+                for (literal, level) in solver.trail_literals() {
+                    let reason = solver.reason(literal.variable());
+                    println!("{} @ {}: {:?}", literal.to_string(), level, reason);
+                }
+            }
+            ["watches", tok] => match parse_literal(tok) {
+                None => println!("not a literal: {}", tok),
+                Some(lit) => {
+                    for clause in solver.watched_clause_literals(lit.into()) {
+                        let lits = clause.iter().map(|lit| lit.to_string()).collect::<Vec<_>>();
+                        println!("({})", lits.join(" "));
+                    }
+                }
+            },
+            ["explain", tok] => match parse_literal(tok) {
+                None => println!("not a literal: {}", tok),
+                Some(lit) => match solver.explain(lit.into()) {
+                    None => println!("{} isn't true", lit),
+                    Some(explanation) => print!("{}", explanation),
+                },
+            },
+            ["undo"] => {
+                solver.undo_decision();
+                println!("decision level {}", solver.decision_level());
+            }
+            ["run"] => {
+                println!("{:?}", solver.run());
+                if let Some(refutation) = solver.refutation() {
+                    print!("{}", refutation);
+                }
+            }
+            ["help"] => println!("{}", REPL_HELP),
+            ["quit"] => break,
+            _ => println!("unknown command, try help"),
+        }
+    }
+    Ok(ExitCode::SUCCESS)
+}
 
-    // useless_set_thing();
-    stepped4_incr();
+pub fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    if args.is_empty() {
+        eprintln!("{}", USAGE);
+        return ExitCode::from(2);
+    }
+    let command = args.remove(0);
+    let result = match command.as_str() {
+        "solve" => solve(args),
+        "verify-model" => verify_model(args),
+        "check-proof" => check_proof(args),
+        "generate" => generate(args),
+        "convert" => convert(args),
+        "repl" => repl(args),
+        "bench" => bench(args),
+        "help" | "--help" => {
+            println!("{}", USAGE);
+            Ok(ExitCode::SUCCESS)
+        }
+        _ => Err(format!("unknown command {}", command)),
+    };
+    match result {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            ExitCode::from(2)
+        }
+    }
 }
@@ -0,0 +1,200 @@
+//! Bitset words kept in a memory-mapped file once they get large, so the
+//! occurrence and clause bitsets of multi-million-variable instances can
+//! outgrow RAM and be paged out by the OS instead.
+
+use crate::fixed_bitset::{BlockBitSet, BlockStorage};
+use std::fs::{File, OpenOptions};
+use std::path::PathBuf;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Storage of this many words or fewer stays on the heap.
+pub const SPILL_WORDS: usize = 1 << 14;
+
+const WORD_BYTES: usize = std::mem::size_of::<usize>();
+
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+/// Where spilled words go; `std::env::temp_dir()` unless `PROR_MMAP_DIR`
+/// is set.
+fn spill_dir() -> PathBuf {
+    std::env::var_os("PROR_MMAP_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir)
+}
+
+/// A shared mapping of `capacity` words of a file that has already been
+/// unlinked, so it's reclaimed as soon as the mapping goes.
+struct Mapping {
+    ptr: NonNull<usize>,
+    capacity: usize,
+}
+
+impl Mapping {
+    fn new(capacity: usize) -> Self {
+        let path = spill_dir().join(format!(
+            "pror-{}-{}.words",
+            std::process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(&path)
+            .unwrap_or_else(|e| panic!("can't create {}: {}", path.display(), e));
+        let _ = std::fs::remove_file(&path);
+        Self::map(&file, capacity)
+    }
+
+    /// Extend `file` with zeros to `capacity` words and map all of it. The
+    /// mapping outlives `file`.
+    fn map(file: &File, capacity: usize) -> Self {
+        use std::os::unix::io::AsRawFd;
+        let bytes = capacity * WORD_BYTES;
+        file.set_len(bytes as u64)
+            .unwrap_or_else(|e| panic!("can't grow bitset file to {} bytes: {}", bytes, e));
+        let ptr = unsafe {
+            libc::mmap(
+                std::ptr::null_mut(),
+                bytes,
+                libc::PROT_READ | libc::PROT_WRITE,
+                libc::MAP_SHARED,
+                file.as_raw_fd(),
+                0,
+            )
+        };
+        if ptr == libc::MAP_FAILED {
+            panic!(
+                "mmap of {} bytes failed: {}",
+                bytes,
+                std::io::Error::last_os_error()
+            );
+        }
+        Mapping {
+            ptr: NonNull::new(ptr as *mut usize).unwrap(),
+            capacity,
+        }
+    }
+}
+
+impl Drop for Mapping {
+    fn drop(&mut self) {
+        unsafe {
+            libc::munmap(self.ptr.as_ptr() as *mut _, self.capacity * WORD_BYTES);
+        }
+    }
+}
+
+enum Words {
+    Heap(Vec<usize>),
+    Mapped(Mapping, usize),
+}
+
+/// `Vec<usize>` until it grows past `SPILL_WORDS`, then a mapping of an
+/// unlinked temporary file that doubles as it grows. Each spilled bitset is one mapping, so
+/// the OS's limit on mappings per process bounds how many can spill.
+pub struct MmapStorage {
+    words: Words,
+}
+
+// the mapping is owned exclusively, like a `Vec`'s buffer
+unsafe impl Send for MmapStorage {}
+unsafe impl Sync for MmapStorage {}
+
+impl MmapStorage {
+    /// Whether the words have moved out to a file.
+    pub fn is_mapped(&self) -> bool {
+        matches!(self.words, Words::Mapped(..))
+    }
+}
+
+impl std::default::Default for MmapStorage {
+    fn default() -> Self {
+        MmapStorage {
+            words: Words::Heap(Vec::new()),
+        }
+    }
+}
+
+impl Clone for MmapStorage {
+    fn clone(&self) -> Self {
+        let mut storage = MmapStorage::default();
+        storage.resize(self.len());
+        storage.copy_from_slice(self);
+        storage
+    }
+}
+
+impl std::ops::Deref for MmapStorage {
+    type Target = [usize];
+
+    fn deref(&self) -> &[usize] {
+        match &self.words {
+            Words::Heap(words) => words,
+            Words::Mapped(mapping, len) => unsafe {
+                std::slice::from_raw_parts(mapping.ptr.as_ptr(), *len)
+            },
+        }
+    }
+}
+
+impl std::ops::DerefMut for MmapStorage {
+    fn deref_mut(&mut self) -> &mut [usize] {
+        match &mut self.words {
+            Words::Heap(words) => words,
+            Words::Mapped(mapping, len) => unsafe {
+                std::slice::from_raw_parts_mut(mapping.ptr.as_ptr(), *len)
+            },
+        }
+    }
+}
+
+impl BlockStorage for MmapStorage {
+    fn resize(&mut self, words: usize) {
+        match &mut self.words {
+            Words::Heap(heap) if words <= SPILL_WORDS => heap.resize(words, 0),
+            Words::Heap(heap) => {
+                let mapping = Mapping::new(words.next_power_of_two());
+                unsafe {
+                    std::ptr::copy_nonoverlapping(heap.as_ptr(), mapping.ptr.as_ptr(), heap.len());
+                }
+                self.words = Words::Mapped(mapping, words);
+            }
+            Words::Mapped(mapping, len) if words <= mapping.capacity => {
+                // keep the words past the end zeroed for the next grow
+                if words < *len {
+                    unsafe {
+                        std::ptr::write_bytes(mapping.ptr.as_ptr().add(words), 0, *len - words);
+                    }
+                }
+                *len = words;
+            }
+            Words::Mapped(mapping, len) => {
+                let grown = Mapping::new(words.next_power_of_two());
+                unsafe {
+                    std::ptr::copy_nonoverlapping(mapping.ptr.as_ptr(), grown.ptr.as_ptr(), *len);
+                }
+                self.words = Words::Mapped(grown, words);
+            }
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        if let Words::Heap(heap) = &mut self.words {
+            heap.shrink_to_fit();
+        } else if self.len() <= SPILL_WORDS {
+            self.words = Words::Heap(self.to_vec());
+        }
+    }
+
+    fn memory_blocks(&self) -> usize {
+        match &self.words {
+            Words::Heap(heap) => heap.capacity(),
+            Words::Mapped(mapping, _) => mapping.capacity,
+        }
+    }
+}
+
+/// A bitset that spills to a memory-mapped file past `SPILL_WORDS` words.
+pub type MmapBitSet = BlockBitSet<MmapStorage>;
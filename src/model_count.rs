@@ -0,0 +1,28 @@
+/// An exact number of models. Counts grow exponentially with the number of
+/// unconstrained variables, so with the `bigint` feature they're arbitrary
+/// precision; without it they're `u128`, and a count past `u128::MAX` is
+/// reported as `None`.
+#[cfg(feature = "bigint")]
+pub type ModelCount = num_bigint::BigUint;
+#[cfg(not(feature = "bigint"))]
+pub type ModelCount = u128;
+
+pub fn one() -> ModelCount {
+    ModelCount::from(1u8)
+}
+
+pub fn is_zero(count: &ModelCount) -> bool {
+    *count == ModelCount::from(0u8)
+}
+
+/// `count * factor`.
+#[cfg(feature = "bigint")]
+pub fn times(count: ModelCount, factor: usize) -> Option<ModelCount> {
+    Some(count * factor)
+}
+
+/// `count * factor`, or `None` past `u128::MAX`.
+#[cfg(not(feature = "bigint"))]
+pub fn times(count: ModelCount, factor: usize) -> Option<ModelCount> {
+    count.checked_mul(factor as u128)
+}
@@ -0,0 +1,71 @@
+use crate::sat::Literal;
+
+/// Clause indices by literal, kept exact as clauses are added and deleted:
+/// what subsumption, variable elimination and blocked clause elimination
+/// walk. Unlike the solver's per-literal bitsets, a list only costs as much
+/// as its literal's occurrences, so visiting the clauses of a rare literal
+/// is cheap.
+#[derive(Clone, Debug, Default)]
+pub struct OccurrenceLists {
+    /// Indexed by `Literal::code`, in no particular order.
+    lists: Vec<Vec<usize>>,
+    num_occurrences: usize,
+}
+
+impl OccurrenceLists {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record that clause `idx` has `literals`.
+    pub fn add(&mut self, idx: usize, literals: impl IntoIterator<Item = Literal>) {
+        for lit in literals {
+            if lit.code() >= self.lists.len() {
+                self.lists.resize_with((lit.code() + 1) | 1, Vec::new);
+            }
+            self.lists[lit.code()].push(idx);
+            self.num_occurrences += 1;
+        }
+    }
+
+    /// Forget clause `idx`, which must have been added with `literals`.
+    pub fn remove(&mut self, idx: usize, literals: impl IntoIterator<Item = Literal>) {
+        for lit in literals {
+            let list = &mut self.lists[lit.code()];
+            let pos = list
+                .iter()
+                .position(|&other| other == idx)
+                .unwrap_or_else(|| {
+                    panic!("clause {} isn't in the list of {}", idx, lit.to_string())
+                });
+            list.swap_remove(pos);
+            self.num_occurrences -= 1;
+        }
+    }
+
+    /// The clauses containing `literal`.
+    pub fn get(&self, literal: Literal) -> &[usize] {
+        self.lists
+            .get(literal.code())
+            .map_or(&[], |list| list.as_slice())
+    }
+
+    pub fn count(&self, literal: Literal) -> usize {
+        self.get(literal).len()
+    }
+
+    /// The literal of `literals` occurring in the fewest clauses, the one to
+    /// start from when looking for clauses containing all of them.
+    pub fn rarest(&self, literals: impl IntoIterator<Item = Literal>) -> Option<Literal> {
+        literals.into_iter().min_by_key(|&lit| self.count(lit))
+    }
+
+    /// The total number of literal occurrences.
+    pub fn len(&self) -> usize {
+        self.num_occurrences
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.num_occurrences == 0
+    }
+}
@@ -0,0 +1,92 @@
+use crate::cdcl::Default;
+use crate::sat::SatResult;
+use std::collections::BTreeMap;
+
+#[derive(Debug)]
+pub enum OptimizeResult {
+    Optimal {
+        cost: usize,
+        model: BTreeMap<usize, bool>,
+    },
+    /// The solver gave up (see `SatResult::Unknown`) after finding this
+    /// model but before proving it optimal.
+    Feasible {
+        cost: usize,
+        model: BTreeMap<usize, bool>,
+    },
+    Unsat,
+    /// The solver gave up before finding any model.
+    Unknown,
+}
+
+fn holds(lit: isize, model: &BTreeMap<usize, bool>) -> bool {
+    model.get(&lit.unsigned_abs()).copied().unwrap_or(false) == (lit > 0)
+}
+
+/// Sinz sequential counter over `lits`, encoding only the direction needed for
+/// upper bounds: the `j`th returned variable is forced true whenever at least
+/// `j + 1` of `lits` are true. Fresh variables are numbered from `*next_var`.
+fn sequential_counter(lits: &[isize], next_var: &mut usize) -> (Vec<Vec<isize>>, Vec<isize>) {
+    let mut clauses = vec![];
+    let mut prev: Vec<isize> = vec![];
+    for (i, &lit) in lits.iter().enumerate() {
+        let row = (0..=i)
+            .map(|_| {
+                let var = *next_var as isize;
+                *next_var += 1;
+                var
+            })
+            .collect::<Vec<_>>();
+        clauses.push(vec![-lit, row[0]]);
+        for j in 0..i {
+            clauses.push(vec![-prev[j], row[j]]);
+            clauses.push(vec![-lit, -prev[j], row[j + 1]]);
+        }
+        prev = row;
+    }
+    (clauses, prev)
+}
+
+/// Find a model of `formula` minimising the number of true literals in
+/// `objective`. Each model found tightens the bound on the counter outputs
+/// with a unit clause and the same incremental solver is re-run until the
+/// bound can no longer be met, or until the solver gives up.
+pub fn optimize(mut formula: Vec<Vec<isize>>, objective: &[isize]) -> OptimizeResult {
+    let max_var = formula
+        .iter()
+        .flatten()
+        .chain(objective.iter())
+        .map(|lit| lit.unsigned_abs())
+        .max()
+        .unwrap_or(0);
+    let mut next_var = max_var + 1;
+    let (counter, at_least) = sequential_counter(objective, &mut next_var);
+    formula.extend(counter);
+
+    let mut solver = Default::new_from_vec(formula);
+    let mut best = OptimizeResult::Unsat;
+    loop {
+        let model = match solver.run() {
+            SatResult::UnsatCore(_) => return best,
+            SatResult::Sat(model) => model,
+            SatResult::Unknown => {
+                return match best {
+                    OptimizeResult::Optimal { cost, model } => {
+                        OptimizeResult::Feasible { cost, model }
+                    }
+                    _ => OptimizeResult::Unknown,
+                }
+            }
+        };
+        let cost = objective.iter().filter(|&&lit| holds(lit, &model)).count();
+        let model = model
+            .into_iter()
+            .filter(|&(var, _)| var <= max_var)
+            .collect();
+        best = OptimizeResult::Optimal { cost, model };
+        if cost == 0 {
+            return best;
+        }
+        solver.add_clause(vec![-at_least[cost - 1]]);
+    }
+}
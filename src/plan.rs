@@ -0,0 +1,200 @@
+//! Experimental: planning as satisfiability for STRIPS problems. A plan of
+//! `horizon` steps is a model of the initial state, `horizon` copies of the
+//! transition clauses and the goal; the horizon grows one step at a time on
+//! a single incremental solver, the goal being assumed rather than added.
+
+use crate::cdcl::Default;
+use crate::sat::SatResult;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A STRIPS action over fluents numbered from 0.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Action {
+    pub name: String,
+    /// Fluents that must hold for the action to be taken.
+    pub pre: Vec<usize>,
+    /// Fluents that hold after it.
+    pub add: Vec<usize>,
+    /// Fluents that don't hold after it, unless it also adds them.
+    pub del: Vec<usize>,
+}
+
+/// A STRIPS problem: `init` lists the fluents true at the start, all others
+/// being false, and a plan must make every fluent in `goal` true.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Problem {
+    pub num_fluents: usize,
+    pub actions: Vec<Action>,
+    pub init: Vec<usize>,
+    pub goal: Vec<usize>,
+}
+
+/// The actions taken at each step. Actions in one step don't interfere, so
+/// they can be taken in any order.
+pub type Plan = Vec<Vec<usize>>;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlanResult {
+    Found(Plan),
+    /// No plan within the horizon.
+    NotFound,
+    /// The solver gave up (see `SatResult::Unknown`) before finding a plan
+    /// or ruling out every horizon.
+    Unknown,
+}
+
+fn holds(lit: isize, model: &BTreeMap<usize, bool>) -> bool {
+    model.get(&lit.unsigned_abs()).copied().unwrap_or(false) == (lit > 0)
+}
+
+impl Problem {
+    fn vars_per_step(&self) -> usize {
+        self.num_fluents + self.actions.len()
+    }
+
+    /// The variable of `fluent` holding before step `step`, or after the
+    /// last one when `step` is the horizon.
+    pub fn fluent(&self, fluent: usize, step: usize) -> isize {
+        (step * self.vars_per_step() + fluent + 1) as isize
+    }
+
+    /// The variable of `action` being taken at step `step`.
+    pub fn action(&self, action: usize, step: usize) -> isize {
+        (step * self.vars_per_step() + self.num_fluents + action + 1) as isize
+    }
+
+    /// Unit clauses fixing every fluent before step 0.
+    pub fn initial_clauses(&self) -> Vec<Vec<isize>> {
+        let init = self.init.iter().collect::<BTreeSet<_>>();
+        (0..self.num_fluents)
+            .map(|fluent| {
+                let var = self.fluent(fluent, 0);
+                vec![if init.contains(&fluent) { var } else { -var }]
+            })
+            .collect()
+    }
+
+    /// Two actions interfere when one deletes a fluent the other needs or
+    /// adds, so taking both in a step would depend on their order.
+    fn interfere(&self, a: usize, b: usize) -> bool {
+        let deletes_from = |a: &Action, b: &Action| {
+            a.del
+                .iter()
+                .any(|fluent| b.pre.contains(fluent) || b.add.contains(fluent))
+        };
+        let (a, b) = (&self.actions[a], &self.actions[b]);
+        deletes_from(a, b) || deletes_from(b, a)
+    }
+
+    /// The clauses taking the fluents before `step` to those after it:
+    /// preconditions and effects of the actions taken, explanatory frame
+    /// axioms (a fluent only changes when an action changes it) and mutual
+    /// exclusion of interfering actions.
+    pub fn step_clauses(&self, step: usize) -> Vec<Vec<isize>> {
+        let mut clauses = vec![];
+        let mut adders = vec![vec![]; self.num_fluents];
+        let mut deleters = vec![vec![]; self.num_fluents];
+        for (idx, action) in self.actions.iter().enumerate() {
+            let taken = self.action(idx, step);
+            for &fluent in &action.pre {
+                clauses.push(vec![-taken, self.fluent(fluent, step)]);
+            }
+            for &fluent in &action.add {
+                clauses.push(vec![-taken, self.fluent(fluent, step + 1)]);
+                adders[fluent].push(taken);
+            }
+            for &fluent in action.del.iter().filter(|f| !action.add.contains(f)) {
+                clauses.push(vec![-taken, -self.fluent(fluent, step + 1)]);
+                deleters[fluent].push(taken);
+            }
+        }
+        for fluent in 0..self.num_fluents {
+            let (before, after) = (self.fluent(fluent, step), self.fluent(fluent, step + 1));
+            let mut became_true = vec![before, -after];
+            became_true.extend(&adders[fluent]);
+            clauses.push(became_true);
+            let mut became_false = vec![-before, after];
+            became_false.extend(&deleters[fluent]);
+            clauses.push(became_false);
+        }
+        for a in 0..self.actions.len() {
+            for b in a + 1..self.actions.len() {
+                if self.interfere(a, b) {
+                    clauses.push(vec![-self.action(a, step), -self.action(b, step)]);
+                }
+            }
+        }
+        clauses
+    }
+
+    /// The goal fluents after `horizon` steps, as literals to assume.
+    pub fn goal(&self, horizon: usize) -> Vec<isize> {
+        self.goal
+            .iter()
+            .map(|&fluent| self.fluent(fluent, horizon))
+            .collect()
+    }
+
+    /// The whole encoding for a fixed `horizon`, the goal as unit clauses.
+    pub fn encode(&self, horizon: usize) -> Vec<Vec<isize>> {
+        let mut clauses = self.initial_clauses();
+        for step in 0..horizon {
+            clauses.extend(self.step_clauses(step));
+        }
+        clauses.extend(self.goal(horizon).into_iter().map(|lit| vec![lit]));
+        clauses
+    }
+
+    /// Read the actions taken at each of `horizon` steps out of a model.
+    pub fn extract(&self, model: &BTreeMap<usize, bool>, horizon: usize) -> Plan {
+        (0..horizon)
+            .map(|step| {
+                (0..self.actions.len())
+                    .filter(|&action| holds(self.action(action, step), model))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Run `plan` from the initial state, giving the fluents true at the
+    /// end, or `None` if some action's preconditions don't hold.
+    pub fn simulate(&self, plan: &[Vec<usize>]) -> Option<BTreeSet<usize>> {
+        let mut state = self.init.iter().copied().collect::<BTreeSet<_>>();
+        for step in plan {
+            let actions = step.iter().map(|&idx| &self.actions[idx]);
+            if !actions
+                .clone()
+                .all(|action| action.pre.iter().all(|fluent| state.contains(fluent)))
+            {
+                return None;
+            }
+            for action in actions.clone() {
+                for fluent in &action.del {
+                    state.remove(fluent);
+                }
+            }
+            for action in actions {
+                state.extend(&action.add);
+            }
+        }
+        Some(state)
+    }
+}
+
+/// Find a plan with the fewest steps, trying horizons up to `max_horizon`.
+/// Each failed horizon is extended by another step's clauses, so what the
+/// solver learned about shorter horizons carries over.
+pub fn plan(problem: &Problem, max_horizon: usize) -> PlanResult {
+    let mut solver = Default::new_from_vec(problem.initial_clauses());
+    for horizon in 0..=max_horizon {
+        match solver.run_with_assumptions(&problem.goal(horizon)) {
+            SatResult::Sat(model) => return PlanResult::Found(problem.extract(&model, horizon)),
+            SatResult::UnsatCore(_) => (),
+            SatResult::Unknown => return PlanResult::Unknown,
+        }
+        for clause in problem.step_clauses(horizon) {
+            solver.add_clause(clause);
+        }
+    }
+    PlanResult::NotFound
+}
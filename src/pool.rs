@@ -1,30 +1,116 @@
+/// Objects that can be put back in their freshly-created state, so that a
+/// pool can hand them out again without the caller clearing them.
+pub trait Reset {
+    fn reset(&mut self);
+}
+
+impl<T> Reset for Vec<T> {
+    fn reset(&mut self) {
+        self.clear()
+    }
+}
+
+use std::sync::Mutex;
+
 #[derive(Clone)]
 pub struct Pool<T> {
     free: Vec<T>,
+    /// Released items past this many are dropped instead of kept.
+    max_retained: Option<usize>,
+    stats: PoolStats,
+}
+
+/// How well a `Pool` is being reused.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStats {
+    /// Acquires served from the free list.
+    pub hits: u64,
+    /// Acquires that had to call the factory.
+    pub misses: u64,
+    /// Releases dropped because the pool was full.
+    pub dropped: u64,
 }
 
 impl<T> Pool<T> {
     pub fn new() -> Self {
-        Pool { free: Vec::new() }
+        Pool {
+            free: Vec::new(),
+            max_retained: None,
+            stats: PoolStats::default(),
+        }
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
         Pool {
             free: Vec::with_capacity(capacity),
+            max_retained: None,
+            stats: PoolStats::default(),
         }
     }
 
+    /// Keep at most `max` released items, dropping any beyond that now and
+    /// on later releases. `None` keeps everything.
+    pub fn set_max_retained(&mut self, max: Option<usize>) {
+        self.max_retained = max;
+        if let Some(max) = max {
+            self.shrink_to(max);
+        }
+    }
+
+    pub fn max_retained(&self) -> Option<usize> {
+        self.max_retained
+    }
+
+    /// Drop free items until at most `len` remain.
+    pub fn shrink_to(&mut self, len: usize) {
+        self.free.truncate(len);
+        self.free.shrink_to_fit();
+    }
+
+    /// Pop a recycled item, counting a hit or a miss.
+    fn take(&mut self) -> Option<T> {
+        let item = self.free.pop();
+        match item {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+        item
+    }
+
     pub fn acquire<F>(&mut self, factory: F) -> T
     where
         F: FnOnce() -> T,
     {
-        self.free.pop().unwrap_or_else(factory)
+        self.take().unwrap_or_else(factory)
+    }
+
+    /// Like `acquire`, but reset a recycled item before handing it out.
+    pub fn acquire_reset<F>(&mut self, factory: F) -> T
+    where
+        T: Reset,
+        F: FnOnce() -> T,
+    {
+        match self.take() {
+            Some(mut item) => {
+                item.reset();
+                item
+            }
+            None => factory(),
+        }
     }
 
     pub fn release(&mut self, item: T) {
+        if self.max_retained.is_some_and(|max| self.free.len() >= max) {
+            self.stats.dropped += 1;
+            return;
+        }
         self.free.push(item);
     }
 
+    pub fn stats(&self) -> PoolStats {
+        self.stats
+    }
+
     pub fn len(&self) -> usize {
         self.free.len()
     }
@@ -39,3 +125,80 @@ impl<T> Default for Pool<T> {
         Pool::new()
     }
 }
+
+/// A `Pool` behind a mutex, for recycling objects across threads; share it
+/// with an `Arc`. Factories and resets run outside the lock.
+#[derive(Default)]
+pub struct SyncPool<T> {
+    inner: Mutex<Pool<T>>,
+}
+
+impl<T> SyncPool<T> {
+    pub fn new() -> Self {
+        SyncPool {
+            inner: Mutex::new(Pool::new()),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        SyncPool {
+            inner: Mutex::new(Pool::with_capacity(capacity)),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Pool<T>> {
+        // nothing can panic while the free list is half-updated
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn set_max_retained(&self, max: Option<usize>) {
+        self.lock().set_max_retained(max)
+    }
+
+    pub fn max_retained(&self) -> Option<usize> {
+        self.lock().max_retained()
+    }
+
+    pub fn shrink_to(&self, len: usize) {
+        self.lock().shrink_to(len)
+    }
+
+    pub fn acquire<F>(&self, factory: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let item = self.lock().take();
+        item.unwrap_or_else(factory)
+    }
+
+    pub fn acquire_reset<F>(&self, factory: F) -> T
+    where
+        T: Reset,
+        F: FnOnce() -> T,
+    {
+        let item = self.lock().take();
+        match item {
+            Some(mut item) => {
+                item.reset();
+                item
+            }
+            None => factory(),
+        }
+    }
+
+    pub fn release(&self, item: T) {
+        self.lock().release(item)
+    }
+
+    pub fn stats(&self) -> PoolStats {
+        self.lock().stats()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+}
@@ -0,0 +1,243 @@
+use crate::cdcl::Default;
+use crate::sat::SatResult;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quantifier {
+    Exists,
+    Forall,
+}
+
+#[derive(Clone, Debug)]
+pub struct Qbf {
+    pub max_var: usize,
+    pub prefix: Vec<(Quantifier, Vec<usize>)>,
+    pub clauses: Vec<Vec<isize>>,
+}
+
+#[derive(Debug)]
+pub enum QbfResult {
+    /// The formula holds. For `∃X∀Y` this carries the witnessing assignment to `X`.
+    True(BTreeMap<usize, bool>),
+    /// The formula is false. For `∀X∃Y` this carries the refuting assignment to `X`.
+    False(BTreeMap<usize, bool>),
+    /// One of the solvers gave up (see `SatResult::Unknown`).
+    Unknown,
+}
+
+/// Parse a QDIMACS string. Comment and header lines are skipped, `e`/`a` lines
+/// build the prefix and everything else is read as a clause.
+pub fn read_string(s: &str) -> Qbf {
+    let mut prefix: Vec<(Quantifier, Vec<usize>)> = Vec::new();
+    let mut clauses = Vec::new();
+    let mut max_var = 0;
+
+    for line in s.lines() {
+        let line = line.trim_start();
+        if line.starts_with('c') || line.starts_with('p') {
+            continue;
+        }
+        let quantifier = if line.starts_with('e') {
+            Some(Quantifier::Exists)
+        } else if line.starts_with('a') {
+            Some(Quantifier::Forall)
+        } else {
+            None
+        };
+        let lits: Vec<isize> = line
+            .split_whitespace()
+            .filter_map(|tok| tok.parse::<isize>().ok())
+            .filter(|&lit| lit != 0)
+            .collect();
+        for lit in &lits {
+            max_var = max_var.max(lit.unsigned_abs());
+        }
+        match quantifier {
+            Some(quantifier) => {
+                let vars = lits.iter().map(|lit| lit.unsigned_abs());
+                match prefix.last_mut() {
+                    Some((last, block)) if *last == quantifier => block.extend(vars),
+                    _ => prefix.push((quantifier, vars.collect())),
+                }
+            }
+            None if !lits.is_empty() => clauses.push(lits),
+            None => (),
+        }
+    }
+
+    Qbf {
+        max_var,
+        prefix,
+        clauses,
+    }
+}
+
+/// Read an entire file and parse it as above.
+pub fn read_file(path: &str) -> io::Result<Qbf> {
+    let contents = fs::read_to_string(path)?;
+    Ok(read_string(&contents))
+}
+
+impl Qbf {
+    /// The prefix restricted to variables that occur in the matrix, with free
+    /// variables bound existentially at the outermost level and adjacent
+    /// blocks of the same quantifier merged.
+    pub fn normalized_prefix(&self) -> Vec<(Quantifier, Vec<usize>)> {
+        let occurring = self
+            .clauses
+            .iter()
+            .flatten()
+            .map(|lit| lit.unsigned_abs())
+            .collect::<BTreeSet<_>>();
+        let bound = self
+            .prefix
+            .iter()
+            .flat_map(|(_, vars)| vars.iter().copied())
+            .collect::<BTreeSet<_>>();
+        let free = occurring.difference(&bound).copied().collect::<Vec<_>>();
+
+        let mut res: Vec<(Quantifier, Vec<usize>)> = Vec::new();
+        let blocks = std::iter::once((Quantifier::Exists, free)).chain(self.prefix.iter().cloned());
+        for (quantifier, vars) in blocks {
+            let vars = vars
+                .into_iter()
+                .filter(|var| occurring.contains(var))
+                .collect::<Vec<_>>();
+            if vars.is_empty() {
+                continue;
+            }
+            match res.last_mut() {
+                Some((last, block)) if *last == quantifier => block.extend(vars),
+                _ => res.push((quantifier, vars)),
+            }
+        }
+        res
+    }
+}
+
+fn model_of(vars: &[usize], res: &BTreeMap<usize, bool>) -> BTreeMap<usize, bool> {
+    vars.iter()
+        .map(|&var| (var, res.get(&var).copied().unwrap_or(false)))
+        .collect()
+}
+
+fn assumptions_of(model: &BTreeMap<usize, bool>) -> Vec<isize> {
+    model
+        .iter()
+        .map(|(&var, &value)| if value { var as isize } else { -(var as isize) })
+        .collect()
+}
+
+fn lit_holds(lit: isize, model: &BTreeMap<usize, bool>) -> Option<bool> {
+    model
+        .get(&lit.unsigned_abs())
+        .map(|&value| value == (lit > 0))
+}
+
+/// Clauses of `clauses` not already satisfied by `model`, with the literals
+/// falsified by `model` removed.
+fn restrict(clauses: &[Vec<isize>], model: &BTreeMap<usize, bool>) -> Vec<Vec<isize>> {
+    clauses
+        .iter()
+        .filter(|clause| {
+            !clause
+                .iter()
+                .any(|&lit| lit_holds(lit, model) == Some(true))
+        })
+        .map(|clause| {
+            clause
+                .iter()
+                .copied()
+                .filter(|&lit| lit_holds(lit, model).is_none())
+                .collect()
+        })
+        .collect()
+}
+
+/// Tseitin encoding of the negation of `clauses`: one fresh selector per
+/// clause, at least one selector true, and a true selector falsifies every
+/// literal of its clause. Selectors are numbered from `*next_var` upwards.
+fn negate(clauses: &[Vec<isize>], next_var: &mut usize) -> Vec<Vec<isize>> {
+    let mut res = Vec::with_capacity(clauses.len() + 1);
+    let mut selectors = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+        let selector = *next_var as isize;
+        *next_var += 1;
+        selectors.push(selector);
+        for &lit in clause {
+            res.push(vec![-selector, -lit]);
+        }
+    }
+    res.push(selectors);
+    res
+}
+
+fn solve_exists_forall(qbf: &Qbf, xs: &[usize], ys: &[usize]) -> QbfResult {
+    let mut next_var = qbf.max_var + 1;
+    let mut counterexamples = Default::new_from_vec(negate(&qbf.clauses, &mut next_var));
+    let mut candidates = Default::new_from_vec(vec![]);
+    loop {
+        let x = match candidates.run() {
+            SatResult::UnsatCore(_) => return QbfResult::False(BTreeMap::new()),
+            SatResult::Sat(res) => model_of(xs, &res),
+            SatResult::Unknown => return QbfResult::Unknown,
+        };
+        let y = match counterexamples.run_with_assumptions(&assumptions_of(&x)) {
+            SatResult::UnsatCore(_) => return QbfResult::True(x),
+            SatResult::Sat(res) => model_of(ys, &res),
+            SatResult::Unknown => return QbfResult::Unknown,
+        };
+        // every candidate must now also survive `y`
+        for clause in restrict(&qbf.clauses, &y) {
+            if clause.is_empty() {
+                return QbfResult::False(BTreeMap::new());
+            }
+            candidates.add_clause(clause);
+        }
+    }
+}
+
+fn solve_forall_exists(qbf: &Qbf, xs: &[usize], ys: &[usize]) -> QbfResult {
+    let mut next_var = qbf.max_var + 1;
+    let mut matrix = Default::new_from_vec(qbf.clauses.clone());
+    let mut candidates = Default::new_from_vec(vec![]);
+    loop {
+        let x = match candidates.run() {
+            SatResult::UnsatCore(_) => return QbfResult::True(BTreeMap::new()),
+            SatResult::Sat(res) => model_of(xs, &res),
+            SatResult::Unknown => return QbfResult::Unknown,
+        };
+        let y = match matrix.run_with_assumptions(&assumptions_of(&x)) {
+            SatResult::UnsatCore(_) => return QbfResult::False(x),
+            SatResult::Sat(res) => model_of(ys, &res),
+            SatResult::Unknown => return QbfResult::Unknown,
+        };
+        // the next candidate must be one that `y` does not answer
+        let remaining = restrict(&qbf.clauses, &y);
+        if remaining.is_empty() {
+            return QbfResult::True(BTreeMap::new());
+        }
+        for clause in negate(&remaining, &mut next_var) {
+            candidates.add_clause(clause);
+        }
+    }
+}
+
+/// Decide a QBF with at most two quantifier blocks (after normalization) by
+/// counterexample-guided abstraction refinement: one solver proposes
+/// assignments to the outer block and a second checks them against the inner
+/// block, feeding back counterexamples. Returns `None` for deeper prefixes.
+pub fn solve(qbf: &Qbf) -> Option<QbfResult> {
+    let prefix = qbf.normalized_prefix();
+    let res = match prefix.as_slice() {
+        [] => solve_exists_forall(qbf, &[], &[]),
+        [(Quantifier::Exists, xs)] => solve_exists_forall(qbf, xs, &[]),
+        [(Quantifier::Forall, xs)] => solve_forall_exists(qbf, xs, &[]),
+        [(Quantifier::Exists, xs), (_, ys)] => solve_exists_forall(qbf, xs, ys),
+        [(Quantifier::Forall, xs), (_, ys)] => solve_forall_exists(qbf, xs, ys),
+        _ => return None,
+    };
+    Some(res)
+}
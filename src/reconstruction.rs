@@ -0,0 +1,43 @@
+use crate::sat::Literal;
+use std::collections::BTreeMap;
+
+/// Clauses removed by simplifications (variable elimination, blocked clause
+/// elimination, equivalent literal substitution), each with a witness
+/// literal. A model of the simplified formula is turned into one of the
+/// original by walking the stack from the top and flipping the witness of
+/// every clause the model falsifies.
+#[derive(Clone, Debug, Default)]
+pub struct Reconstruction {
+    stack: Vec<(Literal, Vec<Literal>)>,
+}
+
+impl Reconstruction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `witness` must be one of the literals of `clause`.
+    pub fn push(&mut self, witness: Literal, clause: Vec<Literal>) {
+        debug_assert!(clause.contains(&witness));
+        self.stack.push((witness, clause));
+    }
+
+    pub fn len(&self) -> usize {
+        self.stack.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.stack.is_empty()
+    }
+
+    pub fn extend(&self, model: &mut BTreeMap<usize, bool>) {
+        for (witness, clause) in self.stack.iter().rev() {
+            let satisfied = clause
+                .iter()
+                .any(|lit| model.get(&lit.variable()) == Some(&lit.value()));
+            if !satisfied {
+                model.insert(witness.variable(), witness.value());
+            }
+        }
+    }
+}
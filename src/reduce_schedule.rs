@@ -0,0 +1,46 @@
+/// When to reduce the learned clause database: once `first` clauses have
+/// been learned, then after `growth` times as many as the time before, so
+/// reductions get rarer as the search goes on.
+#[derive(Clone, Debug)]
+pub struct ReduceSchedule {
+    first: f64,
+    limit: f64,
+    growth: f64,
+    learned_since_reduction: u64,
+}
+
+impl ReduceSchedule {
+    pub fn new(first: u64, growth: f64) -> Self {
+        assert!(growth >= 1.0, "growth {} would shrink the limit", growth);
+        Self {
+            first: first as f64,
+            limit: first as f64,
+            growth,
+            learned_since_reduction: 0,
+        }
+    }
+
+    /// The number of learned clauses to wait for before the next reduction.
+    pub fn limit(&self) -> u64 {
+        self.limit as u64
+    }
+
+    pub fn learned(&mut self) {
+        self.learned_since_reduction += 1;
+    }
+
+    pub fn due(&self) -> bool {
+        self.learned_since_reduction >= self.limit()
+    }
+
+    pub fn reduced(&mut self) {
+        self.learned_since_reduction = 0;
+        self.limit *= self.growth;
+    }
+
+    /// Go back to the first limit, with nothing learned yet.
+    pub fn reset(&mut self) {
+        self.limit = self.first;
+        self.learned_since_reduction = 0;
+    }
+}
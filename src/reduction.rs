@@ -0,0 +1,195 @@
+use std::cmp::Ordering;
+
+/// What a reduction policy gets to see of a deletable learned clause.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReductionCandidate {
+    pub activity: f64,
+    pub lbd: usize,
+    pub len: usize,
+}
+
+/// Which learned clauses to delete when the clause database is reduced.
+pub trait ReductionPolicy: Send {
+    /// `Less` if `a` is less worth keeping than `b`.
+    fn compare(&self, a: &ReductionCandidate, b: &ReductionCandidate) -> Ordering;
+
+    /// The fraction of candidates kept at each reduction.
+    fn keep_fraction(&self) -> f64;
+
+    /// The policy and its parameters, such as `lbd(0.5)`.
+    fn describe(&self) -> String;
+
+    /// A copy for a forked solver.
+    fn boxed_clone(&self) -> Box<dyn ReductionPolicy>;
+
+    /// Indices into `candidates` of the clauses to delete: the least worth
+    /// keeping, leaving `keep_fraction` of them.
+    fn select(&self, candidates: &[ReductionCandidate]) -> Vec<usize> {
+        let mut order = (0..candidates.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| self.compare(&candidates[a], &candidates[b]));
+        let num_to_keep = (candidates.len() as f64 * self.keep_fraction()).ceil() as usize;
+        order.truncate(candidates.len().saturating_sub(num_to_keep));
+        order
+    }
+}
+
+fn check_keep_fraction(keep_fraction: f64) -> f64 {
+    assert!(
+        (0.0..=1.0).contains(&keep_fraction),
+        "can't keep a fraction {} of the clauses",
+        keep_fraction
+    );
+    keep_fraction
+}
+
+/// Delete the clauses least involved in recent conflicts.
+#[derive(Clone, Debug)]
+pub struct ByActivity {
+    keep_fraction: f64,
+}
+
+impl ByActivity {
+    pub fn new(keep_fraction: f64) -> Self {
+        Self {
+            keep_fraction: check_keep_fraction(keep_fraction),
+        }
+    }
+}
+
+impl ReductionPolicy for ByActivity {
+    fn compare(&self, a: &ReductionCandidate, b: &ReductionCandidate) -> Ordering {
+        a.activity.total_cmp(&b.activity)
+    }
+
+    fn keep_fraction(&self) -> f64 {
+        self.keep_fraction
+    }
+
+    fn describe(&self) -> String {
+        format!("activity({})", self.keep_fraction)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ReductionPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+/// Delete the clauses spanning the most decision levels, breaking ties by
+/// activity.
+#[derive(Clone, Debug)]
+pub struct ByLbd {
+    keep_fraction: f64,
+}
+
+impl ByLbd {
+    pub fn new(keep_fraction: f64) -> Self {
+        Self {
+            keep_fraction: check_keep_fraction(keep_fraction),
+        }
+    }
+}
+
+impl ReductionPolicy for ByLbd {
+    fn compare(&self, a: &ReductionCandidate, b: &ReductionCandidate) -> Ordering {
+        b.lbd
+            .cmp(&a.lbd)
+            .then_with(|| a.activity.total_cmp(&b.activity))
+    }
+
+    fn keep_fraction(&self) -> f64 {
+        self.keep_fraction
+    }
+
+    fn describe(&self) -> String {
+        format!("lbd({})", self.keep_fraction)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ReductionPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+/// Delete the longest clauses, breaking ties by activity.
+#[derive(Clone, Debug)]
+pub struct BySize {
+    keep_fraction: f64,
+}
+
+impl BySize {
+    pub fn new(keep_fraction: f64) -> Self {
+        Self {
+            keep_fraction: check_keep_fraction(keep_fraction),
+        }
+    }
+}
+
+impl ReductionPolicy for BySize {
+    fn compare(&self, a: &ReductionCandidate, b: &ReductionCandidate) -> Ordering {
+        b.len
+            .cmp(&a.len)
+            .then_with(|| a.activity.total_cmp(&b.activity))
+    }
+
+    fn keep_fraction(&self) -> f64 {
+        self.keep_fraction
+    }
+
+    fn describe(&self) -> String {
+        format!("size({})", self.keep_fraction)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ReductionPolicy> {
+        Box::new(self.clone())
+    }
+}
+
+/// Never delete glue clauses (LBD at most `max_glue_lbd`), and delete the
+/// rest by activity.
+#[derive(Clone, Debug)]
+pub struct Hybrid {
+    keep_fraction: f64,
+    max_glue_lbd: usize,
+}
+
+impl Hybrid {
+    pub fn new(keep_fraction: f64, max_glue_lbd: usize) -> Self {
+        Self {
+            keep_fraction: check_keep_fraction(keep_fraction),
+            max_glue_lbd,
+        }
+    }
+
+    fn is_glue(&self, clause: &ReductionCandidate) -> bool {
+        clause.lbd <= self.max_glue_lbd
+    }
+}
+
+impl ReductionPolicy for Hybrid {
+    fn compare(&self, a: &ReductionCandidate, b: &ReductionCandidate) -> Ordering {
+        self.is_glue(a)
+            .cmp(&self.is_glue(b))
+            .then_with(|| a.activity.total_cmp(&b.activity))
+    }
+
+    fn keep_fraction(&self) -> f64 {
+        self.keep_fraction
+    }
+
+    fn describe(&self) -> String {
+        format!("hybrid({}, {})", self.keep_fraction, self.max_glue_lbd)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn ReductionPolicy> {
+        Box::new(self.clone())
+    }
+
+    fn select(&self, candidates: &[ReductionCandidate]) -> Vec<usize> {
+        let mut order = (0..candidates.len())
+            .filter(|&idx| !self.is_glue(&candidates[idx]))
+            .collect::<Vec<_>>();
+        order.sort_by(|&a, &b| self.compare(&candidates[a], &candidates[b]));
+        let num_to_keep = (order.len() as f64 * self.keep_fraction).ceil() as usize;
+        order.truncate(order.len() - num_to_keep);
+        order
+    }
+}
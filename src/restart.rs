@@ -0,0 +1,133 @@
+use crate::luby::Luby;
+
+/// When to restart: how many conflicts to allow since the last restart.
+pub trait RestartSchedule: Send {
+    /// The number of conflicts to wait for before the next restart.
+    fn limit(&self) -> u64;
+    /// Move on to the next limit after a restart.
+    fn restarted(&mut self);
+    /// Go back to the first limit.
+    fn reset(&mut self);
+
+    /// The schedule and its parameters, such as `geometric(100, 1.5)`.
+    fn describe(&self) -> String;
+
+    /// A copy for a forked solver.
+    fn boxed_clone(&self) -> Box<dyn RestartSchedule>;
+}
+
+impl RestartSchedule for Luby {
+    fn limit(&self) -> u64 {
+        self.value()
+    }
+
+    fn restarted(&mut self) {
+        self.next();
+    }
+
+    fn reset(&mut self) {
+        Luby::reset(self)
+    }
+
+    fn describe(&self) -> String {
+        Luby::describe(self)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RestartSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+/// Restart after `first` conflicts, then after `growth` times as many as
+/// the time before.
+#[derive(Clone, Debug)]
+pub struct Geometric {
+    first: f64,
+    growth: f64,
+    limit: f64,
+}
+
+impl Geometric {
+    pub fn new(first: u64, growth: f64) -> Self {
+        assert!(growth >= 1.0, "growth {} would shrink the limit", growth);
+        Self {
+            first: first as f64,
+            growth,
+            limit: first as f64,
+        }
+    }
+}
+
+impl RestartSchedule for Geometric {
+    fn limit(&self) -> u64 {
+        self.limit as u64
+    }
+
+    fn restarted(&mut self) {
+        self.limit *= self.growth;
+    }
+
+    fn reset(&mut self) {
+        self.limit = self.first;
+    }
+
+    fn describe(&self) -> String {
+        format!("geometric({}, {})", self.first, self.growth)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RestartSchedule> {
+        Box::new(self.clone())
+    }
+}
+
+/// The inner/outer scheme: the inner limit grows by `growth` at every
+/// restart until it passes the outer one, then starts again from `first`
+/// while the outer limit grows by `growth` too. Runs get longer overall
+/// but keep coming back to short ones.
+#[derive(Clone, Debug)]
+pub struct InnerOuter {
+    first: f64,
+    growth: f64,
+    inner: f64,
+    outer: f64,
+}
+
+impl InnerOuter {
+    pub fn new(first: u64, growth: f64) -> Self {
+        assert!(growth >= 1.0, "growth {} would shrink the limits", growth);
+        Self {
+            first: first as f64,
+            growth,
+            inner: first as f64,
+            outer: first as f64,
+        }
+    }
+}
+
+impl RestartSchedule for InnerOuter {
+    fn limit(&self) -> u64 {
+        self.inner as u64
+    }
+
+    fn restarted(&mut self) {
+        if self.inner >= self.outer {
+            self.outer *= self.growth;
+            self.inner = self.first;
+        } else {
+            self.inner *= self.growth;
+        }
+    }
+
+    fn reset(&mut self) {
+        self.inner = self.first;
+        self.outer = self.first;
+    }
+
+    fn describe(&self) -> String {
+        format!("inner-outer({}, {})", self.first, self.growth)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn RestartSchedule> {
+        Box::new(self.clone())
+    }
+}
@@ -1,19 +1,30 @@
 use crate::bitset::BitSetT;
 use crate::pool::Pool;
-use crate::tombstone::*;
-use std::collections::HashSet;
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Debug)]
 pub enum SatResult {
+    /// A model assigning every variable from 1 to the largest the solver
+    /// knows, in increasing order; variables in no clause are false.
     Sat(BTreeMap<usize, bool>),
     UnsatCore(Vec<crate::sat::Literal>),
+    /// The solver gave up, e.g. on reaching its learned clause memory limit.
+    Unknown,
 }
 
 #[derive(Debug)]
 pub enum StepResult {
     Done(SatResult),
     Continue,
+    /// The step hit a conflict, learned this clause and backjumped.
+    Learned(LearnedClause),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LearnedClause {
+    pub literals: Vec<Literal>,
+    /// The decision level the solver backjumped to, where the clause is unit.
+    pub backjump_level: usize,
 }
 
 #[derive(Debug)]
@@ -24,13 +35,14 @@ pub struct Clause<BitSet: BitSetT> {
     pub num_units: usize,
     pub score: f64,
     pub from_conflict: bool,
+    pub lbd: usize, // distinct decision levels when learned, 0 for original clauses
 }
 
-pub fn satisfies<BitSet: BitSetT>(
-    clauses: &Vec<TombStone<Clause<BitSet>>>,
+pub fn satisfies<'a, BitSet: BitSetT + 'a>(
+    clauses: impl IntoIterator<Item = &'a Clause<BitSet>>,
     assignments: &BTreeMap<usize, bool>,
 ) -> bool {
-    clauses.iter().filter_map(|x| x.value()).all(|clause| {
+    clauses.into_iter().all(|clause| {
         clause.iter_literals().any(|literal| {
             if let Some(&value) = assignments.get(&literal.variable()) {
                 value == literal.value()
@@ -41,6 +53,15 @@ pub fn satisfies<BitSet: BitSetT>(
     })
 }
 
+/// The index of the first of `clauses` with no literal true in `model`.
+pub fn first_unsatisfied(clauses: &[Vec<isize>], model: &BTreeMap<usize, bool>) -> Option<usize> {
+    clauses.iter().position(|clause| {
+        !clause
+            .iter()
+            .any(|&lit| model.get(&lit.unsigned_abs()) == Some(&(lit > 0)))
+    })
+}
+
 impl<BitSet: BitSetT> Clause<BitSet> {
     pub fn empty() -> Self {
         Clause {
@@ -50,6 +71,7 @@ impl<BitSet: BitSetT> Clause<BitSet> {
             num_units: 0,
             score: 0.0,
             from_conflict: false,
+            lbd: 0,
         }
     }
     pub fn create(variables: BitSet, negatives: BitSet) -> Self {
@@ -60,6 +82,7 @@ impl<BitSet: BitSetT> Clause<BitSet> {
             num_units: 0,
             score: 0.0,
             from_conflict: false,
+            lbd: 0,
         }
     }
 
@@ -90,6 +113,7 @@ impl<BitSet: BitSetT> Clause<BitSet> {
             num_units: 0,
             score: 0.0,
             from_conflict: self.from_conflict,
+            lbd: self.lbd,
         }
     }
 
@@ -117,78 +141,167 @@ impl<BitSet: BitSetT> Clause<BitSet> {
     }
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Copy, Hash, PartialOrd, Ord)]
+/// The largest variable a `Literal` can hold.
+pub const MAX_VARIABLE: usize = (1 << 31) - 1;
+
+/// A variable above `MAX_VARIABLE`, found while parsing or adding a clause.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VariableTooLarge(pub usize);
+
+impl std::fmt::Display for VariableTooLarge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "variable {} is above the maximum of {}", self.0, MAX_VARIABLE)
+    }
+}
+
+impl std::error::Error for VariableTooLarge {}
+
+impl From<VariableTooLarge> for std::io::Error {
+    fn from(err: VariableTooLarge) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidData, err)
+    }
+}
+
+/// `lit`, unless its variable doesn't fit in a `Literal`.
+pub fn check_literal(lit: isize) -> Result<isize, VariableTooLarge> {
+    if lit.unsigned_abs() > MAX_VARIABLE {
+        return Err(VariableTooLarge(lit.unsigned_abs()));
+    }
+    Ok(lit)
+}
+
+/// Packed as `2 * var + negated`, so a literal is four bytes and negating it
+/// flips the low bit. Ordering and `Debug` still follow the signed DIMACS value.
+#[derive(Clone, PartialEq, Eq, Copy, Hash)]
 pub struct Literal {
-    value: isize,
+    code: u32,
 }
 
 impl std::convert::Into<isize> for Literal {
     fn into(self) -> isize {
-        self.value
+        let var = self.variable() as isize;
+        if self.value() {
+            var
+        } else {
+            -var
+        }
     }
 }
 
 impl std::convert::From<isize> for Literal {
     fn from(value: isize) -> Self {
-        Self { value }
+        Self::new(value.unsigned_abs(), value > 0)
+    }
+}
+
+pub fn literals_of_ints(lits: &[isize]) -> Vec<Literal> {
+    lits.iter().map(|&lit| lit.into()).collect()
+}
+
+pub fn ints_of_literals(lits: &[Literal]) -> Vec<isize> {
+    lits.iter().map(|&lit| lit.into()).collect()
+}
+
+/// Whether the clause contains some literal and its negation.
+pub fn is_tautology(clause: &[isize]) -> bool {
+    let lits = clause.iter().copied().collect::<BTreeSet<_>>();
+    lits.iter().any(|&lit| lits.contains(&-lit))
+}
+
+impl std::fmt::Debug for Literal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let value: isize = (*self).into();
+        f.debug_struct("Literal").field("value", &value).finish()
+    }
+}
+
+impl PartialOrd for Literal {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Literal {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.order_key().cmp(&other.order_key())
     }
 }
 
 impl Literal {
+    /// Panics if `var` is above `MAX_VARIABLE`.
     pub fn new(var: usize, value: bool) -> Self {
+        assert!(var <= MAX_VARIABLE, "variable {} does not fit in a literal", var);
         Literal {
-            value: if value { var as isize } else { -(var as isize) },
+            code: ((var as u32) << 1) | (!value as u32),
         }
     }
 
-    pub fn to_string(&self) -> String {
-        if self.value > 0 {
-            format!("{}", self.value)
+    /// The signed DIMACS value shifted up by 2^31, so it's ordered the same
+    /// way without converting to `isize`.
+    fn order_key(&self) -> u32 {
+        let var = self.code >> 1;
+        if self.code & 1 == 0 {
+            (1 << 31) + var
         } else {
-            format!("-{}", -self.value)
+            (1 << 31) - var
         }
     }
 
+    pub fn to_string(&self) -> String {
+        let value: isize = (*self).into();
+        format!("{}", value)
+    }
+
     pub fn variable(&self) -> usize {
-        self.value.abs() as usize
+        (self.code >> 1) as usize
     }
 
     pub fn value(&self) -> bool {
-        self.value > 0
+        self.code & 1 == 0
     }
 
     pub fn negate(&self) -> Self {
-        Literal { value: -self.value }
+        Literal {
+            code: self.code ^ 1,
+        }
+    }
+
+    /// The packed `2 * var + negated` code, usable as a dense array index.
+    pub fn code(&self) -> usize {
+        self.code as usize
     }
 }
 
+/// Ordered collections only, so that iterating over a formula (and anything
+/// seeded from it) is the same on every run.
 pub struct Formula<BitSet: BitSetT> {
     pub max_var: usize,
-    pub vars: HashSet<usize>,
+    pub vars: BTreeSet<usize>,
     pub clauses: Vec<Clause<BitSet>>,
-    pub literal_counts: HashMap<Literal, usize>,
+    pub literal_counts: BTreeMap<Literal, usize>,
 }
 
 impl<BitSet: BitSetT> Formula<BitSet> {
-    pub fn new(formula: Vec<Vec<isize>>, bitset_pool: &mut Pool<BitSet>) -> Self {
+    pub fn new<Lits: IntoIterator<Item = isize>>(
+        formula: impl IntoIterator<Item = Lits>,
+        bitset_pool: &mut Pool<BitSet>,
+    ) -> Self {
         let mut max_var = 0;
-        let mut vars = HashSet::new();
-        let mut literal_counts = HashMap::new();
+        let mut vars = BTreeSet::new();
+        let mut literal_counts = BTreeMap::new();
         let mut clauses = Vec::new();
 
         for clause in formula {
-            let mut variables = bitset_pool.acquire(|| BitSet::create());
-            let mut negatives = bitset_pool.acquire(|| BitSet::create());
+            let mut variables = bitset_pool.acquire_reset(BitSet::create);
+            let mut negatives = bitset_pool.acquire_reset(BitSet::create);
             let mut tautology = false;
-            variables.clear_all();
-            negatives.clear_all();
 
             for lit in clause {
                 if lit == 0 {
                     panic!("Can't have 0 vars");
                 }
                 let var = lit.abs() as usize;
-                if variables.contains(var) && !negatives.contains(var) != (lit < 0) {
+                if variables.contains(var) && negatives.contains(var) != (lit < 0) {
                     tautology = true;
                 }
                 variables.set(var);
@@ -209,6 +322,7 @@ impl<BitSet: BitSetT> Formula<BitSet> {
                 num_units: 0,
                 score: 0.0,
                 from_conflict: false,
+                lbd: 0,
             });
         }
 
@@ -219,4 +333,79 @@ impl<BitSet: BitSetT> Formula<BitSet> {
             literal_counts,
         }
     }
+
+    /// How often `var` occurs positively and negatively.
+    pub fn occurrences(&self, var: usize) -> (usize, usize) {
+        let count = |value| {
+            self.literal_counts
+                .get(&Literal::new(var, value))
+                .copied()
+                .unwrap_or(0)
+        };
+        (count(true), count(false))
+    }
+
+    /// Number of clauses of each length, counting distinct variables.
+    /// Tautologies are left out, as in `unit_clauses` and `binary_clauses`.
+    pub fn clause_length_histogram(&self) -> BTreeMap<usize, usize> {
+        let mut histogram = BTreeMap::new();
+        for clause in self.clauses.iter().filter(|clause| !clause.tautology) {
+            *histogram.entry(clause.variables.count()).or_insert(0) += 1;
+        }
+        histogram
+    }
+
+    fn clause_indices(&self, f: impl Fn(&Clause<BitSet>) -> bool) -> Vec<usize> {
+        (0..self.clauses.len())
+            .filter(|&idx| f(&self.clauses[idx]))
+            .collect()
+    }
+
+    pub fn unit_clauses(&self) -> Vec<usize> {
+        self.clause_indices(|clause| !clause.tautology && clause.variables.count() == 1)
+    }
+
+    pub fn binary_clauses(&self) -> Vec<usize> {
+        self.clause_indices(|clause| !clause.tautology && clause.variables.count() == 2)
+    }
+
+    pub fn tautologies(&self) -> Vec<usize> {
+        self.clause_indices(|clause| clause.tautology)
+    }
+
+    pub fn stats(&self) -> FormulaStats {
+        FormulaStats {
+            num_vars: self.vars.len(),
+            num_clauses: self.clauses.len(),
+            num_literals: self.literal_counts.values().sum(),
+            clause_lengths: self.clause_length_histogram(),
+            unit_clauses: self.unit_clauses().len(),
+            binary_clauses: self.binary_clauses().len(),
+            tautologies: self.tautologies().len(),
+            pure_literals: self
+                .vars
+                .iter()
+                .filter(|&&var| {
+                    let (pos, neg) = self.occurrences(var);
+                    (pos == 0) != (neg == 0)
+                })
+                .count(),
+        }
+    }
+}
+
+/// A summary of a formula's structure, from `Formula::stats`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormulaStats {
+    pub num_vars: usize,
+    pub num_clauses: usize,
+    /// Literal occurrences over all clauses, duplicates included.
+    pub num_literals: usize,
+    /// Clauses of each length, tautologies excluded.
+    pub clause_lengths: BTreeMap<usize, usize>,
+    pub unit_clauses: usize,
+    pub binary_clauses: usize,
+    pub tautologies: usize,
+    /// Variables occurring with only one polarity.
+    pub pure_literals: usize,
 }
@@ -1,22 +1,49 @@
-use std::cell::RefCell;
 use std::fmt::Write;
-use std::rc::Rc;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 #[derive(Clone)]
-pub struct SharedStringWriter(Rc<RefCell<String>>);
+pub struct SharedStringWriter(Arc<Mutex<String>>);
 
 impl std::fmt::Write for SharedStringWriter {
     fn write_str(&mut self, s: &str) -> Result<(), std::fmt::Error> {
-        self.0.borrow_mut().write_str(s)
+        self.0.lock().unwrap().write_str(s)
     }
 }
 
 impl SharedStringWriter {
     pub fn new() -> Self {
-        SharedStringWriter(Rc::new(RefCell::new(String::new())))
+        SharedStringWriter(Arc::new(Mutex::new(String::new())))
     }
 
-    pub fn borrow<'a>(&'a self) -> std::cell::Ref<'a, String> {
-        self.0.borrow()
+    pub fn borrow(&self) -> MutexGuard<'_, String> {
+        self.0.lock().unwrap()
+    }
+}
+
+/// Adapts a `std::io::Write` (a file, pipe or socket) into the
+/// `std::fmt::Write` that debug and stats output go to.
+pub struct IoWriter<W: std::io::Write>(W);
+
+impl<W: std::io::Write> std::fmt::Write for IoWriter<W> {
+    fn write_str(&mut self, s: &str) -> Result<(), std::fmt::Error> {
+        self.0.write_all(s.as_bytes()).map_err(|_| std::fmt::Error)
+    }
+}
+
+impl<W: std::io::Write> IoWriter<W> {
+    pub fn new(inner: W) -> Self {
+        IoWriter(inner)
+    }
+
+    pub fn into_inner(self) -> W {
+        self.0
+    }
+}
+
+impl IoWriter<std::io::BufWriter<std::fs::File>> {
+    /// Buffered output to a new file at `path`, flushed when dropped.
+    pub fn create(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let file = std::fs::File::create(path)?;
+        Ok(IoWriter(std::io::BufWriter::new(file)))
     }
 }
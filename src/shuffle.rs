@@ -0,0 +1,58 @@
+use rand::prelude::*;
+use rand_pcg::Pcg64;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A formula with its variables renamed to `1..=n` in a random order and its
+/// clauses and literals permuted, along with the renaming used.
+#[derive(Clone, Debug)]
+pub struct Shuffled {
+    pub clauses: Vec<Vec<isize>>,
+    /// original variable -> new variable
+    pub renaming: BTreeMap<usize, usize>,
+}
+
+impl Shuffled {
+    /// Translate a model of the shuffled formula back to the original names.
+    pub fn original_model(&self, model: &BTreeMap<usize, bool>) -> BTreeMap<usize, bool> {
+        self.renaming
+            .iter()
+            .filter_map(|(&var, new_var)| model.get(new_var).map(|&value| (var, value)))
+            .collect()
+    }
+}
+
+/// Deterministically (for a given `seed`) shuffle `clauses`, for checking
+/// how sensitive the heuristics are to input order or for sharing an
+/// instance without its variable numbering.
+pub fn shuffle(clauses: &[Vec<isize>], seed: u64) -> Shuffled {
+    let mut rng = Pcg64::seed_from_u64(seed);
+    let vars = clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.unsigned_abs())
+        .collect::<BTreeSet<_>>();
+    let mut names = (1..=vars.len()).collect::<Vec<_>>();
+    names.shuffle(&mut rng);
+    let renaming = vars.into_iter().zip(names).collect::<BTreeMap<_, _>>();
+
+    let mut clauses = clauses
+        .iter()
+        .map(|clause| {
+            let mut clause = clause
+                .iter()
+                .map(|&lit| {
+                    let var = renaming[&lit.unsigned_abs()] as isize;
+                    if lit > 0 {
+                        var
+                    } else {
+                        -var
+                    }
+                })
+                .collect::<Vec<_>>();
+            clause.shuffle(&mut rng);
+            clause
+        })
+        .collect::<Vec<_>>();
+    clauses.shuffle(&mut rng);
+    Shuffled { clauses, renaming }
+}
@@ -0,0 +1,281 @@
+use crate::analysis::InstanceFeatures;
+use crate::cdcl::{
+    ConfigT, RandomConfig, RandomConfigDebug, State, VsidsAvlConfig, VsidsConfig, VsidsConfigDebug,
+};
+use crate::description::Description;
+use crate::luby::Luby;
+use crate::reduction::{ByActivity, ByLbd, BySize, Hybrid, ReductionPolicy};
+use crate::restart::{Geometric, InnerOuter, RestartSchedule};
+use crate::sat::SatResult;
+use crate::stats::Stats;
+use std::time::Instant;
+
+/// The incremental solving interface shared by every `State<Config>`, for
+/// picking a configuration at runtime.
+pub trait Solver: Send {
+    fn add_clause(&mut self, clause: Vec<isize>);
+    fn solve(&mut self) -> SatResult;
+    /// `None` if `deadline` passed before the solve finished.
+    fn solve_until(&mut self, deadline: Instant) -> Option<SatResult>;
+    fn run_with_assumptions(&mut self, assumptions: &[isize]) -> SatResult;
+    fn stats(&self) -> Stats;
+    fn describe(&self) -> Description;
+    /// Where debug output goes; only debug configs write any.
+    fn set_writer(&mut self, writer: Box<dyn std::fmt::Write + Send>);
+}
+
+macro_rules! impl_solver {
+    ($config:ty) => {
+        impl Solver for State<$config> {
+            fn add_clause(&mut self, clause: Vec<isize>) {
+                State::add_clause(self, clause)
+            }
+
+            fn solve(&mut self) -> SatResult {
+                self.run()
+            }
+
+            fn solve_until(&mut self, deadline: Instant) -> Option<SatResult> {
+                self.run_until(deadline)
+            }
+
+            fn run_with_assumptions(&mut self, assumptions: &[isize]) -> SatResult {
+                State::run_with_assumptions(self, assumptions)
+            }
+
+            fn stats(&self) -> Stats {
+                State::stats(self)
+            }
+
+            fn describe(&self) -> Description {
+                State::describe(self)
+            }
+
+            fn set_writer(&mut self, writer: Box<dyn std::fmt::Write + Send>) {
+                self.set_debug_writer(writer)
+            }
+        }
+    };
+}
+
+impl_solver!(VsidsConfig);
+impl_solver!(VsidsConfigDebug);
+impl_solver!(VsidsAvlConfig);
+impl_solver!(RandomConfig);
+impl_solver!(RandomConfigDebug);
+#[cfg(all(feature = "mmap", unix))]
+impl_solver!(crate::cdcl::VsidsMmapConfig);
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heuristic {
+    #[default]
+    Vsids,
+    Random,
+}
+
+impl std::str::FromStr for Heuristic {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "vsids" => Ok(Heuristic::Vsids),
+            "random" => Ok(Heuristic::Random),
+            _ => Err(format!("unknown heuristic {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RestartKind {
+    #[default]
+    Luby,
+    Geometric,
+    InnerOuter,
+}
+
+impl std::str::FromStr for RestartKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "luby" => Ok(RestartKind::Luby),
+            "geometric" => Ok(RestartKind::Geometric),
+            "inner-outer" => Ok(RestartKind::InnerOuter),
+            _ => Err(format!("unknown restart schedule {}", s)),
+        }
+    }
+}
+
+/// The restart schedule. For `Luby`, restart after `base` times the Luby
+/// terms in conflicts, with the terms powers of `factor` and no run longer
+/// than `cap`. For `Geometric` and `InnerOuter` the first run is `base`
+/// conflicts and runs grow by `factor`; `cap` is ignored.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RestartOptions {
+    pub kind: RestartKind,
+    pub base: u64,
+    pub factor: f64,
+    pub cap: Option<u64>,
+}
+
+impl RestartOptions {
+    pub fn schedule(&self) -> Box<dyn RestartSchedule> {
+        match self.kind {
+            RestartKind::Luby => Box::new(Luby::with_factor(self.base, self.factor, self.cap)),
+            RestartKind::Geometric => Box::new(Geometric::new(self.base, self.factor)),
+            RestartKind::InnerOuter => Box::new(InnerOuter::new(self.base, self.factor)),
+        }
+    }
+}
+
+impl Default for RestartOptions {
+    fn default() -> Self {
+        RestartOptions {
+            kind: RestartKind::Luby,
+            base: 32,
+            factor: 2.0,
+            cap: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReductionKind {
+    #[default]
+    Activity,
+    Lbd,
+    Size,
+    /// Keep clauses of LBD 2 or less, delete the rest by activity.
+    Hybrid,
+}
+
+impl std::str::FromStr for ReductionKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "activity" => Ok(ReductionKind::Activity),
+            "lbd" => Ok(ReductionKind::Lbd),
+            "size" => Ok(ReductionKind::Size),
+            "hybrid" => Ok(ReductionKind::Hybrid),
+            _ => Err(format!("unknown reduction policy {}", s)),
+        }
+    }
+}
+
+/// How the learned clause database is reduced: first after `first` learned
+/// clauses, then after `growth` times more each time, keeping
+/// `keep_fraction` of the deletable clauses as ranked by `kind`. With
+/// `bump_reasons`, propagating counts towards a clause's activity as well
+/// as taking part in conflicts.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ReductionOptions {
+    pub kind: ReductionKind,
+    pub keep_fraction: f64,
+    pub first: u64,
+    pub growth: f64,
+    pub bump_reasons: bool,
+}
+
+impl ReductionOptions {
+    pub fn policy(&self) -> Box<dyn ReductionPolicy> {
+        match self.kind {
+            ReductionKind::Activity => Box::new(ByActivity::new(self.keep_fraction)),
+            ReductionKind::Lbd => Box::new(ByLbd::new(self.keep_fraction)),
+            ReductionKind::Size => Box::new(BySize::new(self.keep_fraction)),
+            ReductionKind::Hybrid => Box::new(Hybrid::new(self.keep_fraction, 2)),
+        }
+    }
+}
+
+impl Default for ReductionOptions {
+    fn default() -> Self {
+        ReductionOptions {
+            kind: ReductionKind::Activity,
+            keep_fraction: 0.5,
+            first: 2000,
+            growth: 1.1,
+            bump_reasons: false,
+        }
+    }
+}
+
+/// The factors VSIDS and clause activities decay by at every conflict.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecayOptions {
+    pub vsids: f64,
+    pub clause: f64,
+}
+
+impl Default for DecayOptions {
+    fn default() -> Self {
+        DecayOptions {
+            vsids: 0.95,
+            clause: 0.75,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct SolverOptions {
+    pub heuristic: Heuristic,
+    /// Trace every step to the writer (see `Solver::set_writer`) and check
+    /// models against the clauses.
+    pub debug: bool,
+    pub restarts: RestartOptions,
+    pub reduction: ReductionOptions,
+    pub decay: DecayOptions,
+    /// The value decisions try first; `None` leaves it to the heuristic.
+    pub initial_phase: Option<bool>,
+}
+
+impl SolverOptions {
+    /// Settings suited to `formula`, going by its `InstanceFeatures`:
+    /// deciding false first on mostly Horn formulas, and on random-looking
+    /// ones restarting geometrically and decaying activities slowly.
+    /// Anything else gets the defaults.
+    pub fn auto(formula: &[Vec<isize>]) -> Self {
+        let features = InstanceFeatures::of(formula);
+        let mut options = SolverOptions::default();
+        if features.is_mostly_horn() {
+            options.initial_phase = Some(false);
+        }
+        if features.looks_random() {
+            options.restarts = RestartOptions {
+                kind: RestartKind::Geometric,
+                base: 100,
+                factor: 1.5,
+                cap: None,
+            };
+            options.decay = DecayOptions {
+                vsids: 0.99,
+                clause: 0.9,
+            };
+        }
+        options
+    }
+}
+
+fn build<Config: ConfigT + 'static>(options: SolverOptions) -> Box<dyn Solver>
+where
+    State<Config>: Solver,
+{
+    let mut state = State::<Config>::new_from_vec(vec![]);
+    state.set_restart_schedule(options.restarts.schedule());
+    state.set_reduction_policy(options.reduction.policy());
+    state.set_reduce_schedule(options.reduction.first, options.reduction.growth);
+    state.set_bump_reasons(options.reduction.bump_reasons);
+    state.set_decay_factors(options.decay.vsids, options.decay.clause);
+    state.set_default_phase(options.initial_phase);
+    Box::new(state)
+}
+
+/// An empty solver with the configuration chosen by `options`.
+pub fn build_solver(options: SolverOptions) -> Box<dyn Solver> {
+    match (options.heuristic, options.debug) {
+        (Heuristic::Vsids, false) => build::<VsidsConfig>(options),
+        (Heuristic::Vsids, true) => build::<VsidsConfigDebug>(options),
+        (Heuristic::Random, false) => build::<RandomConfig>(options),
+        (Heuristic::Random, true) => build::<RandomConfigDebug>(options),
+    }
+}
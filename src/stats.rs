@@ -0,0 +1,171 @@
+use std::time::{Duration, Instant};
+
+/// Search counters accumulated over the lifetime of a `State`, plus the size
+/// of the clause database and variable set at the time they were taken.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    pub decisions: u64,
+    pub propagations: u64,
+    pub conflicts: u64,
+    pub restarts: u64,
+    pub learned_clauses: u64,
+    /// Conflicts whose learned clause was already in the database (or
+    /// subsumed by a clause there), so nothing was added.
+    pub duplicate_learned_clauses: u64,
+    /// Learned clauses deleted because a newer learned clause subsumed them.
+    pub subsumed_clauses: u64,
+    /// Problem clauses deleted as asymmetric tautologies.
+    pub eliminated_clauses: u64,
+    /// Solves answered by the 2-SAT and Horn fast paths, without any
+    /// search.
+    pub two_sat_solves: u64,
+    pub horn_solves: u64,
+    /// Solves answered with the model of the previous one.
+    pub cached_models: u64,
+    /// Propagations taken from the trail saved at a backjump.
+    pub replayed_propagations: u64,
+    /// Models yielded by `State::models`, and the clauses it added to block
+    /// them.
+    pub models_found: u64,
+    pub blocking_clauses: u64,
+    pub clauses: usize,
+    pub variables: usize,
+    pub profile: Profile,
+}
+
+impl Stats {
+    fn fields(&self) -> [(&'static str, u64); 16] {
+        [
+            ("decisions", self.decisions),
+            ("propagations", self.propagations),
+            ("conflicts", self.conflicts),
+            ("restarts", self.restarts),
+            ("learned_clauses", self.learned_clauses),
+            ("duplicate_learned_clauses", self.duplicate_learned_clauses),
+            ("subsumed_clauses", self.subsumed_clauses),
+            ("eliminated_clauses", self.eliminated_clauses),
+            ("two_sat_solves", self.two_sat_solves),
+            ("horn_solves", self.horn_solves),
+            ("cached_models", self.cached_models),
+            ("replayed_propagations", self.replayed_propagations),
+            ("models_found", self.models_found),
+            ("blocking_clauses", self.blocking_clauses),
+            ("clauses", self.clauses as u64),
+            ("variables", self.variables as u64),
+        ]
+    }
+
+    pub fn to_json(&self) -> String {
+        let fields = self
+            .fields()
+            .iter()
+            .map(|(name, value)| format!("\"{}\":{}", name, value))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", fields.join(","))
+    }
+
+    /// A DIMACS comment line, as printed by the periodic reporter.
+    pub fn to_progress_line(&self) -> String {
+        let fields = self
+            .fields()
+            .iter()
+            .map(|(name, value)| format!("{} {}", name, value))
+            .collect::<Vec<_>>();
+        format!("c {}", fields.join(" "))
+    }
+}
+
+/// Adds up the stats of several solvers, say one per cube of a split
+/// problem. The clause and variable counts are added too.
+impl std::ops::AddAssign for Stats {
+    fn add_assign(&mut self, other: Stats) {
+        self.decisions += other.decisions;
+        self.propagations += other.propagations;
+        self.conflicts += other.conflicts;
+        self.restarts += other.restarts;
+        self.learned_clauses += other.learned_clauses;
+        self.duplicate_learned_clauses += other.duplicate_learned_clauses;
+        self.subsumed_clauses += other.subsumed_clauses;
+        self.eliminated_clauses += other.eliminated_clauses;
+        self.two_sat_solves += other.two_sat_solves;
+        self.horn_solves += other.horn_solves;
+        self.cached_models += other.cached_models;
+        self.replayed_propagations += other.replayed_propagations;
+        self.models_found += other.models_found;
+        self.blocking_clauses += other.blocking_clauses;
+        self.clauses += other.clauses;
+        self.variables += other.variables;
+        self.profile += other.profile;
+    }
+}
+
+/// Number of calls to, and total time spent in, one phase of the search.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PhaseProfile {
+    pub calls: u64,
+    pub time: Duration,
+}
+
+impl PhaseProfile {
+    pub fn record(&mut self, start: Instant) {
+        self.calls += 1;
+        self.time += start.elapsed();
+    }
+}
+
+impl std::ops::AddAssign for PhaseProfile {
+    fn add_assign(&mut self, other: PhaseProfile) {
+        self.calls += other.calls;
+        self.time += other.time;
+    }
+}
+
+/// Timers for the main phases of the search. They are left out of
+/// [`Stats::to_json`] and [`Stats::to_progress_line`] so that those stay
+/// reproducible between runs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Profile {
+    pub propagation: PhaseProfile,
+    pub analysis: PhaseProfile,
+    pub reduction: PhaseProfile,
+    pub restarts: PhaseProfile,
+}
+
+impl Profile {
+    pub fn phases(&self) -> [(&'static str, PhaseProfile); 4] {
+        [
+            ("propagation", self.propagation),
+            ("analysis", self.analysis),
+            ("reduction", self.reduction),
+            ("restarts", self.restarts),
+        ]
+    }
+
+    /// A DIMACS comment line such as `c profile propagation 10 0.001s ...`,
+    /// giving the number of calls and the time spent in each phase.
+    pub fn to_progress_line(&self) -> String {
+        let phases = self
+            .phases()
+            .iter()
+            .map(|(name, phase)| {
+                format!("{} {} {:.3}s", name, phase.calls, phase.time.as_secs_f64())
+            })
+            .collect::<Vec<_>>();
+        format!("c profile {}", phases.join(" "))
+    }
+}
+
+impl std::ops::AddAssign for Profile {
+    fn add_assign(&mut self, other: Profile) {
+        self.propagation += other.propagation;
+        self.analysis += other.analysis;
+        self.reduction += other.reduction;
+        self.restarts += other.restarts;
+    }
+}
+
+/// Writes a progress line to `writer` every `every_conflicts` conflicts.
+pub struct Reporter {
+    pub every_conflicts: u64,
+    pub writer: Box<dyn std::fmt::Write + Send>,
+}
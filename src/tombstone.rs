@@ -54,3 +54,142 @@ impl<T> TombStone<T> {
         self.value_mut().unwrap()
     }
 }
+
+/// A slot map: a `Vec` of `TombStone`s whose free slots are threaded into a
+/// list and reused. A slot's generation is bumped each time it's freed, so an
+/// `(index, generation)` pair names one value and goes stale once it's gone.
+#[derive(Debug)]
+pub struct GenVec<T> {
+    slots: Vec<TombStone<T>>,
+    first_free: Option<usize>,
+    live: usize,
+}
+
+impl<T> GenVec<T> {
+    pub fn new() -> Self {
+        GenVec {
+            slots: Vec::new(),
+            first_free: None,
+            live: 0,
+        }
+    }
+
+    /// Store `t`, reusing the most recently freed slot if there is one.
+    pub fn insert(&mut self, t: T) -> usize {
+        self.live += 1;
+        match self.first_free {
+            None => {
+                self.slots.push(TombStone::new(0, t));
+                self.slots.len() - 1
+            }
+            Some(idx) => {
+                let gen = *self.slots[idx].generation();
+                self.first_free = self.slots[idx].tombstone_idx_exn();
+                self.slots[idx] = TombStone::new(gen, t);
+                idx
+            }
+        }
+    }
+
+    /// Free the slot at `idx`, invalidating its current generation.
+    pub fn remove(&mut self, idx: usize) -> Option<T> {
+        let slot = self.slots.get_mut(idx)?;
+        slot.value()?;
+        let gen = *slot.generation() + 1;
+        let old = std::mem::replace(slot, TombStone::TombStone(gen, self.first_free));
+        self.first_free = Some(idx);
+        self.live -= 1;
+        match old {
+            TombStone::T(_, t) => Some(t),
+            TombStone::TombStone(_, _) => unreachable!(),
+        }
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&T> {
+        self.slots.get(idx).and_then(|x| x.value())
+    }
+
+    pub fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        self.slots.get_mut(idx).and_then(|x| x.value_mut())
+    }
+
+    pub fn get_mut_exn(&mut self, idx: usize) -> &mut T {
+        self.slots[idx].value_mut_exn()
+    }
+
+    /// The value at `idx`, if it's still the one from generation `gen`.
+    pub fn get_checked(&self, idx: usize, gen: Generation) -> Option<&T> {
+        match self.slots.get(idx)? {
+            TombStone::T(g, t) if *g == gen => Some(t),
+            _ => None,
+        }
+    }
+
+    pub fn is_live(&self, idx: usize, gen: Generation) -> bool {
+        self.get_checked(idx, gen).is_some()
+    }
+
+    pub fn generation(&self, idx: usize) -> Generation {
+        *self.slots[idx].generation()
+    }
+
+    /// Live values with their indices, in slot order.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (usize, &T)> + '_ {
+        self.slots
+            .iter()
+            .enumerate()
+            .filter_map(|(i, x)| x.value().map(|v| (i, v)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl DoubleEndedIterator<Item = (usize, &mut T)> + '_ {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(i, x)| x.value_mut().map(|v| (i, v)))
+    }
+
+    pub fn values(&self) -> impl DoubleEndedIterator<Item = &T> + '_ {
+        self.slots.iter().filter_map(|x| x.value())
+    }
+
+    pub fn slots(&self) -> &[TombStone<T>] {
+        &self.slots
+    }
+
+    /// Number of live values.
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+}
+
+impl<T> Default for GenVec<T> {
+    fn default() -> Self {
+        GenVec::new()
+    }
+}
+
+impl<T> FromIterator<T> for GenVec<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let slots = iter
+            .into_iter()
+            .map(|t| TombStone::new(0, t))
+            .collect::<Vec<_>>();
+        GenVec {
+            live: slots.len(),
+            slots,
+            first_free: None,
+        }
+    }
+}
+
+impl<T> std::ops::Index<usize> for GenVec<T> {
+    type Output = TombStone<T>;
+
+    fn index(&self, idx: usize) -> &TombStone<T> {
+        &self.slots[idx]
+    }
+}
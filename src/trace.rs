@@ -0,0 +1,49 @@
+use crate::sat::Literal;
+use std::fs;
+use std::io;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TraceEvent {
+    Decision(Literal),
+    Restart,
+}
+
+/// The heuristic decisions and restarts of a run, in order. Displayed as one
+/// token per event: the decided literal, or `r` for a restart.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Trace(pub Vec<TraceEvent>);
+
+impl std::fmt::Display for Trace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let tokens = self
+            .0
+            .iter()
+            .map(|event| match event {
+                TraceEvent::Decision(literal) => literal.to_string(),
+                TraceEvent::Restart => "r".to_string(),
+            })
+            .collect::<Vec<_>>();
+        write!(f, "{}", tokens.join(" "))
+    }
+}
+
+/// Parse a trace as displayed by `Trace`, skipping unparsable tokens.
+pub fn read_string(s: &str) -> Trace {
+    Trace(
+        s.split_whitespace()
+            .filter_map(|tok| match tok {
+                "r" => Some(TraceEvent::Restart),
+                tok => tok
+                    .parse::<isize>()
+                    .ok()
+                    .filter(|&lit| lit != 0)
+                    .map(|lit| TraceEvent::Decision(lit.into())),
+            })
+            .collect(),
+    )
+}
+
+pub fn read_file(path: &str) -> io::Result<Trace> {
+    let contents = fs::read_to_string(path)?;
+    Ok(read_string(&contents))
+}
@@ -0,0 +1,111 @@
+use crate::cdcl::{BranchingView, ConfigT, SearchEvent, State};
+use crate::sat::Literal;
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+/// Levels drawn at most; deeper searches show their latest ones.
+const MAX_LEVELS: usize = 20;
+
+/// Activity heat, coolest first.
+const HEAT: &[u8] = b" .:-=+*#%@";
+
+/// Draw the search as text `width` columns wide: the counters, then a line
+/// per decision level with its decision and a bar of the literals it
+/// implied, then the VSIDS activity of every variable as a strip of heat.
+pub fn render<Config: ConfigT>(view: &BranchingView<Config>, width: usize) -> String {
+    let width = width.max(20);
+    let stats = view.stats();
+    let mut levels: Vec<(Option<isize>, usize)> = vec![(None, 0)];
+    let mut vars = view.unassigned_variables().collect::<Vec<_>>();
+    for (literal, level) in view.trail_with_levels() {
+        vars.push(literal.variable());
+        if level >= levels.len() {
+            levels.resize(level + 1, (None, 0));
+            levels[level].0 = Some(literal.into());
+        } else {
+            levels[level].1 += 1;
+        }
+    }
+    // a decision being announced isn't on the trail yet
+    if levels.len() <= view.decision_level() {
+        levels.resize(view.decision_level() + 1, (None, 0));
+    }
+    vars.sort_unstable();
+    let assigned = vars.len() - view.unassigned_variables().count();
+
+    let mut lines = vec![format!(
+        "level {}  assigned {}/{}  conflicts {}  learned {}  restarts {}",
+        view.decision_level(),
+        assigned,
+        vars.len(),
+        stats.conflicts,
+        stats.learned_clauses,
+        stats.restarts
+    )];
+    if levels.len() > MAX_LEVELS {
+        lines.push(format!(
+            "  ... {} earlier levels",
+            levels.len() - MAX_LEVELS
+        ));
+    }
+    let max_implied = levels
+        .iter()
+        .map(|&(_, implied)| implied)
+        .max()
+        .unwrap_or(0);
+    let bar_width = width - 16;
+    for (level, &(decision, implied)) in levels
+        .iter()
+        .enumerate()
+        .skip(levels.len().saturating_sub(MAX_LEVELS))
+    {
+        let decision = decision.map_or(String::new(), |lit| lit.to_string());
+        let bar = (implied * bar_width).div_ceil(max_implied.max(1));
+        lines.push(format!("{:>4} {:>6} |{}", level, decision, "#".repeat(bar)));
+    }
+
+    let score = |var: usize| {
+        let (pos, neg) = (
+            view.score(Literal::new(var, true)),
+            view.score(Literal::new(var, false)),
+        );
+        pos.max(neg)
+    };
+    let max_score = vars.iter().map(|&var| score(var)).fold(0.0, f64::max);
+    // less the room for "activity [" and "]"
+    let heat_width = width - 11;
+    let heat = vars
+        .chunks(vars.len().div_ceil(heat_width).max(1))
+        .map(|chunk| {
+            let hottest = chunk.iter().map(|&var| score(var)).fold(0.0, f64::max);
+            let idx = if max_score > 0.0 {
+                (hottest / max_score * (HEAT.len() - 1) as f64).round() as usize
+            } else {
+                0
+            };
+            HEAT[idx] as char
+        })
+        .collect::<String>();
+    lines.push(format!("activity [{}]", heat));
+    lines.join("\n")
+}
+
+/// Redraw `render`'s frame on `writer`, a terminal, at most once every
+/// `every` as `state` searches.
+pub fn attach<Config: ConfigT + 'static>(
+    state: &mut State<Config>,
+    mut writer: Box<dyn Write + Send>,
+    width: usize,
+    every: Duration,
+) {
+    let mut last_frame: Option<Instant> = None;
+    state.set_event_hook(Box::new(move |_: &SearchEvent, view| {
+        if last_frame.is_some_and(|last| last.elapsed() < every) {
+            return;
+        }
+        last_frame = Some(Instant::now());
+        // home the cursor and clear the screen, then draw
+        let _ = writeln!(writer, "\x1b[H\x1b[2J{}", render(view, width));
+        let _ = writer.flush();
+    }));
+}
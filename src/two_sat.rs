@@ -0,0 +1,101 @@
+use std::collections::BTreeMap;
+
+/// Every clause has at most two literals.
+pub fn is_two_sat(clauses: &[Vec<isize>]) -> bool {
+    clauses.iter().all(|clause| clause.len() <= 2)
+}
+
+/// Node of literal `lit` in the implication graph.
+fn node(lit: isize) -> usize {
+    2 * lit.unsigned_abs() + (lit < 0) as usize
+}
+
+/// Strongly connected components of the graph, numbered in the order
+/// Tarjan's algorithm finds them, which is reverse topological order.
+fn components(edges: &[Vec<usize>]) -> Vec<usize> {
+    const UNVISITED: usize = usize::MAX;
+    let n = edges.len();
+    let mut index = vec![UNVISITED; n];
+    let mut lowlink = vec![0; n];
+    let mut component = vec![UNVISITED; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = vec![];
+    let mut next_index = 0;
+    let mut next_component = 0;
+    // explicit call stack of (node, next edge to look at)
+    let mut calls = vec![];
+    for root in 0..n {
+        if index[root] != UNVISITED {
+            continue;
+        }
+        calls.push((root, 0));
+        while let Some((v, edge)) = calls.pop() {
+            if edge == 0 {
+                index[v] = next_index;
+                lowlink[v] = next_index;
+                next_index += 1;
+                stack.push(v);
+                on_stack[v] = true;
+            }
+            if let Some(&w) = edges[v].get(edge) {
+                calls.push((v, edge + 1));
+                if index[w] == UNVISITED {
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    lowlink[v] = lowlink[v].min(index[w]);
+                }
+                continue;
+            }
+            if lowlink[v] == index[v] {
+                while let Some(w) = stack.pop() {
+                    on_stack[w] = false;
+                    component[w] = next_component;
+                    if w == v {
+                        break;
+                    }
+                }
+                next_component += 1;
+            }
+            if let Some(&(parent, _)) = calls.last() {
+                lowlink[parent] = lowlink[parent].min(lowlink[v]);
+            }
+        }
+    }
+    component
+}
+
+/// Solve a formula whose clauses have at most two literals, in linear time:
+/// it is unsatisfiable iff some literal and its negation imply each other,
+/// and otherwise each variable takes the value whose literal comes later in
+/// topological order of the implication graph's components.
+pub fn solve(clauses: &[Vec<isize>]) -> Option<BTreeMap<usize, bool>> {
+    assert!(is_two_sat(clauses), "not a 2-SAT formula");
+    let max_var = clauses
+        .iter()
+        .flatten()
+        .map(|lit| lit.unsigned_abs())
+        .max()
+        .unwrap_or(0);
+    let mut edges = vec![vec![]; 2 * (max_var + 1)];
+    for clause in clauses {
+        match clause.as_slice() {
+            [] => return None,
+            &[a] => edges[node(-a)].push(node(a)),
+            &[a, b] => {
+                edges[node(-a)].push(node(b));
+                edges[node(-b)].push(node(a));
+            }
+            _ => unreachable!(),
+        }
+    }
+    let component = components(&edges);
+    let mut model = BTreeMap::new();
+    for var in clauses.iter().flatten().map(|lit| lit.unsigned_abs()) {
+        let (pos, neg) = (component[2 * var], component[2 * var + 1]);
+        if pos == neg {
+            return None;
+        }
+        model.insert(var, pos < neg);
+    }
+    Some(model)
+}
@@ -6,6 +6,7 @@ use std::collections::HashSet;
 
 use pror::bitset::*;
 use pror::fixed_bitset::*;
+use pror::hybrid_bitset::HybridBitSet;
 
 #[derive(Clone, Debug)]
 struct Ops {
@@ -26,6 +27,8 @@ enum BitSetOp {
     Nth(usize),
     FirstSet,
     FirstSetGe(usize),
+    LastSet,
+    FirstSetLe(usize),
     // FirstUnset,
     // FirstUnsetGe(usize),
 }
@@ -40,6 +43,9 @@ enum BinaryOp {
     IterDifference,
     IntersectFirstSet,
     IntersectFirstSetGe(usize),
+    CountIntersection,
+    CountUnion,
+    CountDifference,
 }
 
 #[derive(Clone, Debug)]
@@ -58,10 +64,12 @@ impl Arbitrary for BitSetOp {
             0..50 => BitSetOp::Set(bit),
             50..60 => BitSetOp::ClearNth(usize::arbitrary(g) % 3),
             60..80 => BitSetOp::Contains(bit),
-            80..90 => BitSetOp::FirstSetGe(bit),
+            80..85 => BitSetOp::FirstSetGe(bit),
+            85..90 => BitSetOp::FirstSetLe(bit),
             90..92 => BitSetOp::Count,
-            92..98 => BitSetOp::Nth(bit),
-            98..100 => BitSetOp::FirstSet,
+            92..97 => BitSetOp::Nth(bit),
+            97..99 => BitSetOp::FirstSet,
+            99..100 => BitSetOp::LastSet,
             _ => unreachable!(),
         }
     }
@@ -78,8 +86,11 @@ impl Arbitrary for BinaryOp {
             15..32 => BinaryOp::IterUnion,
             32..49 => BinaryOp::IterIntersect,
             49..56 => BinaryOp::IterDifference,
-            56..75 => BinaryOp::IntersectFirstSet,
-            75..100 => BinaryOp::IntersectFirstSetGe(bit),
+            56..70 => BinaryOp::IntersectFirstSet,
+            70..88 => BinaryOp::IntersectFirstSetGe(bit),
+            88..92 => BinaryOp::CountIntersection,
+            92..96 => BinaryOp::CountUnion,
+            96..100 => BinaryOp::CountDifference,
             _ => unreachable!(),
         }
     }
@@ -141,6 +152,8 @@ fn apply<BitSet: BitSetT>(bs: &mut BitSet, op: &BitSetOp) -> UnaryRes {
         BitSetOp::Nth(n) => Nth(bs.nth(*n)),
         BitSetOp::FirstSet => FirstSet(bs.first_set()),
         BitSetOp::FirstSetGe(x) => FirstSet(bs.first_set_ge(*x)),
+        BitSetOp::LastSet => FirstSet(bs.last_set()),
+        BitSetOp::FirstSetLe(x) => FirstSet(bs.first_set_le(*x)),
     }
 }
 
@@ -187,6 +200,9 @@ fn apply_binop<BitSet: BitSetT>(bs1: &mut BitSet, bs2: &mut BitSet, op: &BinaryO
             let first_set = bs1.intersect_first_set_ge(bs2, *x);
             Unary(UnaryRes::FirstSet(first_set))
         }
+        BinaryOp::CountIntersection => Unary(UnaryRes::Count(bs1.count_intersection(bs2))),
+        BinaryOp::CountUnion => Unary(UnaryRes::Count(bs1.count_union(bs2))),
+        BinaryOp::CountDifference => Unary(UnaryRes::Count(bs1.count_difference(bs2))),
     }
 }
 
@@ -268,6 +284,66 @@ fn qc_prop_bitset_matches_naive2(
     TestResult::passed()
 }
 
+#[quickcheck]
+fn qc_hybrid_matches_naive(
+    initial_state_a: BoundedVec<1024>,
+    initial_state_b: BoundedVec<1024>,
+    ops: BinOps,
+) -> TestResult {
+    let mut a = HybridBitSet::create();
+    let mut b = HybridBitSet::create();
+    let mut naive_a = BTreeBitSet::create();
+    let mut naive_b = BTreeBitSet::create();
+
+    // spread over many chunks as well as the first
+    initial_state_a.0.iter().for_each(|&i| {
+        for bit in [i, i * 97] {
+            a.set(bit);
+            naive_a.set(bit)
+        }
+    });
+    initial_state_b.0.iter().for_each(|&i| {
+        for bit in [i, i * 89] {
+            b.set(bit);
+            naive_b.set(bit)
+        }
+    });
+
+    for op in &ops.ops {
+        let res = apply2(&mut a, &mut b, op);
+        let res_naive = apply2(&mut naive_a, &mut naive_b, op);
+        let la = a.iter().collect::<Vec<usize>>();
+        let lb = b.iter().collect::<Vec<usize>>();
+
+        let la_naive = naive_a.iter().collect::<Vec<usize>>();
+        let lb_naive = naive_b.iter().collect::<Vec<usize>>();
+        if res != res_naive || la != la_naive || lb != lb_naive {
+            println!(
+                "Failed on op: {:?}\ngood: {:?} ({} and {} bits)\nnaive: {:?}",
+                op,
+                res,
+                la.len(),
+                lb.len(),
+                res_naive
+            );
+            return TestResult::failed();
+        }
+    }
+
+    let last = a.last_set();
+    if last != naive_a.last_set() || a.first_unset() != naive_a.first_unset() {
+        return TestResult::failed();
+    }
+    a.complement();
+    naive_a.complement();
+    if a.iter().ne(naive_a.iter()) {
+        println!("Failed on complement below {:?}", last);
+        return TestResult::failed();
+    }
+
+    TestResult::passed()
+}
+
 #[quickcheck]
 fn qc_intersect_first_set_ge(
     initial_state_a: BoundedVec<1024>,
@@ -352,6 +428,63 @@ fn qc_iter_difference(
     TestResult::passed()
 }
 
+#[quickcheck]
+fn qc_iter_rev(initial_state: BoundedVec<1024>) -> TestResult {
+    let mut b = BitSet::create();
+    let mut naive = BTreeBitSet::create();
+    initial_state.0.iter().for_each(|&i| {
+        b.set(i);
+        naive.set(i)
+    });
+
+    let res = b.iter_rev().collect::<Vec<usize>>();
+    let mut naive_res = naive.iter().collect::<Vec<usize>>();
+    naive_res.reverse();
+    let default_res = BitSetT::iter_rev(&naive).collect::<Vec<usize>>();
+    if res != naive_res || default_res != naive_res {
+        println!(
+            "Failed on iter_rev\n\
+             good: {:?}\n\
+             default: {:?}\n\
+             naive: {:?}",
+            res, default_res, naive_res
+        );
+        return TestResult::failed();
+    }
+
+    TestResult::passed()
+}
+
+#[quickcheck]
+fn qc_iter_range(initial_state: BoundedVec<1024>, start: usize, end: usize) -> TestResult {
+    let (start, end) = (start % (1 << 16), end % (1 << 16));
+    let mut b = BitSet::create();
+    let mut naive = BTreeBitSet::create();
+    initial_state.0.iter().for_each(|&i| {
+        b.set(i);
+        naive.set(i)
+    });
+
+    let res = b.iter_range(start..end).collect::<Vec<usize>>();
+    let naive_res = naive
+        .iter()
+        .filter(|bit| (start..end).contains(bit))
+        .collect::<Vec<usize>>();
+    let default_res = BitSetT::iter_range(&naive, start..end).collect::<Vec<usize>>();
+    if res != naive_res || default_res != naive_res {
+        println!(
+            "Failed on iter_range {}..{}\n\
+             good: {:?}\n\
+             default: {:?}\n\
+             naive: {:?}",
+            start, end, res, default_res, naive_res
+        );
+        return TestResult::failed();
+    }
+
+    TestResult::passed()
+}
+
 #[quickcheck]
 fn qc_intersect_first_set(
     initial_state_a: BoundedVec<1024>,
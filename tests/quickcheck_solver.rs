@@ -0,0 +1,37 @@
+use quickcheck::TestResult;
+use quickcheck_macros::*;
+
+use pror::arbitrary::*;
+use pror::dpll;
+use pror::solver::{build_solver, Heuristic, SolverOptions};
+
+fn check(options: SolverOptions, cnf: Cnf, assumptions: Assumptions) -> TestResult {
+    let mut solver = build_solver(options);
+    match check_against_dpll(solver.as_mut(), &cnf, &assumptions) {
+        Ok(()) => TestResult::passed(),
+        Err(e) => TestResult::error(e),
+    }
+}
+
+#[quickcheck]
+fn vsids_matches_dpll(cnf: Cnf, assumptions: Assumptions) -> TestResult {
+    check(SolverOptions::default(), cnf, assumptions)
+}
+
+#[quickcheck]
+fn random_matches_dpll(cnf: Cnf, assumptions: Assumptions) -> TestResult {
+    let options = SolverOptions {
+        heuristic: Heuristic::Random,
+        debug: true,
+        ..SolverOptions::default()
+    };
+    check(options, cnf, assumptions)
+}
+
+#[test]
+fn dpll_reference() {
+    assert!(dpll::solve(&[vec![1, 2], vec![-1], vec![-2, 3]], &[]).is_some());
+    assert!(dpll::solve(&[vec![1, 2], vec![-1], vec![-2]], &[]).is_none());
+    assert!(dpll::solve(&[vec![1, 2]], &[-1, -2]).is_none());
+    assert!(dpll::solve(&[], &[1, -1]).is_none());
+}
@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pror::analysis::*;
+
+    #[test]
+    fn features() {
+        let horn = vec![vec![1], vec![-1, 2], vec![-2, -3, 4], vec![-4, -1]];
+        let features = InstanceFeatures::of(&horn);
+        expect!["InstanceFeatures { num_vars: 4, num_clauses: 4, ratio: 1.0, horn_fraction: 1.0, average_clause_len: 2.0 }"]
+            .assert_eq(&format!("{:?}", features));
+        assert!(features.is_mostly_horn() && !features.looks_random());
+
+        let random = pror::generate::random_k_cnf(100, 426, 3, 0);
+        let features = InstanceFeatures::of(&random);
+        assert_eq!(features.average_clause_len, 3.0);
+        assert!(features.looks_random() && !features.is_mostly_horn());
+
+        assert_eq!(InstanceFeatures::of(&[]), InstanceFeatures::default());
+    }
+}
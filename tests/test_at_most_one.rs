@@ -0,0 +1,77 @@
+use pror::at_most_one;
+use pror::dimacs;
+use pror::sat::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    use pror::cdcl::Default;
+
+    #[test]
+    fn extract_pairwise_clique() {
+        let formula = vec![
+            vec![1, 2, 3, 4],
+            vec![-1, -2],
+            vec![-1, -3],
+            vec![-2, -3],
+            vec![-1, -4],
+            vec![-2, -4],
+            vec![-3, -4],
+            vec![4, 5],
+            vec![-5, -6],
+        ];
+        let (remaining, groups) = at_most_one::extract(formula, 3);
+        let s = format!("{:?}\n{:?}", remaining, groups);
+        let expect = expect![[r#"
+            [[1, 2, 3, 4], [4, 5], [-5, -6]]
+            [[1, 2, 3, 4]]"#]];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn native_at_most_one() {
+        let mut solver = Default::new_from_vec(vec![vec![1, 2, 3]]);
+        solver.add_at_most_one(vec![1, 2, 3]);
+        let models = solver.enumerate_models_projected(&[1, 2, 3], 10);
+        let s = format!("{:?}", models);
+        let expect = expect!["[{1: false, 2: false, 3: true}, {1: false, 2: true, 3: false}, {1: true, 2: false, 3: false}]"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn at_most_one_with_unit_propagation() {
+        let mut solver = Default::new_from_vec(vec![vec![2], vec![-1, 3], vec![1, 4]]);
+        solver.add_at_most_one(vec![1, 2, -3]);
+        let s = format!("{:?}", solver.run());
+        let expect = expect!["Sat({1: false, 2: true, 3: true, 4: true})"];
+        expect.assert_eq(&s);
+        solver.add_at_most_one(vec![4, 2]);
+        let s = format!("{:?}", solver.run());
+        let expect = expect!["UnsatCore([])"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn sudoku_with_extracted_at_most_ones() {
+        let formula = dimacs::read_example("sudoku");
+        let (remaining, groups) = at_most_one::extract(formula.clone(), 3);
+        assert!(remaining.len() < formula.len());
+        let mut solver = Default::new_from_vec(remaining);
+        for group in groups {
+            solver.add_at_most_one(group);
+        }
+        match solver.run() {
+            SatResult::Sat(model) => {
+                for clause in &formula {
+                    assert!(clause
+                        .iter()
+                        .any(|&lit| model.get(&lit.unsigned_abs()) == Some(&(lit > 0))));
+                }
+            }
+            res => panic!("expected sat, got {:?}", res),
+        }
+    }
+}
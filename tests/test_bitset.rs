@@ -179,6 +179,82 @@ mod iter_tests {
         assert_eq!(a.first_unset_ge(64), Some(65));
     }
 
+    #[test]
+    fn test_last_set() {
+        let mut a = BitSet::new(0);
+        assert_eq!(a.last_set(), None);
+        assert_eq!(a.first_set_le(10), None);
+        a.set(0);
+        a.set(10);
+        a.set(63);
+        a.set(64);
+        a.set(12313);
+        assert_eq!(a.last_set(), Some(12313));
+        assert_eq!(a.first_set_le(12312), Some(64));
+        assert_eq!(a.first_set_le(64), Some(64));
+        assert_eq!(a.first_set_le(63), Some(63));
+        assert_eq!(a.first_set_le(9), Some(0));
+        assert_eq!(a.first_set_le(usize::MAX), Some(12313));
+        assert_eq!(a.iter_rev().collect::<Vec<_>>(), vec![12313, 64, 63, 10, 0]);
+        a.clear(0);
+        assert_eq!(a.first_set_le(9), None);
+    }
+
+    #[test]
+    fn test_complement() {
+        let mut a = BitSet::new(2);
+        a.set(0);
+        a.set(70);
+        a.complement();
+        assert_eq!(a.count(), 126);
+        assert!(!a.contains(0));
+        assert!(a.contains(1));
+        assert!(!a.contains(70));
+        assert!(a.contains(127));
+        assert!(!a.contains(128));
+
+        let mut b = pror::bitset::BTreeBitSet::create();
+        b.set(1);
+        b.set(4);
+        b.complement();
+        assert_eq!(b.iter().collect::<Vec<_>>(), vec![0, 2, 3]);
+    }
+
+    #[test]
+    fn test_set_op_counts() {
+        let mut a = BitSet::new(0);
+        let mut b = BitSet::new(0);
+        for bit in [1, 5, 64, 200] {
+            a.set(bit);
+        }
+        for bit in [5, 64, 65] {
+            b.set(bit);
+        }
+        assert_eq!(a.count_intersection(&b), 2);
+        assert_eq!(a.count_union(&b), 5);
+        assert_eq!(b.count_union(&a), 5);
+        assert_eq!(a.count_difference(&b), 2);
+        assert_eq!(b.count_difference(&a), 1);
+    }
+
+    #[test]
+    fn test_iter_range() {
+        let mut a = BitSet::new(0);
+        for bit in [0, 3, 63, 64, 65, 127, 128, 300] {
+            a.set(bit);
+        }
+        assert_eq!(a.iter_range(3..65).collect::<Vec<_>>(), vec![3, 63, 64]);
+        assert_eq!(a.iter_range(64..128).collect::<Vec<_>>(), vec![64, 65, 127]);
+        assert_eq!(a.iter_range(1..3).count(), 0);
+        assert_eq!(a.iter_range(65..64).count(), 0);
+        assert_eq!(a.iter_range(128..10_000).collect::<Vec<_>>(), vec![128, 300]);
+
+        let mut b = pror::bitset::BTreeBitSet::create();
+        b.set(5);
+        assert_eq!(b.iter_range(0..6).collect::<Vec<_>>(), vec![5]);
+        assert_eq!(b.iter_range(6..5).count(), 0);
+    }
+
     #[test]
     fn test_nth() {
         let mut a = BitSet::new(0);
@@ -264,4 +340,187 @@ mod iter_tests {
         // beyond edge → none
         assert_eq!(a.intersect_first_set_ge(&b, 9), None);
     }
+
+    #[test]
+    fn test_collection_traits() {
+        use std::collections::HashSet;
+        let a = [1, 5, 64, 200].into_iter().collect::<BitSet>();
+        let mut b = BitSet::new(8);
+        b.extend([200, 64, 5, 1]);
+        // equal despite the different capacities
+        assert_eq!(a, b);
+        assert_eq!(HashSet::from([a.clone(), b.clone()]).len(), 1);
+        assert_eq!(format!("{:?}", a), "{1, 5, 64, 200}");
+        assert_eq!((&a).into_iter().collect::<Vec<_>>(), vec![1, 5, 64, 200]);
+        assert_eq!(b.into_iter().collect::<Vec<_>>(), vec![1, 5, 64, 200]);
+
+        let c = [5, 65, 300].into_iter().collect::<BitSet>();
+        assert_eq!(&a & &c, BitSet::from_iter([5]));
+        assert_eq!(&a | &c, BitSet::from_iter([1, 5, 64, 65, 200, 300]));
+        assert_eq!(&a ^ &c, BitSet::from_iter([1, 64, 65, 200, 300]));
+        assert_eq!(&a - &c, BitSet::from_iter([1, 64, 200]));
+        assert_eq!(&c - &a, BitSet::from_iter([65, 300]));
+        let mut d = a.clone() ^ &a;
+        assert_eq!(d, BitSet::new(0));
+        d |= &c;
+        d -= &BitSet::from_iter([65]);
+        d &= &a;
+        assert_eq!(d, BitSet::from_iter([5]));
+    }
+
+    #[test]
+    fn test_word_access() {
+        let mut a = BitSet::from_words(vec![0b101, 0, 1]);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![0, 2, 128]);
+        a.as_words_mut()[1] = usize::MAX;
+        assert_eq!(a.count(), 67);
+        a.set(200);
+        assert_eq!(a.as_words(), &[0b101, usize::MAX, 1, 1 << 8]);
+        assert_eq!(BitSet::from_words(a.clone().into_words()), a);
+    }
+
+    #[test]
+    fn test_small_bitset() {
+        use pror::bitset::BitSetT;
+        let mut a = SmallBitSet::<2>::create();
+        assert_eq!(a.capacity(), 128);
+        a.set(3);
+        a.set_between(60, 70);
+        let mut b = SmallBitSet::<2>::create();
+        b.extend([3, 64, 127]);
+        assert_eq!(a.count_intersection(&b), 2);
+        a.union_with(&b);
+        assert_eq!(a.iter_rev().take(2).collect::<Vec<_>>(), vec![127, 69]);
+        assert_eq!(
+            format!("{:?}", &a - &b),
+            "{60, 61, 62, 63, 65, 66, 67, 68, 69}"
+        );
+        a.complement();
+        assert_eq!(a.first_unset(), Some(3));
+    }
+
+    #[test]
+    #[should_panic(expected = "can't grow")]
+    fn test_small_bitset_overflow() {
+        let mut a = SmallBitSet::<1>::default();
+        a.set(64);
+    }
+
+    #[test]
+    fn test_shrink_to_fit() {
+        let mut a = BitSet::new(0);
+        a.set(3);
+        a.set(10_000);
+        assert!(a.memory_blocks() >= 157);
+        a.clear(10_000);
+        a.shrink_to_fit();
+        assert_eq!(a.capacity(), 64);
+        assert_eq!(a.memory_blocks(), 1);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![3]);
+        a.clear_all();
+        assert_eq!(a.memory_blocks(), 1);
+        a.shrink_to_fit();
+        assert_eq!(a.memory_blocks(), 0);
+        assert_eq!(a, BitSet::new(4));
+
+        // inline storage keeps its words
+        let mut b = SmallBitSet::<2>::default();
+        b.set(1);
+        b.shrink_to_fit();
+        assert_eq!(b.memory_blocks(), 2);
+        assert!(b.contains(1));
+    }
+
+    #[cfg(all(feature = "mmap", unix))]
+    #[test]
+    fn test_mmap_storage() {
+        use pror::mmap_storage::*;
+
+        let mut words = MmapStorage::default();
+        words.resize(4);
+        words[3] = 7;
+        assert!(!words.is_mapped());
+        words.resize(SPILL_WORDS + 1);
+        assert!(words.is_mapped());
+        assert_eq!(words[3], 7);
+        assert_eq!(words[SPILL_WORDS], 0);
+        words[SPILL_WORDS] = 1;
+        words.resize(SPILL_WORDS);
+        words.resize(SPILL_WORDS + 1);
+        assert_eq!(words[SPILL_WORDS], 0);
+        let copy = words.clone();
+        assert_eq!(&*copy, &*words);
+        words.resize(4);
+        words.shrink_to_fit();
+        assert!(!words.is_mapped());
+        assert_eq!(&*words, &[0, 0, 0, 7]);
+
+        let mut a = MmapBitSet::default();
+        let big = 64 * SPILL_WORDS * 4;
+        for bit in [5, 1000, big, big + 1] {
+            a.set(bit);
+        }
+        let b = a.clone();
+        a.clear(1000);
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![5, big, big + 1]);
+        assert_eq!(b.count(), 4);
+        assert!(a.memory_blocks() > SPILL_WORDS);
+    }
+
+    #[cfg(all(feature = "mmap", unix))]
+    #[test]
+    fn test_mmap_solver() {
+        use pror::cdcl::{State, VsidsMmapConfig};
+        use pror::dimacs;
+        use pror::sat::SatResult;
+
+        let formula = dimacs::read_example("sudoku");
+        let result = State::<VsidsMmapConfig>::solve(formula);
+        assert!(matches!(result, SatResult::Sat(_)));
+    }
+
+    #[test]
+    fn test_retain() {
+        use pror::bitset::{BTreeBitSet, BitSetT};
+        use pror::hybrid_bitset::HybridBitSet;
+        fn check<B: BitSetT>() {
+            let mut a = B::create();
+            a.set_between(0, 10_000);
+            a.set(200_000);
+            a.retain(|bit| bit % 3 == 0 && bit != 9);
+            assert_eq!(a.count(), 3333);
+            assert_eq!(a.iter().take(4).collect::<Vec<_>>(), vec![0, 3, 6, 12]);
+            // 200_000 isn't a multiple of 3
+            assert_eq!(a.last_set(), Some(9999));
+            a.retain(|bit| bit > 9990);
+            assert_eq!(a.iter().collect::<Vec<_>>(), vec![9993, 9996, 9999]);
+        }
+        check::<BitSet>();
+        check::<BTreeBitSet>();
+        check::<HybridBitSet>();
+    }
+
+    #[test]
+    fn test_cached_count() {
+        let mut a = BitSet::new(0);
+        a.set(5);
+        a.cache_count();
+        a.set(5);
+        a.set(300);
+        a.clear(6);
+        a.clear(5);
+        a.clear(10_000);
+        assert_eq!(a.count(), 1);
+        a.set_between(10, 20);
+        assert_eq!(a.count(), 11);
+        a.complement();
+        assert_eq!(a.count(), a.capacity() - 11);
+        a ^= &BitSet::from_iter([0, 1, 1000]);
+        a.retain(|bit| bit < 200);
+        assert_eq!(a.count(), a.iter().count());
+        a.clear_all();
+        assert_eq!(a.count(), 0);
+        a.as_words_mut()[0] = 0b111;
+        assert_eq!(a.count(), 3);
+    }
 }
@@ -12,6 +12,149 @@ mod tests {
     use pror::cdcl::Default;
     use pror::sat::SatResult;
 
+    #[test]
+    fn literal_packing() {
+        assert_eq!(std::mem::size_of::<Literal>(), 4);
+        let mut lits = [3, -1, 2, -3, 1]
+            .into_iter()
+            .map(Literal::from)
+            .collect::<Vec<_>>();
+        lits.sort();
+        let s = format!("{:?} {:?}", lits, lits[0].negate());
+        let expect = expect!["[Literal { value: -3 }, Literal { value: -1 }, Literal { value: 1 }, Literal { value: 2 }, Literal { value: 3 }] Literal { value: 3 }"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    #[should_panic(expected = "does not fit in a literal")]
+    fn literal_out_of_range() {
+        Literal::new(1 << 31, true);
+    }
+
+    #[test]
+    fn branching_callback() {
+        let mut solver = Default::new_from_vec(vec![vec![1, 2, 3], vec![-3, 4, 5]]);
+        solver.set_brancher(Box::new(|view| {
+            view.unassigned_variables()
+                .next()
+                .map(|var| Literal::new(var, false))
+        }));
+        let s = format!("{:?}", solver.run());
+        let expect = expect!["Sat({1: false, 2: false, 3: true, 4: false, 5: true})"];
+        expect.assert_eq(&s);
+
+        // returning None falls back to vsids
+        let mut solver = Default::new_from_vec(dimacs::read_example("sudoku"));
+        solver.set_brancher(Box::new(|view| {
+            if view.decision_level() < 3 {
+                view.unassigned_variables()
+                    .last()
+                    .map(|var| Literal::new(var, true))
+            } else {
+                None
+            }
+        }));
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+    }
+
+    #[test]
+    fn avl_score_order_matches_btree() {
+        for name in ["sudoku", "fail_eg"] {
+            let formula = dimacs::read_example(name);
+            let mut btree = State::<VsidsConfig>::new_from_vec(formula.clone());
+            let mut avl = State::<VsidsAvlConfig>::new_from_vec(formula);
+            assert_eq!(format!("{:?}", btree.run()), format!("{:?}", avl.run()));
+            assert_eq!(btree.stats().decisions, avl.stats().decisions);
+        }
+    }
+
+    #[test]
+    fn stats_and_reporting() {
+        let mut solver = Default::new_from_vec(dimacs::read_example("sudoku"));
+        let writer = SharedStringWriter::new();
+        solver.set_stats_reporter(50, writer.clone());
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+        let stats = solver.stats();
+        let lines = writer.borrow().lines().count() as u64;
+        assert_eq!(lines, stats.conflicts / 50);
+        assert!(writer
+            .borrow()
+            .lines()
+            .all(|line| line.starts_with("c decisions ")));
+        assert!(stats.decisions > 0 && stats.propagations > 0);
+        assert!(stats.profile.propagation.calls > 0 && stats.profile.analysis.calls > 0);
+        assert_eq!(stats.profile.restarts.calls, stats.restarts + 1);
+        assert!(stats
+            .profile
+            .to_progress_line()
+            .starts_with("c profile propagation "));
+        let s = Default::new_from_vec(vec![vec![1, 2], vec![-1]]).stats_json();
+        let expect = expect![[r#"{"decisions":0,"propagations":0,"conflicts":0,"restarts":0,"learned_clauses":0,"duplicate_learned_clauses":0,"subsumed_clauses":0,"eliminated_clauses":0,"two_sat_solves":0,"horn_solves":0,"cached_models":0,"replayed_propagations":0,"models_found":0,"blocking_clauses":0,"clauses":2,"variables":2}"#]];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn clause_metadata() {
+        let formula = dimacs::read_example("sudoku");
+        let num_original = formula.len();
+        let mut solver = Default::new_from_vec(formula);
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+        let (original, learned): (Vec<_>, Vec<_>) = solver
+            .clauses_iter()
+            .partition(|info| info.origin == ClauseOrigin::Original);
+        assert!(original.len() <= num_original);
+        assert!(original.iter().all(|info| info.lbd == 0));
+        assert!(!learned.is_empty());
+        assert!(learned
+            .iter()
+            .all(|info| info.lbd >= 1 && info.lbd <= info.literals.len()));
+        let solver = Default::new_from_vec(vec![vec![2, -1]]);
+        let s = format!("{:?}", solver.clauses_iter().collect::<Vec<_>>());
+        let expect = expect!["[ClauseInfo { literals: [Literal { value: -1 }, Literal { value: 2 }], activity: 0.0, lbd: 0, origin: Original }]"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn reasons() {
+        use std::fmt::Write;
+        let mut solver = Default::new_from_vec(vec![vec![1], vec![-1, 2], vec![3, 4]]);
+        solver.set_fast_paths(false);
+        let mut writer = SharedStringWriter::new();
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        for var in 1..=5 {
+            writeln!(writer, "{}: {:?}", var, solver.reason(var)).unwrap();
+        }
+        let expect = expect![[r#"
+            Sat({1: true, 2: true, 3: true, 4: true})
+            1: Some(Implied([Literal { value: 1 }]))
+            2: Some(Implied([Literal { value: -1 }, Literal { value: 2 }]))
+            3: Some(Decision)
+            4: Some(Decision)
+            5: None
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn inspect_and_undo() {
+        use std::fmt::Write;
+        let formula = vec![vec![1, 2, 3], vec![-2, 4], vec![-1, -4]];
+        let mut solver = Default::new_from_vec(formula);
+        let mut writer = SharedStringWriter::new();
+        solver.step(Some(Literal::new(1, true)));
+        solver.step(None);
+        writeln!(writer, "{:?}", solver.trail_literals()).unwrap();
+        writeln!(writer, "{:?}", solver.watched_clause_literals(Literal::new(4, false))).unwrap();
+        solver.undo_decision();
+        writeln!(writer, "{} {:?}", solver.decision_level(), solver.trail_literals()).unwrap();
+        let expect = expect![[r#"
+            [(Literal { value: 1 }, 1), (Literal { value: -4 }, 1), (Literal { value: -2 }, 1)]
+            [[Literal { value: -1 }, Literal { value: -4 }]]
+            0 []
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
     #[test]
     fn simple_satisfiable_1() {
         let formula = vec![vec![1]];
@@ -84,7 +227,7 @@ mod tests {
         let formula = vec![vec![1, -1], vec![2]];
         let result = Default::solve(formula);
         let s = format!("{:?}", result);
-        let expect = expect!["Sat({1: false, 2: true})"];
+        let expect = expect!["Sat({1: true, 2: true})"];
         expect.assert_eq(&s);
     }
 
@@ -163,7 +306,7 @@ mod tests {
             undoing trail entry: -2 at decision level 2
             adding watched literal 2 for unit clause ("(1 2)")
 
-            Continue
+            Learned(LearnedClause { literals: [Literal { value: 1 }, Literal { value: 2 }], backjump_level: 1 })
             found unit clause: Literal { value: 2 } in clause ("(1 2)") unit clauses rn: 
             adding to trail at decision level 1: 2
             updating watched clauses for literal 2
@@ -178,7 +321,7 @@ mod tests {
             undoing trail entry: -1 at decision level 1
             adding watched literal 1 for unit clause ("(1)")
 
-            Continue
+            Learned(LearnedClause { literals: [Literal { value: 1 }], backjump_level: 0 })
             found unit clause: Literal { value: 1 } in clause ("(1)") unit clauses rn: 
             adding to trail at decision level 0: 1
             updating watched clauses for literal 1
@@ -215,7 +358,7 @@ mod tests {
             undoing trail entry: 2 at decision level 1
             adding watched literal -6 for unit clause ("(-1 -6)")
 
-            Continue
+            Learned(LearnedClause { literals: [Literal { value: -1 }, Literal { value: -6 }], backjump_level: 0 })
             found unit clause: Literal { value: -6 } in clause ("(-1 -6)") unit clauses rn: 
             adding to trail at decision level 0: -6
             updating watched clauses for literal -6
@@ -829,35 +972,133 @@ mod tests {
 
     #[test]
     fn sudoku_dnf() {
-        let formula = dimacs::read_string(dimacs::SUDOKU);
+        let formula = dimacs::read_example("sudoku");
         let result = Default::solve(formula);
         assert!(matches!(result, SatResult::Sat(_)));
     }
 
     #[test]
     fn succ_dnf() {
-        let formula = dimacs::read_string(dimacs::SUCC_EG);
+        let formula = dimacs::read_example("succ_eg");
         let result = Default::solve(formula);
         assert!(matches!(result, SatResult::Sat(_)));
     }
 
     #[test]
     fn succ_factor() {
-        let formula = dimacs::read_string(dimacs::FACTOR_1234321);
+        let formula = dimacs::read_example("factor_1234321");
         let result = Default::solve(formula);
         assert!(matches!(result, SatResult::Sat(_)));
     }
 
+    #[test]
+    fn formula_stats() {
+        let mut pool = pror::pool::Pool::new();
+        let formula = Formula::<pror::fixed_bitset::BitSet>::new(
+            vec![
+                vec![1],
+                vec![1, -2],
+                vec![2, 3, -2],
+                vec![-1, 3, 4],
+                vec![4, 4],
+            ],
+            &mut pool,
+        );
+        assert_eq!(formula.unit_clauses(), vec![0, 4]);
+        assert_eq!(formula.binary_clauses(), vec![1]);
+        assert_eq!(formula.tautologies(), vec![2]);
+        assert_eq!(formula.occurrences(1), (2, 1));
+        assert_eq!(formula.occurrences(4), (3, 0));
+        let expect = expect![[r#"
+            FormulaStats {
+                num_vars: 4,
+                num_clauses: 5,
+                num_literals: 11,
+                clause_lengths: {
+                    1: 2,
+                    2: 1,
+                    3: 1,
+                },
+                unit_clauses: 2,
+                binary_clauses: 1,
+                tautologies: 1,
+                pure_literals: 2,
+            }"#]];
+        expect.assert_eq(&format!("{:#?}", formula.stats()));
+    }
+
+    #[test]
+    fn tautologies_are_satisfied() {
+        assert!(is_tautology(&[1, 2, -1]));
+        assert!(!is_tautology(&[1, 2, 1]));
+        let result = Default::solve(vec![vec![1, 2, -1], vec![-2], vec![1]]);
+        assert!(matches!(result, SatResult::Sat(_)));
+        let result = Default::solve(vec![vec![1, 1], vec![-1]]);
+        assert!(matches!(result, SatResult::UnsatCore(_)));
+
+        let mut state = Default::new_from_vec(vec![vec![1]]);
+        state.add_clause([-1, 1, 2]);
+        state.add_clauses(vec![vec![3, -3]]);
+        assert!(matches!(state.run(), SatResult::Sat(_)));
+    }
+
+    #[test]
+    fn asymmetric_tautology_elimination() {
+        // (1 3) follows from (1 2) and (-2 3), and (2 4) from (2 -1) and
+        // (1 4) once (1 3) is gone
+        let formula = vec![
+            vec![1, 2],
+            vec![-2, 3],
+            vec![1, 3],
+            vec![2, -1],
+            vec![1, 4],
+            vec![2, 4],
+        ];
+        let mut state = Default::new_from_vec(formula.clone());
+        assert_eq!(state.eliminate_asymmetric_tautologies(), 2);
+        assert_eq!(state.stats().eliminated_clauses, 2);
+        assert_eq!(state.eliminate_asymmetric_tautologies(), 0);
+        let clauses = state
+            .clauses_iter()
+            .map(|info| ints_of_literals(&info.literals))
+            .collect::<Vec<_>>();
+        let s = format!("{:?}", clauses);
+        let expect = expect!["[[1, 2], [-2, 3], [-1, 2], [1, 4]]"];
+        expect.assert_eq(&s);
+        match state.run() {
+            SatResult::Sat(model) => assert!(formula.iter().all(|clause| clause
+                .iter()
+                .any(|&lit| model[&lit.unsigned_abs()] == (lit > 0)))),
+            res => panic!("expected sat, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn proof_with_inprocessing() {
+        use pror::drup;
+        for seed in 0..5 {
+            let formula = pror::generate::random_k_cnf(40, 240, 3, seed);
+            let mut state = Default::new_from_vec(formula.clone());
+            state.start_proof();
+            state.set_ate_interval(Some(1));
+            state.set_reduce_schedule(20, 1.0);
+            if let SatResult::UnsatCore(_) = state.run() {
+                let proof = state.take_proof();
+                assert_eq!(drup::check(&formula, &proof), Ok(()), "seed {}", seed);
+            }
+        }
+    }
+
     // #[test]
     // fn fail_factor() {
-    //     let formula = dimacs::read_string(dimacs::FACTOR_1235321);
+    //     let formula = dimacs::read_example("factor_1235321");
     //     let result = Default::solve(formula);
     //     assert!(matches!(result, SatResult::Unsat));
     // }
 
     // #[test]
     // fn fail_dnf() {
-    //     let formula = dimacs::read_string(dimacs::FAIL_EG);
+    //     let formula = dimacs::read_example("fail_eg");
     //     let result = Default::solve(formula);
     //     let s = format!("{:?}", result);
     //     let expect = expect!["Unsat"];
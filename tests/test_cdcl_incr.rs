@@ -1,5 +1,6 @@
 use pror::cdcl::*;
 use pror::dimacs;
+use pror::generate::pigeonhole;
 use pror::sat::*;
 use pror::shared_string_writer::SharedStringWriter;
 
@@ -14,6 +15,30 @@ mod tests {
     use pror::cdcl::Default;
     use pror::sat::SatResult;
 
+    #[test]
+    fn core_of_propagated_conflict() {
+        // -5 forces 4, which forces both 7 and -7; the learned clause is
+        // just -4, which doesn't mention the assumption
+        let mut solver = Default::new_from_vec(vec![vec![-4, -7], vec![4, 5], vec![-4, 7]]);
+        let res = solver.run_with_assumptions(&[-5]);
+        expect!["UnsatCore([Literal { value: -5 }])"].assert_eq(&format!("{:?}", res));
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+    }
+
+    #[test]
+    fn assumptions_survive_backjumps() {
+        for seed in 0..50 {
+            let formula = pror::generate::random_k_cnf(12, 50, 3, seed);
+            let assumptions = [1, -2, 3];
+            let mut solver = State::<RandomConfig>::new_from_vec(formula);
+            if let SatResult::Sat(model) = solver.run_with_assumptions(&assumptions) {
+                for lit in assumptions {
+                    assert_eq!(model[&lit.unsigned_abs()], lit > 0, "seed {}", seed);
+                }
+            }
+        }
+    }
+
     #[test]
     fn stepped2_with_assumptions() {
         use std::fmt::Write;
@@ -56,8 +81,8 @@ mod tests {
             Sat({1: true, 2: false, 3: true, 4: false, 5: false, 6: false})
             Sat({1: true, 2: true, 3: true, 4: true, 5: false, 6: false})
             UnsatCore([Literal { value: 1 }, Literal { value: 2 }, Literal { value: 5 }])
-            Sat({1: false, 2: false, 3: true, 4: false, 5: false, 6: true})
-            UnsatCore([Literal { value: 1 }, Literal { value: 2 }])
+            Sat({1: false, 2: false, 3: false, 4: true, 5: false, 6: true})
+            UnsatCore([Literal { value: 2 }, Literal { value: 6 }])
             Sat({1: false, 2: false, 3: false, 4: false, 5: false, 6: true})
             Sat({1: false, 2: false, 3: false, 4: false, 5: false, 6: false})
         "#]];
@@ -118,11 +143,567 @@ Sat({1: true})
         let expect = expect![[r#"
             Sat({1: true, 2: true})
             Sat({1: true, 2: true, 3: true})
-            Sat({1: false, 2: true, 3: true})
+            Sat({1: true, 2: false, 3: false})
         "#]];
         expect.assert_eq(writer.borrow().as_ref());
     }
 
+    #[test]
+    fn add_clauses_in_bulk() {
+        use std::fmt::Write;
+        let mut solver = Default::new_from_vec(vec![]);
+        let mut writer = SharedStringWriter::new();
+        solver.add_clauses(vec![vec![1, 2], vec![-2, 3]]);
+        let res1 = solver.run();
+        writeln!(writer, "{:?}", res1).unwrap();
+        solver.add_clauses(vec![vec![-1, -3], vec![-3]]);
+        let res2 = solver.run();
+        writeln!(writer, "{:?}", res2).unwrap();
+        let expect = expect![[r#"
+            Sat({1: true, 2: true, 3: true})
+            Sat({1: true, 2: false, 3: false})
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+
+        let mut solver = Default::new_from_vec(vec![]);
+        solver.add_clauses(dimacs::read_example("sudoku"));
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+    }
+
+    #[test]
+    fn add_clauses_matches_add_clause() {
+        let batches = vec![
+            vec![vec![1, 2], vec![4, -4], vec![-2, 3, 6]],
+            vec![vec![5, -5, 1], vec![-1, -3], vec![-6]],
+        ];
+        let mut bulk = Default::new_from_vec(vec![]);
+        let mut single = Default::new_from_vec(vec![]);
+        let mut results = vec![];
+        for batch in batches {
+            bulk.add_clauses(batch.clone());
+            for clause in batch {
+                single.add_clause(clause);
+            }
+            let clauses = |solver: &Default| {
+                solver
+                    .clauses_iter()
+                    .map(|info| ints_of_literals(&info.literals))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(clauses(&bulk), clauses(&single));
+            assert_eq!(bulk.stats().variables, single.stats().variables);
+            let res = format!("{:?}", bulk.run());
+            assert_eq!(res, format!("{:?}", single.run()));
+            results.push(format!("{} {}", bulk.stats().variables, res));
+        }
+        let expect = expect![[r#"
+            [
+                "5 Sat({1: true, 2: true, 3: true, 4: true, 5: false, 6: true})",
+                "6 Sat({1: true, 2: false, 3: false, 4: true, 5: false, 6: false})",
+            ]
+        "#]];
+        expect.assert_debug_eq(&results);
+    }
+
+    #[test]
+    fn clauses_from_iterators() {
+        use std::fmt::Write;
+        // at most one of 1..=3, streamed as pairwise clauses
+        let pairs = (1..=3).flat_map(|a| (a + 1..=3).map(move |b| [-a, -b]));
+        let mut solver = Default::from_clauses(pairs);
+        let mut writer = SharedStringWriter::new();
+        solver.add_clause(1..=3);
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        solver.add_clauses([-1, -2].map(std::iter::once));
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        let expect = expect![[r#"
+            Sat({1: false, 2: false, 3: true})
+            Sat({1: false, 2: false, 3: true})
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn load_dimacs() {
+        use std::fmt::Write;
+        let input = "c a comment\np cnf 3 3\n1 2\n0 -1 3 0\n-3 -2 0\n-1";
+        let mut solver = Default::new_from_vec(vec![]);
+        let mut writer = SharedStringWriter::new();
+        writeln!(writer, "{:?}", solver.load_dimacs(input.as_bytes())).unwrap();
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        let mut solver = Default::new_from_vec(vec![]);
+        let num_clauses = solver
+            .load_dimacs(dimacs::example("sudoku").unwrap().as_bytes())
+            .unwrap();
+        assert_eq!(num_clauses, dimacs::read_example("sudoku").len());
+        let expect = expect![[r#"
+            Ok(4)
+            Sat({1: false, 2: true, 3: false})
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn fork_with_seed() {
+        let mut solver = Default::new_from_vec(dimacs::read_example("sudoku"));
+        let expected = match solver.run() {
+            SatResult::Sat(model) => model,
+            SatResult::UnsatCore(_) | SatResult::Unknown => panic!("sudoku should be satisfiable"),
+        };
+        let mut fork = solver.fork_with_seed(1);
+        // the sudoku has a unique solution
+        assert!(matches!(fork.run(), SatResult::Sat(model) if model == expected));
+
+        let mut solver = Default::new_from_vec(vec![vec![1, 2], vec![-1, 2]]);
+        solver.add_at_most_one(vec![2, 3]);
+        let mut fork = solver.fork_with_seed(7);
+        fork.add_clause(vec![-2]);
+        let s = format!("{:?}\n{:?}", fork.run(), solver.run());
+        let expect = expect![[r#"
+            UnsatCore([])
+            Sat({1: true, 2: true, 3: false})"#]];
+        expect.assert_eq(&s);
+
+        let mut solver = Default::new_from_vec(pigeonhole(4));
+        solver.set_restart_schedule(Box::new(pror::restart::Geometric::new(50, 1.5)));
+        solver.set_reduction_policy(Box::new(pror::reduction::ByLbd::new(0.3)));
+        solver.set_bump_reasons(true);
+        solver.set_decay_factors(0.9, 0.99);
+        let fork = solver.fork_with_seed(2);
+        assert_eq!(fork.describe(), solver.describe());
+
+        let mut solver = Default::new_from_vec(vec![vec![1, 2, 3], vec![-1, -2, -3]]);
+        solver.suggest_phase(1, true);
+        solver.suggest_phase(2, true);
+        solver.suggest_phase(3, false);
+        let mut fork = solver.fork_with_seed(5);
+        let s = format!("{:?}", fork.run());
+        expect!["Sat({1: true, 2: true, 3: false})"].assert_eq(&s);
+    }
+
+    #[test]
+    fn fork_after_ate() {
+        let formula = vec![
+            vec![1, 2],
+            vec![-2, 3],
+            vec![1, 3],
+            vec![2, -1],
+            vec![1, 4],
+            vec![2, 4],
+            vec![-1, -3, -4],
+        ];
+        let mut solver = Default::new_from_vec(formula.clone());
+        assert_eq!(solver.eliminate_asymmetric_tautologies(), 2);
+        for seed in 0..10 {
+            match solver.fork_with_seed(seed).run() {
+                SatResult::Sat(model) => assert!(formula.iter().all(|clause| clause
+                    .iter()
+                    .any(|&lit| model[&lit.unsigned_abs()] == (lit > 0)))),
+                res => panic!("expected sat, got {:?}", res),
+            }
+        }
+    }
+
+    #[test]
+    fn export_import_learned() {
+        let mut solver = Default::new_from_vec(pigeonhole(3));
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        let mut exported = vec![];
+        solver.export_learned(&mut exported).unwrap();
+        let exported = String::from_utf8(exported).unwrap();
+        let num_learned = solver
+            .clauses_iter()
+            .filter(|info| info.origin == ClauseOrigin::Learned)
+            .count();
+        assert!(num_learned > 0 && exported.starts_with("p cnf"));
+        assert_eq!(dimacs::read_string(&exported).unwrap().len(), num_learned);
+
+        let mut warm = Default::new_from_vec(pigeonhole(3));
+        warm.import_learned(exported.as_bytes()).unwrap();
+        let imported = warm
+            .clauses_iter()
+            .filter(|info| info.origin == ClauseOrigin::Learned)
+            .count();
+        assert_eq!(imported, num_learned);
+        assert!(matches!(warm.run(), SatResult::UnsatCore(_)));
+    }
+
+    #[test]
+    fn initial_model() {
+        use std::fmt::Write;
+        let formula = vec![vec![1, 2, 3], vec![-1, -2], vec![-2, -3], vec![-1, -3]];
+        let mut writer = SharedStringWriter::new();
+        let mut solver = Default::new_from_vec(formula.clone());
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        for var in 1..=3 {
+            let model = (1..=3).map(|v| (v, v == var)).collect();
+            let mut solver = Default::new_from_vec(formula.clone());
+            solver.set_initial_model(&model);
+            writeln!(writer, "{:?}", solver.run()).unwrap();
+        }
+        let expect = expect![[r#"
+            Sat({1: false, 2: false, 3: true})
+            Sat({1: true, 2: false, 3: false})
+            Sat({1: false, 2: true, 3: false})
+            Sat({1: false, 2: false, 3: true})
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn phase_and_priority_hints() {
+        use std::fmt::Write;
+        let formula = vec![vec![1, 2, 3], vec![-1, -2], vec![-2, -3], vec![-1, -3]];
+        let mut writer = SharedStringWriter::new();
+        let mut solver = Default::new_from_vec(formula.clone());
+        solver.bump_priority(1, 10.0);
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        solver.suggest_phase(1, true);
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        let mut solver = Default::new_from_vec(formula);
+        solver.suggest_phase(2, true);
+        writeln!(writer, "{:?}", solver.run()).unwrap();
+        let expect = expect![[r#"
+            Sat({1: false, 2: false, 3: true})
+            Sat({1: true, 2: false, 3: false})
+            Sat({1: false, 2: true, 3: false})
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn record_and_replay() {
+        let mut solver = Default::new_from_vec(pigeonhole(5));
+        solver.start_recording();
+        let res = solver.run();
+        let trace = solver.take_trace();
+        assert!(trace.0.contains(&pror::trace::TraceEvent::Restart));
+        let trace = pror::trace::read_string(&trace.to_string());
+
+        let mut replayed = Default::new_from_vec(pigeonhole(5));
+        replayed.start_recording();
+        let replayed_res = replayed.replay(&trace);
+        assert_eq!(format!("{:?}", res), format!("{:?}", replayed_res));
+        assert_eq!(replayed.take_trace(), trace);
+    }
+
+    #[test]
+    fn learned_clauses_are_distinct() {
+        let mut solver = Default::new_from_vec(pigeonhole(5));
+        loop {
+            match solver.step(None) {
+                StepResult::Done(res) => {
+                    assert!(matches!(res, SatResult::UnsatCore(_)));
+                    break;
+                }
+                _ => assert_eq!(solver.invariant_violations(), Vec::<String>::new()),
+            }
+        }
+        let learned = solver
+            .clauses_iter()
+            .filter(|info| info.origin == ClauseOrigin::Learned)
+            .map(|info| info.literals)
+            .collect::<Vec<_>>();
+        let distinct = learned.iter().collect::<std::collections::BTreeSet<_>>();
+        assert!(!learned.is_empty());
+        assert_eq!(learned.len(), distinct.len());
+        assert!(solver.stats().subsumed_clauses > 0);
+    }
+
+    #[test]
+    fn reduce_schedule() {
+        let mut schedule = pror::reduce_schedule::ReduceSchedule::new(4, 1.5);
+        let mut limits = vec![];
+        for _ in 0..4 {
+            limits.push(schedule.limit());
+            while !schedule.due() {
+                schedule.learned();
+            }
+            schedule.reduced();
+        }
+        assert_eq!(limits, vec![4, 6, 9, 13]);
+
+        let mut solver = Default::new_from_vec(pigeonhole(5));
+        solver.set_reduce_schedule(10, 2.0);
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        let stats = solver.stats();
+        assert!(stats.learned_clauses > 30);
+        assert!(stats.profile.reduction.calls >= 2);
+    }
+
+    #[test]
+    fn learned_memory_limit() {
+        let mut solver = Default::new_from_vec(pigeonhole(6));
+        solver.set_learned_memory_limit(Some(0));
+        assert!(matches!(solver.run(), SatResult::Unknown));
+
+        let mut unlimited = Default::new_from_vec(pigeonhole(6));
+        assert!(matches!(unlimited.run(), SatResult::UnsatCore(_)));
+        let limit = unlimited.learned_clause_bytes() / 4;
+        let mut limited = Default::new_from_vec(pigeonhole(6));
+        limited.set_learned_memory_limit(Some(limit));
+        assert!(matches!(limited.run(), SatResult::UnsatCore(_)));
+        assert!(limited.learned_clause_bytes() < unlimited.learned_clause_bytes());
+    }
+
+    #[test]
+    fn reproducible_runs() {
+        let formula = pror::generate::random_k_cnf(40, 170, 3, 1);
+        let run = |mut solver: State<RandomConfig>| {
+            solver.start_recording();
+            let res = solver.run();
+            format!("{:?}\n{}", res, solver.take_trace())
+        };
+        let first = run(State::new_from_vec(formula.clone()));
+        assert_eq!(first, run(State::new_from_vec(formula.clone())));
+        let solver = State::<RandomConfig>::new_from_vec(formula);
+        assert_eq!(run(solver.fork_with_seed(3)), run(solver.fork_with_seed(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "replay diverged")]
+    fn replay_divergence() {
+        let mut solver = Default::new_from_vec(vec![vec![1, 2], vec![-1, 2]]);
+        solver.replay(&pror::trace::read_string("-2 2"));
+    }
+
+    #[test]
+    fn explain_literal() {
+        let mut solver =
+            Default::new_from_vec(vec![vec![-1, 2], vec![-2, -3, 4], vec![-4, 5], vec![6]]);
+        // propagates whatever is pending first, so only decides `lit` if
+        // nothing else assigns it
+        let mut assign = |lit: isize| {
+            while solver.reason(lit.unsigned_abs()).is_none() {
+                solver.step(Some(lit.into()));
+            }
+        };
+        assign(1);
+        assign(3);
+        assign(5);
+        assert_eq!(solver.explain(Literal::new(5, false)), None);
+        assert_eq!(solver.explain(Literal::new(7, true)), None);
+        let explanation = solver.explain(Literal::new(5, true)).unwrap();
+        assert_eq!(
+            explanation.decisions,
+            vec![(Literal::new(1, true), 1), (Literal::new(3, true), 2)]
+        );
+        let expect = expect![[r#"
+            5 follows from deciding 1 @ 1, 3 @ 2
+              2 @ 1 by (-1 2)
+              4 @ 2 by (-2 -3 4)
+              5 @ 2 by (-4 5)
+        "#]];
+        expect.assert_eq(&explanation.to_string());
+        let expect = expect![[r#"
+            6 holds at level 0
+              6 @ 0 by (6)
+        "#]];
+        expect.assert_eq(&solver.explain(Literal::new(6, true)).unwrap().to_string());
+    }
+
+    #[test]
+    fn refutation_at_level_zero() {
+        let mut solver = Default::new_from_vec(vec![
+            vec![1],
+            vec![-1, 2],
+            vec![-2, 3],
+            vec![-3, -1],
+            vec![4, 5],
+        ]);
+        solver.set_fast_paths(false);
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        let expect = expect![[r#"
+            (-1 -3) is falsified by
+              1 @ 0 by (1)
+              2 @ 0 by (-1 2)
+              3 @ 0 by (-2 3)
+        "#]];
+        expect.assert_eq(&solver.refutation().unwrap().to_string());
+
+        let mut solver = Default::new_from_vec(vec![vec![1, 2], vec![-1, 2], vec![-2, 3]]);
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+        assert_eq!(solver.refutation(), None);
+        solver.add_clause([-3]);
+        solver.add_clause([1, -2]);
+        solver.set_fast_paths(false);
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        let expect = expect![[r#"
+            (-1 2) is falsified by
+              -3 @ 0 by (-3)
+              -2 @ 0 by (-2 3)
+              1 @ 0 by (1 2)
+        "#]];
+        expect.assert_eq(&solver.refutation().unwrap().to_string());
+        let res = solver.run_with_assumptions(&[1]);
+        assert!(matches!(res, SatResult::UnsatCore(_)));
+        assert!(solver.refutation().is_some());
+    }
+
+    #[test]
+    fn fast_path_leaves_trail_empty() {
+        let formula = vec![vec![1, 2], vec![-1, 2], vec![-2, 3]];
+        let mut solver = Default::new_from_vec(formula.clone());
+        let model = match solver.run() {
+            SatResult::Sat(model) => model,
+            res => panic!("expected sat, got {:?}", res),
+        };
+        assert_eq!(solver.stats().two_sat_solves, 1);
+        assert!(solver.trail_literals().is_empty());
+        assert!(solver.reason(3).is_none());
+        assert!(solver.explain(Literal::new(3, model[&3])).is_none());
+        // nothing was fixed at level 0, so the model can still change
+        solver.add_clause([-1]);
+        let s = format!("{:?}", solver.run());
+        expect!["Sat({1: false, 2: true, 3: true})"].assert_eq(&s);
+
+        let mut solver = Default::new_from_vec(formula);
+        solver.set_fast_paths(false);
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+        assert_eq!(solver.trail_literals().len(), 3);
+        assert!(solver.explain(Literal::new(3, true)).is_some());
+    }
+
+    #[test]
+    fn import_clause_between_steps() {
+        use std::fmt::Write;
+        let step_to_done = |solver: &mut Default| loop {
+            if let StepResult::Done(res) = solver.step(None) {
+                return res;
+            }
+        };
+        let mut writer = SharedStringWriter::new();
+        let mut solver = Default::new_from_vec(vec![vec![1, 2, 3], vec![-1, 4]]);
+        solver.step(Some(Literal::new(1, true)));
+        solver.step(None);
+        writeln!(writer, "{:?}", solver.trail_literals()).unwrap();
+        // falsified by the trail
+        writeln!(writer, "{:?}", solver.import_clause(&[-1, -4])).unwrap();
+        writeln!(writer, "{:?}", solver.trail_literals()).unwrap();
+        writeln!(writer, "{:?}", step_to_done(&mut solver)).unwrap();
+        writeln!(writer, "{:?}", solver.import_clause(&[-2])).unwrap();
+        // unit under -2, and propagating it falsifies (1 2 3) at level 0
+        writeln!(writer, "{:?}", solver.import_clause(&[-3])).unwrap();
+        let expect = expect![[r#"
+            [(Literal { value: 1 }, 1), (Literal { value: 4 }, 1)]
+            Learned(LearnedClause { literals: [Literal { value: -1 }], backjump_level: 0 })
+            []
+            Sat({1: false, 2: true, 3: true, 4: true})
+            Learned(LearnedClause { literals: [Literal { value: -2 }], backjump_level: 0 })
+            Done(UnsatCore([]))
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn add_clause_between_steps() {
+        use std::fmt::Write;
+        let mut writer = SharedStringWriter::new();
+        let mut solver = Default::new_from_vec(vec![vec![1, 2, 3], vec![-1, 4]]);
+        solver.step(Some(Literal::new(1, true)));
+        solver.step(None);
+        solver.step(Some(Literal::new(2, true)));
+        writeln!(writer, "{:?}", solver.trail_literals()).unwrap();
+        // falsified with -2 alone at level 2, so unit at level 1
+        solver.add_clause(vec![-1, -2]);
+        writeln!(writer, "{:?}", solver.trail_literals()).unwrap();
+        solver.step(None);
+        writeln!(writer, "{:?}", solver.trail_literals()).unwrap();
+        // falsified at level 1, so unit at level 0
+        solver.add_clause(vec![-4]);
+        solver.step(None);
+        writeln!(writer, "{:?}", solver.trail_literals()).unwrap();
+        assert_eq!(solver.invariant_violations(), Vec::<String>::new());
+        // falsified at level 0
+        solver.add_clause(vec![1, 4]);
+        writeln!(writer, "{:?}", solver.step(None)).unwrap();
+        let expect = expect![[r#"
+            [(Literal { value: 1 }, 1), (Literal { value: 4 }, 1), (Literal { value: 2 }, 2)]
+            [(Literal { value: 1 }, 1), (Literal { value: 4 }, 1)]
+            [(Literal { value: 1 }, 1), (Literal { value: 4 }, 1), (Literal { value: -2 }, 1)]
+            [(Literal { value: -4 }, 0), (Literal { value: -1 }, 0)]
+            Done(UnsatCore([]))
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+
+        let mut solver = Default::new_from_vec(vec![vec![1, 2]]);
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+        solver.add_clause(vec![]);
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        let mut solver = Default::new_from_vec(vec![vec![1, 2]]);
+        solver.add_clauses(vec![vec![3], vec![]]);
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+    }
+
+    #[test]
+    fn clause_exporter() {
+        use std::sync::{Arc, Mutex};
+        let exported = Arc::new(Mutex::new(vec![]));
+        let mut solver = Default::new_from_vec(pigeonhole(4));
+        let sink = Arc::clone(&exported);
+        solver.set_clause_exporter(
+            2,
+            Box::new(move |clause| sink.lock().unwrap().push(clause.to_vec())),
+        );
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        let exported = exported.lock().unwrap();
+        assert!(!exported.is_empty());
+        assert!(exported.len() <= solver.stats().learned_clauses as usize);
+        for clause in solver.clauses_iter().filter(|info| info.lbd <= 2) {
+            if clause.origin == ClauseOrigin::Learned {
+                let lits = clause
+                    .literals
+                    .iter()
+                    .map(|&lit| lit.into())
+                    .collect::<Vec<isize>>();
+                assert!(exported.contains(&lits));
+            }
+        }
+
+        // the exported clauses are implied, so a fresh solver can import them
+        let mut importer = Default::new_from_vec(pigeonhole(4));
+        for clause in exported.iter() {
+            importer.import_clause(clause);
+        }
+        assert!(matches!(importer.run(), SatResult::UnsatCore(_)));
+    }
+
+    #[test]
+    fn import_clauses_under_trail() {
+        for seed in 0..50 {
+            let formula = pror::generate::random_k_cnf(25, 105, 3, seed);
+            let expected = Default::solve(formula.clone());
+            let mut solver = DefaultDebug::new_from_vec(formula.clone());
+            let mut step = 0;
+            let res = loop {
+                if let StepResult::Done(res) = solver.step(None) {
+                    break res;
+                }
+                // weakening an original clause by the latest assignments keeps
+                // it implied, and the trail falsifies it or makes it unit
+                let mut clause = formula[step % formula.len()].clone();
+                for &(literal, _) in solver.trail_literals().iter().rev().take(step % 3) {
+                    clause.push(literal.negate().into());
+                }
+                step += 1;
+                let res = solver.import_clause(&clause);
+                assert_eq!(solver.invariant_violations(), Vec::<String>::new());
+                if let StepResult::Done(res) = res {
+                    break res;
+                }
+            };
+            match (res, expected) {
+                (SatResult::Sat(model), SatResult::Sat(_)) => {
+                    assert!(formula.iter().all(|clause| clause
+                        .iter()
+                        .any(|&lit| model[&lit.unsigned_abs()] == (lit > 0))));
+                }
+                (SatResult::UnsatCore(_), SatResult::UnsatCore(_)) => (),
+                results => panic!("seed {}: {:?}", seed, results),
+            }
+        }
+    }
+
     #[test]
     fn introduces_smaller_variable_after_larger_clause() {
         use std::fmt::Write;
@@ -138,9 +719,9 @@ Sat({1: true})
         writeln!(writer, "{:?}", res2).unwrap();
 
         let expect = expect![[r#"
-Sat({7: true})
-Sat({2: true, 7: true})
-"#]];
+            Sat({1: false, 2: false, 3: false, 4: false, 5: false, 6: false, 7: true})
+            Sat({1: false, 2: true, 3: false, 4: false, 5: false, 6: false, 7: true})
+        "#]];
         expect.assert_eq(writer.borrow().as_ref());
     }
 
@@ -192,4 +773,502 @@ Sat({1: true})
 "#]];
         expect.assert_eq(writer.borrow().as_ref());
     }
+
+    #[test]
+    fn enumerate_models_projected() {
+        use std::fmt::Write;
+        // 3 <-> (1 & 2), with 4 unconstrained
+        let formula = vec![vec![-3, 1], vec![-3, 2], vec![3, -1, -2], vec![1, 2, 4]];
+        let mut solver = Default::new_from_vec(formula);
+        let mut writer = SharedStringWriter::new();
+        for model in solver.enumerate_models_projected(&[1, 2], 10) {
+            writeln!(writer, "{:?}", model).unwrap();
+        }
+        let res = solver.enumerate_models_projected(&[1, 2], 10);
+        writeln!(writer, "{:?}", res).unwrap();
+        let expect = expect![[r#"
+            {1: true, 2: true}
+            {1: false, 2: true}
+            {1: true, 2: false}
+            {1: false, 2: false}
+            []
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn enumerate_models_projected_limit() {
+        let mut solver = Default::new_from_vec(vec![vec![1, 2, 3]]);
+        let models = solver.enumerate_models_projected(&[1, 2, 3], 3);
+        assert_eq!(models.len(), 3);
+        let models = solver.enumerate_models_projected(&[1, 2, 3], 10);
+        assert_eq!(models.len(), 4);
+    }
+
+    #[test]
+    fn count_models_projected() {
+        use std::collections::BTreeSet;
+        let num_vars = 10;
+        for seed in 0..20 {
+            // two halves over disjoint variables, and 10 occurring nowhere
+            let mut formula = pror::generate::random_k_cnf(4, 6, 2, seed);
+            formula.extend(
+                pror::generate::random_k_cnf(5, 8, 3, seed + 100)
+                    .into_iter()
+                    .map(|clause| {
+                        clause
+                            .into_iter()
+                            .map(|lit| lit + lit.signum() * 4)
+                            .collect()
+                    }),
+            );
+            let mut solver = Default::new_from_vec(formula.clone());
+            solver.add_at_most_one(vec![5, 6, -7]);
+            let projection = [1, 3, 5, 6, 10];
+            let brute_force = (0..1u32 << num_vars)
+                .map(|bits| move |var: usize| bits & (1 << (var - 1)) != 0)
+                .filter(|value| {
+                    formula.iter().all(|clause| {
+                        clause
+                            .iter()
+                            .any(|&lit| value(lit.unsigned_abs()) == (lit > 0))
+                    }) && [value(5), value(6), !value(7)]
+                        .iter()
+                        .filter(|&&v| v)
+                        .count()
+                        <= 1
+                })
+                .map(|value| projection.map(value))
+                .collect::<BTreeSet<_>>()
+                .len();
+            assert_eq!(
+                solver
+                    .count_models_projected(&projection)
+                    .map(|count| count.to_string()),
+                Some(brute_force.to_string()),
+                "seed {}",
+                seed
+            );
+            assert!(matches!(solver.run(), SatResult::Sat(_)) == (brute_force > 0));
+        }
+    }
+
+    #[test]
+    fn count_models_projected_large() {
+        // 3 models of the clause, times 2^100 for the variables it doesn't use
+        let solver = Default::new_from_vec(vec![vec![1, 2]]);
+        let vars = (1..=102).collect::<Vec<_>>();
+        let count = solver.count_models_projected(&vars).unwrap();
+        expect!["3802951800684688204490109616128"].assert_eq(&count.to_string());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn count_models_projected_past_u128() {
+        let solver = Default::new_from_vec(vec![vec![1, 2]]);
+        let vars = (1..=202).collect::<Vec<_>>();
+        let count = solver.count_models_projected(&vars);
+        assert_eq!(count, Some(num_bigint::BigUint::from(3u8) << 200));
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn count_models_projected_overflow() {
+        let solver = Default::new_from_vec(vec![vec![1, 2]]);
+        let vars = (1..=202).collect::<Vec<_>>();
+        assert_eq!(solver.count_models_projected(&vars), None);
+    }
+
+    #[test]
+    fn enumeration_progress() {
+        use std::sync::{Arc, Mutex};
+        let events = Arc::new(Mutex::new(vec![]));
+        let mut solver = Default::new_from_vec(vec![vec![1, 2], vec![-1, -2, 3]]);
+        let hook_events = Arc::clone(&events);
+        solver.set_event_hook(Box::new(move |event, view| {
+            if matches!(event, SearchEvent::Model | SearchEvent::Exhausted) {
+                let stats = view.stats();
+                let progress = (stats.models_found, stats.blocking_clauses);
+                hook_events.lock().unwrap().push((event.clone(), progress));
+            }
+        }));
+        let mut models = solver.models();
+        assert_eq!(models.by_ref().take(2).count(), 2);
+        assert!(!models.is_exhausted());
+        assert_eq!(models.count(), 3);
+        let stats = solver.stats();
+        assert_eq!((stats.models_found, stats.blocking_clauses), (5, 5));
+        let mut models = solver.models();
+        assert_eq!(models.next(), None);
+        assert!(models.is_exhausted());
+        expect!["[(Model, (1, 0)), (Model, (2, 1)), (Model, (3, 2)), (Model, (4, 3)), (Model, (5, 4)), (Exhausted, (5, 5)), (Exhausted, (5, 5))]"]
+        .assert_eq(&format!("{:?}", events.lock().unwrap()));
+    }
+
+    #[test]
+    fn models_iterator() {
+        use std::fmt::Write;
+        let formula = vec![vec![1, 2], vec![-1, -2, 3]];
+        let mut solver = Default::new_from_vec(formula);
+        let mut writer = SharedStringWriter::new();
+        for model in solver.models() {
+            writeln!(writer, "{:?}", model).unwrap();
+        }
+        let mut models = solver.models();
+        writeln!(writer, "{:?}", models.next()).unwrap();
+        drop(models);
+        let mut solver = Default::new_from_vec(vec![vec![1, 2], vec![-1, -2, 3]]);
+        let mut models = solver.models().project(&[1]);
+        writeln!(writer, "{:?}", models.next()).unwrap();
+        drop(models);
+        for model in solver.models().project(&[1]) {
+            writeln!(writer, "{:?}", model).unwrap();
+        }
+        let expect = expect![[r#"
+            {1: true, 2: false, 3: true}
+            {1: false, 2: true, 3: true}
+            {1: true, 2: true, 3: true}
+            {1: false, 2: true, 3: false}
+            {1: true, 2: false, 3: false}
+            None
+            Some({1: true})
+            {1: false}
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn literal_assumptions() {
+        use std::fmt::Write;
+        let mut solver = Default::new_from_vec(vec![vec![1, 2], vec![-1, 3]]);
+        let mut writer = SharedStringWriter::new();
+        let assumptions = literals_of_ints(&[1, -3]);
+        writeln!(writer, "{:?}", ints_of_literals(&assumptions)).unwrap();
+        let res = solver.run_with_literal_assumptions(&assumptions);
+        writeln!(writer, "{:?}", res).unwrap();
+        let res = solver.run_with_literal_assumptions(&[Literal::new(2, false)]);
+        writeln!(writer, "{:?}", res).unwrap();
+        let res = solver.run_with_literal_assumptions(&[Literal::new(2, true), Literal::new(7, true)]);
+        writeln!(writer, "{:?}", res).unwrap();
+        let expect = expect![[r#"
+            [1, -3]
+            Ok(UnsatCore([Literal { value: 1 }, Literal { value: -3 }]))
+            Ok(Sat({1: true, 2: false, 3: true}))
+            Err(UnknownVariable(7))
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn inccnf_replay() {
+        use std::fmt::Write;
+        let lines = dimacs::read_inccnf_string(
+            "p inccnf\n\
+             c a comment 1 2 3\n\
+             1 2 0\n\
+             -1 3 0\n\
+             a -2 0\n\
+             -3 0\n\
+             a -2 0\n\
+             a 0\n",
+        )
+        .unwrap();
+        let mut solver = Default::new_from_vec(vec![]);
+        let mut writer = SharedStringWriter::new();
+        for res in solver.run_inccnf(lines) {
+            writeln!(writer, "{:?}", res).unwrap();
+        }
+        let expect = expect![[r#"
+            Sat({1: true, 2: false, 3: true})
+            UnsatCore([Literal { value: -2 }])
+            Sat({1: false, 2: true, 3: false})
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+
+    #[test]
+    fn solve_with_timeout() {
+        use std::sync::atomic::AtomicBool;
+        use std::sync::Arc;
+        use std::time::Duration;
+
+        // 10 pigeons in 9 holes is far too much for a few milliseconds
+        let pigeons = 10;
+        let holes = 9;
+        let var = |pigeon: isize, hole: isize| pigeon * holes + hole + 1;
+        let mut formula = (0..pigeons)
+            .map(|pigeon| (0..holes).map(|hole| var(pigeon, hole)).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        for hole in 0..holes {
+            for a in 0..pigeons {
+                for b in a + 1..pigeons {
+                    formula.push(vec![-var(a, hole), -var(b, hole)]);
+                }
+            }
+        }
+        let outcome = Default::solve_with_timeout(formula, Duration::from_millis(20));
+        assert!(outcome.timed_out);
+        assert!(matches!(outcome.result, SatResult::Unknown));
+        assert!(outcome.elapsed < Duration::from_secs(10));
+        assert!(outcome.stats.conflicts > 0);
+
+        let outcome =
+            Default::solve_with_timeout(vec![vec![1, 2], vec![-1]], Duration::from_secs(60));
+        assert!(!outcome.timed_out);
+        assert!(matches!(outcome.result, SatResult::Sat(model) if model[&2]));
+
+        let mut state = Default::new_from_vec(vec![vec![1, 2], vec![-1, 2]]);
+        state.set_fast_paths(false);
+        state.set_cancel_flag(Some(Arc::new(AtomicBool::new(true))));
+        assert!(matches!(state.run(), SatResult::Unknown));
+        state.set_cancel_flag(None);
+        assert!(matches!(state.run(), SatResult::Sat(_)));
+
+        // neither the 2-SAT fast path nor elimination starts once cancelled
+        let cancelled = Some(Arc::new(AtomicBool::new(true)));
+        let mut state = Default::new_from_vec(vec![vec![1, 2], vec![-1, 2]]);
+        state.set_cancel_flag(cancelled.clone());
+        assert!(matches!(state.run(), SatResult::Unknown));
+        assert_eq!(state.stats().two_sat_solves, 0);
+        let mut state = Default::new_from_vec(vec![vec![1, 2], vec![-2, 3], vec![1, 3]]);
+        state.set_cancel_flag(cancelled);
+        assert_eq!(state.eliminate_asymmetric_tautologies(), 0);
+        state.set_cancel_flag(None);
+        assert_eq!(state.eliminate_asymmetric_tautologies(), 1);
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn solve_cubes() {
+        // every sign combination of the first three variables
+        let cubes = (0..8isize)
+            .map(|bits| {
+                (1..=3)
+                    .map(|var| var * (bits >> (var - 1) & 1) * 2 - var)
+                    .collect()
+            })
+            .collect::<Vec<Vec<isize>>>();
+        for seed in 0..10 {
+            let formula = pror::generate::random_k_cnf(30, 130, 3, seed);
+            let outcome = Default::solve_cubes(&formula, &cubes);
+            match (Default::solve(formula.clone()), outcome.result) {
+                (SatResult::Sat(_), SatResult::Sat(model)) => {
+                    assert!(formula.iter().all(|clause| clause
+                        .iter()
+                        .any(|&lit| model[&lit.unsigned_abs()] == (lit > 0))))
+                }
+                (SatResult::UnsatCore(_), SatResult::UnsatCore(_)) => {
+                    // nothing was cancelled, so every cube's stats count
+                    let mut conflicts = 0;
+                    for cube in &cubes {
+                        let mut solver = Default::new_from_vec(formula.clone());
+                        solver.run_with_assumptions(cube);
+                        conflicts += solver.stats().conflicts;
+                    }
+                    assert_eq!(outcome.stats.conflicts, conflicts, "seed {}", seed);
+                }
+                (plain, cubed) => panic!("seed {}: {:?} but cubes gave {:?}", seed, plain, cubed),
+            }
+        }
+    }
+
+    #[test]
+    fn assumption_order() {
+        // only 29 and 30 conflict, and they come last
+        let mut formula = (1..=28).map(|var| vec![var, 31]).collect::<Vec<_>>();
+        formula.push(vec![-29, -30]);
+        let assumptions = (1..=30).collect::<Vec<isize>>();
+        let mut decisions = vec![];
+        for policy in [
+            AssumptionOrder::AsGiven,
+            AssumptionOrder::Activity,
+            AssumptionOrder::LastFailure,
+        ] {
+            let mut state = Default::new_from_vec(formula.clone());
+            state.set_fast_paths(false);
+            state.set_assumption_order_policy(policy);
+            let mut cores = vec![];
+            let mut per_solve = vec![];
+            for _ in 0..2 {
+                let before = state.stats().decisions;
+                match state.run_with_assumptions(&assumptions) {
+                    SatResult::UnsatCore(core) => cores.push(ints_of_literals(&core)),
+                    res => panic!("expected a core, got {:?}", res),
+                }
+                per_solve.push(state.stats().decisions - before);
+            }
+            assert!(cores
+                .iter()
+                .all(|core| core.contains(&29) && core.contains(&30)));
+            decisions.push((policy, per_solve));
+        }
+        let s = format!("{:?}", decisions);
+        let expect = expect!["[(AsGiven, [29, 29]), (Activity, [29, 29]), (LastFailure, [29, 1])]"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn reset_and_clear() {
+        let formula = pror::generate::random_k_cnf(20, 100, 3, 1);
+        let mut fresh = Default::new_from_vec(formula.clone());
+        fresh.add_at_most_one(vec![1, 2, 3]);
+        fresh.set_fast_paths(false);
+        let expected = format!("{:?}", fresh.run());
+
+        let mut state = Default::new_from_vec(formula.clone());
+        state.add_at_most_one(vec![1, 2, 3]);
+        state.set_fast_paths(false);
+        let num_clauses = state.clauses_iter().count();
+        assert_eq!(format!("{:?}", state.run()), expected);
+        assert!(state.stats().learned_clauses > 0);
+        state.reset();
+        assert_eq!(state.clauses_iter().count(), num_clauses);
+        assert_eq!(state.stats().conflicts, 0);
+        assert_eq!(state.decision_level(), 0);
+        assert_eq!(format!("{:?}", state.run()), expected);
+
+        // the fast paths stay off, so the 2-SAT solver isn't used
+        state.clear();
+        assert_eq!(state.clauses_iter().count(), 0);
+        state.add_clauses(vec![vec![1, 2], vec![-1, 2], vec![-2, -3]]);
+        let res = state.run();
+        assert_eq!(state.stats().two_sat_solves, 0);
+        expect!["Sat({1: true, 2: true, 3: false})"].assert_eq(&format!("{:?}", res));
+    }
+
+    #[test]
+    fn cached_model() {
+        let mut state = Default::new_from_vec(pror::generate::random_k_cnf(20, 60, 3, 2));
+        state.set_fast_paths(false);
+        let mut results = vec![];
+        let mut run = |state: &mut Default| {
+            let decisions = state.stats().decisions;
+            let res = format!("{:?}", state.run());
+            let stats = state.stats();
+            results.push((stats.cached_models, stats.decisions > decisions));
+            res
+        };
+        let first = run(&mut state);
+        assert_eq!(run(&mut state), first);
+        state.run_with_assumptions(&[1]);
+        run(&mut state);
+        run(&mut state);
+        state.add_clause(vec![-4, 5]);
+        run(&mut state);
+        state.suggest_phase(3, true);
+        run(&mut state);
+        expect!["[(0, true), (1, false), (1, true), (2, false), (2, true), (2, true)]"]
+            .assert_eq(&format!("{:?}", results));
+    }
+
+    #[test]
+    fn event_hook() {
+        use std::sync::{Arc, Mutex};
+        let counts = Arc::new(Mutex::new([0u64; 3]));
+        let mut solver = Default::new_from_vec(pigeonhole(5));
+        let hook_counts = Arc::clone(&counts);
+        solver.set_event_hook(Box::new(move |event, view| {
+            let mut counts = hook_counts.lock().unwrap();
+            match event {
+                SearchEvent::Decision(literal) => {
+                    assert_eq!(view.value(literal.variable()), None);
+                    counts[0] += 1;
+                }
+                SearchEvent::Conflict(learned) => {
+                    assert_eq!(view.decision_level(), learned.backjump_level);
+                    counts[1] += 1;
+                }
+                SearchEvent::Restart => {
+                    assert_eq!(view.trail_with_levels().count(), view.trail().count());
+                    counts[2] += 1;
+                }
+                SearchEvent::Model | SearchEvent::Exhausted => unreachable!("not enumerating"),
+            }
+        }));
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        let stats = solver.stats();
+        // the final conflict at level 0 refutes rather than backjumps
+        assert_eq!(
+            *counts.lock().unwrap(),
+            [stats.decisions, stats.conflicts - 1, stats.restarts]
+        );
+        assert!(stats.restarts > 0);
+    }
+
+    #[test]
+    fn trail_saving() {
+        let mut replayed = 0;
+        for seed in 0..20 {
+            let formula = pror::generate::random_k_cnf(60, 258, 3, seed);
+            let mut plain = Default::new_from_vec(formula.clone());
+            let mut saving = Default::new_from_vec(formula.clone());
+            saving.set_trail_saving(true);
+            match (plain.run(), saving.run()) {
+                (SatResult::Sat(_), SatResult::Sat(model)) => {
+                    assert!(formula.iter().all(|clause| clause
+                        .iter()
+                        .any(|&lit| model[&lit.unsigned_abs()] == (lit > 0))));
+                }
+                (SatResult::UnsatCore(_), SatResult::UnsatCore(_)) => (),
+                results => panic!("seed {}: {:?}", seed, results),
+            }
+            replayed += saving.stats().replayed_propagations;
+            assert_eq!(plain.stats().replayed_propagations, 0);
+        }
+        assert!(replayed > 0);
+
+        let mut solver = Default::new_from_vec(pigeonhole(6));
+        solver.set_trail_saving(true);
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        assert!(solver.stats().replayed_propagations > 0);
+    }
+
+    #[test]
+    fn invariants() {
+        for seed in 0..20 {
+            let formula = pror::generate::random_k_cnf(30, 128, 3, seed);
+            let mut solver = Default::new_from_vec(formula.clone());
+            solver.set_trail_saving(seed % 2 == 0);
+            loop {
+                match solver.step(None) {
+                    StepResult::Done(_) => break,
+                    _ => assert_eq!(solver.invariant_violations(), Vec::<String>::new()),
+                }
+            }
+
+            // the debug config checks after every step itself, so this
+            // panics if incremental solving breaks them
+            let (first, second) = formula.split_at(64);
+            let mut solver = DefaultDebug::new_from_vec(first.to_vec());
+            solver.add_at_most_one(vec![1, 2, 3]);
+            solver.run();
+            for clause in second {
+                solver.add_clause(clause.clone());
+            }
+            solver.run_with_assumptions(&[-3, 4]);
+            solver.models().take(10).count();
+        }
+    }
+
+    #[test]
+    fn complete_models() {
+        let formula = vec![vec![1, -5], vec![5, 9], vec![-1, 5, 9]];
+        for fast_paths in [true, false] {
+            let mut solver = Default::new_from_vec(formula.clone());
+            solver.set_fast_paths(fast_paths);
+            let SatResult::Sat(model) = solver.run() else {
+                panic!("unsat")
+            };
+            assert_eq!(
+                model.keys().copied().collect::<Vec<_>>(),
+                (1..=9).collect::<Vec<_>>()
+            );
+            assert!([2, 3, 4, 6, 7, 8].iter().all(|var| !model[var]));
+        }
+        // the unused variables don't multiply the models
+        let mut solver = Default::new_from_vec(vec![vec![1, 3]]);
+        let models = solver.models().collect::<Vec<_>>();
+        expect![[r#"[{1: true, 2: false, 3: true}, {1: true, 2: false, 3: false}, {1: false, 2: false, 3: true}]"#]]
+            .assert_eq(&format!("{:?}", models));
+    }
 }
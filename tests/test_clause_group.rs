@@ -0,0 +1,39 @@
+use pror::cdcl::*;
+use pror::clause_group::*;
+use pror::shared_string_writer::SharedStringWriter;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    use pror::cdcl::Default;
+
+    #[test]
+    fn enable_and_disable_groups() {
+        use std::fmt::Write;
+        let mut solver = Default::new_from_vec(vec![vec![1, 2]]);
+        let mut groups = ClauseGroups::new(3);
+        let not_1 = groups.new_group();
+        let not_2 = groups.new_group();
+        let unused = groups.new_group();
+        groups.add_clause(&mut solver, not_1, vec![-1]);
+        groups.add_clause(&mut solver, not_2, vec![-2]);
+
+        let mut writer = SharedStringWriter::new();
+        writeln!(writer, "{:?}", groups.run(&mut solver, &[])).unwrap();
+        writeln!(writer, "{:?}", groups.run(&mut solver, &[not_1])).unwrap();
+        writeln!(writer, "{:?}", groups.run(&mut solver, &[not_2, unused])).unwrap();
+        writeln!(writer, "{:?}", groups.run(&mut solver, &[not_1, not_2])).unwrap();
+        writeln!(writer, "{:?}", groups.run(&mut solver, &[not_2])).unwrap();
+        let expect = expect![[r#"
+            Sat({1: true, 2: true})
+            Sat({1: false, 2: true})
+            Sat({1: true, 2: false})
+            UnsatCore([ClauseGroup(0), ClauseGroup(1)])
+            Sat({1: true, 2: false})
+        "#]];
+        expect.assert_eq(writer.borrow().as_ref());
+    }
+}
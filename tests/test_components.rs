@@ -0,0 +1,21 @@
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pror::components::*;
+
+    #[test]
+    fn components() {
+        let clauses = vec![
+            vec![1, -2],
+            vec![3, 4],
+            vec![],
+            vec![-5],
+            vec![2, 6],
+            vec![-4, 7, -6],
+            vec![8],
+        ];
+        let components = connected_components(&clauses);
+        expect!["[[0, 1, 4, 5], [2], [3], [6]]"].assert_eq(&format!("{:?}", components));
+        assert!(connected_components(&[]).is_empty());
+    }
+}
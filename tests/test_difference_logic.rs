@@ -0,0 +1,84 @@
+use pror::difference_logic::DifferenceLogic;
+use pror::sat::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    use pror::cdcl::Default;
+
+    #[test]
+    fn cyclic_precedence() {
+        // x0 < x1 < x2 < x0
+        let mut theory = DifferenceLogic::new();
+        theory.add_atom(1, 0, 1, -1);
+        theory.add_atom(2, 1, 2, -1);
+        theory.add_atom(3, 2, 0, -1);
+        let mut solver = Default::new_from_vec(vec![vec![1], vec![2], vec![3]]);
+        solver.set_theory(Box::new(theory));
+        let s = format!("{:?}", solver.run());
+        let expect = expect!["UnsatCore([])"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn implied_atom() {
+        // x0 <= x1 <= x2 forces x0 <= x2, which in turn forces 5
+        let mut theory = DifferenceLogic::new();
+        theory.add_atom(1, 0, 1, 0);
+        theory.add_atom(2, 1, 2, 0);
+        theory.add_atom(3, 0, 2, 0);
+        let mut solver = Default::new_from_vec(vec![vec![1], vec![2], vec![-3, 5], vec![3, 4, -5]]);
+        solver.set_theory(Box::new(theory));
+        let s = format!("{:?}", solver.run());
+        let expect = expect!["Sat({1: true, 2: true, 3: true, 4: true, 5: true})"];
+        expect.assert_eq(&s);
+    }
+
+    fn schedule(deadline: i64) -> (SatResult, Option<Vec<i64>>) {
+        // x0 is the origin, task a (x1) takes 2 units and task b (x2) takes 3
+        let atoms = [
+            (1, 0, 1, 0),
+            (2, 0, 2, 0),
+            (3, 1, 0, deadline - 2),
+            (4, 2, 0, deadline - 3),
+            (5, 1, 2, -2),
+            (6, 2, 1, -3),
+        ];
+        let mut theory = DifferenceLogic::new();
+        for (var, x, y, c) in atoms {
+            theory.add_atom(var, x, y, c);
+        }
+        let mut solver =
+            Default::new_from_vec(vec![vec![1], vec![2], vec![3], vec![4], vec![5, 6]]);
+        solver.set_theory(Box::new(theory));
+        let result = solver.run();
+        let values = match &result {
+            SatResult::Sat(model) => {
+                let mut theory = DifferenceLogic::new();
+                for (var, x, y, c) in atoms {
+                    theory.add_atom(var, x, y, c);
+                }
+                theory
+                    .values(model)
+                    .map(|values| values.iter().map(|value| value - values[0]).collect())
+            }
+            SatResult::UnsatCore(_) | SatResult::Unknown => None,
+        };
+        (result, values)
+    }
+
+    #[test]
+    fn scheduling() {
+        let s = format!("{:?}", schedule(4));
+        let expect = expect!["(UnsatCore([]), None)"];
+        expect.assert_eq(&s);
+        let (_, values) = schedule(5);
+        let values = values.unwrap();
+        assert!(values[1] >= 0 && values[1] + 2 <= 5);
+        assert!(values[2] >= 0 && values[2] + 3 <= 5);
+        assert!(values[1] + 2 <= values[2] || values[2] + 3 <= values[1]);
+    }
+}
@@ -0,0 +1,96 @@
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pror::cdcl::Default;
+    use pror::encodings::*;
+    use pror::sat::SatResult;
+
+    fn factor(n: u64, bits: usize) -> Option<(u64, u64)> {
+        let encoding = factoring(n, bits);
+        match Default::solve(encoding.clauses.clone()) {
+            SatResult::Sat(model) => Some(encoding.factors(&model)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn factors_composites() {
+        for (n, bits) in [
+            (4, 2),
+            (15, 3),
+            (143, 4),
+            (1111 * 1111, 11),
+            (1001 * 997, 10),
+        ] {
+            let (x, y) = factor(n, bits).unwrap();
+            assert_eq!(x * y, n);
+            assert!(x > 1 && y > 1);
+        }
+    }
+
+    #[test]
+    fn primes_and_out_of_range_are_unsat() {
+        let results = [(13, 4), (1, 4), (9973, 7), (143, 3), (6, 1), (1 << 20, 4)]
+            .map(|(n, bits)| factor(n, bits));
+        expect!["[None, None, None, None, None, None]"].assert_eq(&format!("{:?}", results));
+    }
+
+    #[test]
+    fn size() {
+        let encoding = factoring(1234321, 11);
+        let num_vars = encoding.clauses.iter().flatten().map(|lit| lit.abs()).max();
+        let s = format!("{:?} {}", num_vars, encoding.clauses.len());
+        expect!["Some(363) 1850"].assert_eq(&s);
+    }
+
+    fn place_queens(n: usize) -> Option<String> {
+        let queens = n_queens(n);
+        let mut solver = Default::new_from_vec(queens.clauses.clone());
+        for group in &queens.at_most_ones {
+            solver.add_at_most_one(group.clone());
+        }
+        match solver.run() {
+            SatResult::Sat(model) => Some(queens.board(&model)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn queens() {
+        expect![[r#"
+            . Q . .
+            . . . Q
+            Q . . .
+            . . Q ."#]]
+        .assert_eq(&place_queens(4).unwrap());
+        assert_eq!(place_queens(1).as_deref(), Some("Q"));
+        assert_eq!(place_queens(2), None);
+        assert_eq!(place_queens(3), None);
+
+        let board = place_queens(8).unwrap();
+        let squares = board
+            .lines()
+            .enumerate()
+            .flat_map(|(row, line)| {
+                line.split(' ')
+                    .enumerate()
+                    .filter(|&(_, square)| square == "Q")
+                    .map(move |(col, _)| (row as isize, col as isize))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(squares.len(), 8);
+        for (idx, &(r1, c1)) in squares.iter().enumerate() {
+            for &(r2, c2) in &squares[idx + 1..] {
+                assert!(r1 != r2 && c1 != c2 && (r1 - r2).abs() != (c1 - c2).abs());
+            }
+        }
+    }
+
+    #[test]
+    fn queens_solutions() {
+        let counts = (1..=6)
+            .map(|n| Default::new_from_vec(n_queens(n).to_cnf()).models().count())
+            .collect::<Vec<_>>();
+        expect!["[1, 0, 0, 2, 10, 4]"].assert_eq(&format!("{:?}", counts));
+    }
+}
@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pror::cdcl::Default;
+    use pror::generate::*;
+    use pror::sat::SatResult;
+
+    #[test]
+    fn pigeonhole_is_unsat() {
+        expect![
+            "[[1, 2], [3, 4], [5, 6], [-1, -3], [-1, -5], [-3, -5], [-2, -4], [-2, -6], [-4, -6]]"
+        ]
+        .assert_eq(&format!("{:?}", pigeonhole(2)));
+        for holes in 1..=4 {
+            let formula = pigeonhole(holes);
+            assert_eq!(formula.len(), holes + 1 + holes * holes * (holes + 1) / 2);
+            assert!(matches!(Default::solve(formula), SatResult::UnsatCore(_)));
+        }
+    }
+
+    #[test]
+    fn tseitin_parity_is_unsat() {
+        for (n, seed) in [(3, 0), (3, 1), (4, 2)] {
+            let formula = tseitin_parity(n, seed);
+            assert_eq!(formula.len(), n * n * 8);
+            let vars = formula.iter().flatten().map(|lit| lit.abs()).max();
+            assert_eq!(vars, Some(2 * (n * n) as isize));
+            assert!(matches!(Default::solve(formula), SatResult::UnsatCore(_)));
+        }
+    }
+
+    #[test]
+    fn tseitin_parity_needs_odd_charge() {
+        // dropping a vertex's constraints frees its charge, leaving a
+        // satisfiable formula
+        let formula = tseitin_parity(4, 7);
+        let freed = formula[8..].to_vec();
+        assert!(matches!(Default::solve(freed), SatResult::Sat(_)));
+    }
+}
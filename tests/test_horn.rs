@@ -0,0 +1,106 @@
+use pror::cdcl::Default;
+use pror::horn;
+use pror::sat::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    fn satisfies(formula: &[Vec<isize>], model: &std::collections::BTreeMap<usize, bool>) -> bool {
+        formula.iter().all(|clause| {
+            clause
+                .iter()
+                .any(|&lit| model.get(&lit.unsigned_abs()) == Some(&(lit > 0)))
+        })
+    }
+
+    #[test]
+    fn horn_minimal_model() {
+        // 1, 1 -> 2, 1 & 2 -> 3, 4 -> 5
+        let formula = vec![
+            vec![1],
+            vec![-1, 2],
+            vec![-1, -2, 3],
+            vec![-4, 5],
+            vec![-3, -5],
+        ];
+        assert!(horn::is_horn(&formula));
+        let s = format!("{:?}", horn::solve_horn(&formula));
+        let expect = expect!["Some({1: true, 2: true, 3: true, 4: false, 5: false})"];
+        expect.assert_eq(&s);
+        let mut unsat = formula.clone();
+        unsat.push(vec![5]);
+        assert_eq!(horn::solve_horn(&unsat), None);
+    }
+
+    #[test]
+    fn renamable_horn() {
+        let formula = vec![vec![1, 2, 3], vec![-1, 2], vec![-3, 4]];
+        assert!(!horn::is_horn(&formula));
+        let s = format!("{:?}", horn::horn_renaming(&formula));
+        let expect = expect!["Some({1, 2, 3, 4})"];
+        expect.assert_eq(&s);
+        assert!(horn::horn_renaming(&[
+            vec![1, 2],
+            vec![-1, -2],
+            vec![1, -2],
+            vec![2, 3, 4],
+            vec![-3, -4]
+        ])
+        .is_none());
+        match horn::try_solve(&formula) {
+            Some(SatResult::Sat(model)) => assert!(satisfies(&formula, &model)),
+            res => panic!("expected a model, got {:?}", res),
+        }
+    }
+
+    #[test]
+    fn fast_path_agrees_with_search() {
+        let mut horn_solves = 0;
+        for seed in 0..40 {
+            // Horn clauses over renamed variables, plus the odd arbitrary one
+            let formula = pror::generate::random_k_cnf(12, 30, 3, seed)
+                .into_iter()
+                .enumerate()
+                .map(|(idx, clause)| {
+                    let horn = clause
+                        .iter()
+                        .enumerate()
+                        .map(|(i, &lit)| if i == 0 || idx == 7 { lit } else { -lit.abs() })
+                        .collect::<Vec<_>>();
+                    horn.into_iter()
+                        .map(|lit| {
+                            if lit.unsigned_abs() as u64 % 3 == seed % 3 {
+                                -lit
+                            } else {
+                                lit
+                            }
+                        })
+                        .collect()
+                })
+                .collect::<Vec<Vec<isize>>>();
+            let mut solver = Default::new_from_vec(formula.clone());
+            let fast = solver.run();
+            let mut searching = Default::new_from_vec(formula.clone());
+            searching.set_fast_paths(false);
+            let slow = searching.run();
+            assert_eq!(
+                matches!(fast, SatResult::Sat(_)),
+                matches!(slow, SatResult::Sat(_)),
+                "seed {}",
+                seed
+            );
+            if let SatResult::Sat(model) = fast {
+                assert!(satisfies(&formula, &model), "seed {}", seed);
+            }
+            assert_eq!(searching.stats().horn_solves, 0);
+            if solver.stats().horn_solves == 1 {
+                assert_eq!(solver.stats().decisions, 0);
+                horn_solves += 1;
+            }
+        }
+        assert!(horn_solves > 0 && horn_solves < 40, "{}", horn_solves);
+    }
+}
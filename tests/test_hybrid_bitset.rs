@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests {
+    use pror::bitset::BitSetT;
+    use pror::hybrid_bitset::HybridBitSet;
+
+    #[test]
+    fn sparse_over_many_chunks() {
+        let mut a = HybridBitSet::create();
+        for bit in [3, 70_000, 1 << 30, 5] {
+            a.set(bit);
+        }
+        assert_eq!(a.iter().collect::<Vec<_>>(), vec![3, 5, 70_000, 1 << 30]);
+        assert_eq!(a.capacity(), (1 << 30) + 1);
+        assert_eq!(a.first_set_ge(6), Some(70_000));
+        assert_eq!(a.first_set_le(69_999), Some(5));
+        assert_eq!(a.nth(3), Some(1 << 30));
+        a.clear(70_000);
+        assert_eq!(a.first_set_ge(6), Some(1 << 30));
+        assert_eq!(a.count(), 3);
+    }
+
+    #[test]
+    fn dense_chunks() {
+        let mut a = HybridBitSet::create();
+        a.set_between(10, 100_010);
+        assert_eq!(a.count(), 100_000);
+        assert_eq!(a.first_set(), Some(10));
+        assert_eq!(a.first_unset(), Some(0));
+        assert_eq!(a.first_unset_ge(10), Some(100_010));
+        assert_eq!(a.last_set(), Some(100_009));
+        for bit in (10..100_010).step_by(2) {
+            a.clear(bit);
+        }
+        assert_eq!(a.count(), 50_000);
+        assert_eq!(a.first_unset_ge(10), Some(10));
+        assert_eq!(a.nth(1), Some(13));
+        assert_eq!(a.iter().step_by(25_000).collect::<Vec<_>>(), vec![11, 50_011]);
+
+        let mut b = HybridBitSet::create();
+        for bit in (0..200_000).step_by(3) {
+            b.set(bit);
+        }
+        let mut union = a.clone();
+        union.union_with(&b);
+        let mut intersection = a.clone();
+        intersection.intersect_with(&b);
+        let mut difference = a.clone();
+        difference.difference_with(&b);
+        // odd multiples of 3 between 10 and 100_010
+        assert_eq!(intersection.count(), 16_666);
+        assert_eq!(union.count(), a.count() + b.count() - intersection.count());
+        assert_eq!(difference.count(), a.count() - intersection.count());
+        assert!(intersection.iter().all(|bit| bit % 6 == 3));
+    }
+}
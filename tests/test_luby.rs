@@ -0,0 +1,52 @@
+#[cfg(test)]
+mod tests {
+    use pror::cdcl::Default;
+    use pror::luby::*;
+    use pror::sat::SatResult;
+
+    use expect_test::expect;
+
+    #[test]
+    fn luby_terms() {
+        let terms = (1..=15).map(luby_term).collect::<Vec<_>>();
+        assert_eq!(terms, [1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+        // deep into the sequence without recursing
+        assert_eq!(luby_term((1 << 62) - 1), 1 << 61);
+        assert_eq!(luby_term(1 << 62), 1);
+    }
+
+    #[test]
+    fn luby_schedule() {
+        let mut luby = Luby::new(32);
+        let mut values = vec![luby.value()];
+        values.extend(luby.by_ref().take(6));
+        luby.reset();
+        values.push(luby.value());
+        let s = format!("{:?}", values);
+        let expect = expect!["[32, 32, 64, 32, 32, 64, 128, 32]"];
+        expect.assert_eq(&s);
+
+        let luby = Luby::with_factor(10, 1.5, Some(30));
+        let mut values = vec![luby.value()];
+        values.extend(luby.take(14));
+        let s = format!("{:?}", values);
+        let expect = expect!["[10, 10, 15, 10, 10, 15, 22, 10, 10, 15, 10, 10, 15, 22, 30]"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn restart_schedule() {
+        let clauses = pror::generate::random_k_cnf(60, 255, 3, 4);
+        let mut solver = Default::new_from_vec(clauses.clone());
+        solver.set_restart_schedule(Box::new(Luby::with_factor(1, 2.0, Some(1))));
+        let res = solver.run();
+        let stats = solver.stats();
+        assert_eq!(stats.restarts, stats.conflicts);
+        let mut solver = Default::new_from_vec(clauses);
+        let expected = solver.run();
+        assert_eq!(
+            matches!(res, SatResult::Sat(_)),
+            matches!(expected, SatResult::Sat(_))
+        );
+    }
+}
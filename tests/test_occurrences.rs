@@ -0,0 +1,64 @@
+#[cfg(test)]
+mod tests {
+    use pror::cdcl::{ClauseOrigin, Default};
+    use pror::occurrences::*;
+    use pror::sat::*;
+
+    #[test]
+    fn add_and_remove() {
+        let mut occurrences = OccurrenceLists::new();
+        let lits = |ints: &[isize]| {
+            ints.iter()
+                .map(|&lit| Literal::from(lit))
+                .collect::<Vec<_>>()
+        };
+        occurrences.add(0, lits(&[1, -2]));
+        occurrences.add(1, lits(&[1, 3]));
+        occurrences.add(2, lits(&[-2, 3, 1]));
+        assert_eq!(occurrences.get(Literal::from(1)), [0, 1, 2]);
+        assert_eq!(occurrences.get(Literal::from(-1)), [] as [usize; 0]);
+        assert_eq!(occurrences.get(Literal::from(-7)), [] as [usize; 0]);
+        assert_eq!(occurrences.rarest(lits(&[1, 3])), Some(Literal::from(3)));
+        occurrences.remove(0, lits(&[-2, 1]));
+        assert_eq!(occurrences.get(Literal::from(1)), [2, 1]);
+        assert_eq!(occurrences.count(Literal::from(-2)), 1);
+        assert_eq!(occurrences.len(), 5);
+        occurrences.remove(1, lits(&[1, 3]));
+        occurrences.remove(2, lits(&[1, -2, 3]));
+        assert!(occurrences.is_empty());
+    }
+
+    #[test]
+    fn tracked_through_a_solve() {
+        let clauses = pror::generate::random_k_cnf(50, 215, 3, 8);
+        let mut state = Default::new_from_vec(clauses);
+        state.track_occurrences();
+        state.set_reduce_schedule(10, 1.0);
+        state.add_clause([4, 4, -9]);
+        state.eliminate_asymmetric_tautologies();
+        state.run();
+        let occurrences = state.occurrence_lists().unwrap();
+        let clauses = state.clauses_iter().collect::<Vec<_>>();
+        assert!(clauses
+            .iter()
+            .any(|info| info.origin == ClauseOrigin::Learned));
+        assert_eq!(
+            occurrences.len(),
+            clauses
+                .iter()
+                .map(|info| info.literals.len())
+                .sum::<usize>()
+        );
+        for var in 1..=50 {
+            for lit in [Literal::new(var, true), Literal::new(var, false)] {
+                let count = clauses
+                    .iter()
+                    .filter(|info| info.literals.contains(&lit))
+                    .count();
+                assert_eq!(occurrences.count(lit), count, "{}", lit.to_string());
+            }
+        }
+        state.stop_tracking_occurrences();
+        assert!(state.occurrence_lists().is_none());
+    }
+}
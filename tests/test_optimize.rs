@@ -0,0 +1,38 @@
+use pror::optimize::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    #[test]
+    fn vertex_cover_of_triangle() {
+        let formula = vec![vec![1, 2], vec![2, 3], vec![1, 3]];
+        let result = optimize(formula, &[1, 2, 3]);
+        let s = format!("{:?}", result);
+        let expect = expect!["Optimal { cost: 2, model: {1: true, 2: true, 3: false} }"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn negative_objective_literals() {
+        // maximise the number of true variables subject to pairwise exclusion
+        let formula = vec![vec![-1, -2], vec![-2, -3], vec![-1, -4]];
+        let result = optimize(formula, &[-1, -2, -3, -4]);
+        let s = format!("{:?}", result);
+        let expect = expect!["Optimal { cost: 2, model: {1: false, 2: false, 3: true, 4: true} }"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn zero_cost_and_unsat() {
+        let free = optimize(vec![vec![1, -2]], &[1, 2]);
+        let unsat = optimize(vec![vec![1], vec![-1]], &[1]);
+        let s = format!("{:?}\n{:?}", free, unsat);
+        let expect = expect![[r#"
+            Optimal { cost: 0, model: {1: false, 2: false} }
+            Unsat"#]];
+        expect.assert_eq(&s);
+    }
+}
@@ -0,0 +1,134 @@
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pror::cdcl::Default;
+    use pror::plan::*;
+    use pror::sat::SatResult;
+    use std::collections::BTreeMap;
+
+    /// Blocks world over `blocks`, from `init` to `goal`, each a list of
+    /// facts like `"on c a"`, `"table a"` or `"clear b"`.
+    fn blocks_world(blocks: &[&str], init: &[&str], goal: &[&str]) -> Problem {
+        let mut fluents = BTreeMap::new();
+        let mut fluent = |name: String| {
+            let next = fluents.len();
+            *fluents.entry(name).or_insert(next)
+        };
+        let mut actions = vec![];
+        let mut action = |name: String, pre: Vec<String>, add: Vec<String>, del: Vec<String>| {
+            let mut ids = |names: Vec<String>| names.into_iter().map(&mut fluent).collect();
+            let (pre, add, del) = (ids(pre), ids(add), ids(del));
+            actions.push(Action {
+                name,
+                pre,
+                add,
+                del,
+            });
+        };
+        let on = |x: &str, y: &str| format!("on {} {}", x, y);
+        let table = |x: &str| format!("table {}", x);
+        let clear = |x: &str| format!("clear {}", x);
+        for &x in blocks {
+            for &y in blocks.iter().filter(|&&y| y != x) {
+                action(
+                    format!("{} from {} to table", x, y),
+                    vec![on(x, y), clear(x)],
+                    vec![table(x), clear(y)],
+                    vec![on(x, y)],
+                );
+                action(
+                    format!("{} from table to {}", x, y),
+                    vec![table(x), clear(x), clear(y)],
+                    vec![on(x, y)],
+                    vec![table(x), clear(y)],
+                );
+                for &z in blocks.iter().filter(|&&z| z != x && z != y) {
+                    action(
+                        format!("{} from {} to {}", x, y, z),
+                        vec![on(x, y), clear(x), clear(z)],
+                        vec![on(x, z), clear(y)],
+                        vec![on(x, y), clear(z)],
+                    );
+                }
+            }
+        }
+        let init = init.iter().map(|name| fluent(name.to_string())).collect();
+        let goal = goal.iter().map(|name| fluent(name.to_string())).collect();
+        Problem {
+            num_fluents: fluents.len(),
+            actions,
+            init,
+            goal,
+        }
+    }
+
+    fn sussman() -> Problem {
+        blocks_world(
+            &["a", "b", "c"],
+            &["on c a", "table a", "table b", "clear c", "clear b"],
+            &["on a b", "on b c"],
+        )
+    }
+
+    fn names<'a>(problem: &'a Problem, plan: &Plan) -> Vec<Vec<&'a str>> {
+        plan.iter()
+            .map(|step| {
+                step.iter()
+                    .map(|&idx| problem.actions[idx].name.as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn sussman_anomaly() {
+        let problem = sussman();
+        assert_eq!(plan(&problem, 2), PlanResult::NotFound);
+        let PlanResult::Found(found) = plan(&problem, 10) else {
+            panic!("the anomaly has a plan");
+        };
+        expect![[r#"[["c from a to table"], ["b from table to c"], ["a from table to b"]]"#]]
+            .assert_eq(&format!("{:?}", names(&problem, &found)));
+        let end = problem.simulate(&found).unwrap();
+        assert!(problem.goal.iter().all(|fluent| end.contains(fluent)));
+    }
+
+    #[test]
+    fn fixed_horizon() {
+        let problem = sussman();
+        for horizon in 0..=4 {
+            let result = Default::solve(problem.encode(horizon));
+            match result {
+                SatResult::Sat(model) => {
+                    assert!(horizon >= 3);
+                    let found = problem.extract(&model, horizon);
+                    let end = problem.simulate(&found).unwrap();
+                    assert!(problem.goal.iter().all(|fluent| end.contains(fluent)));
+                }
+                _ => assert!(horizon < 3),
+            }
+        }
+    }
+
+    #[test]
+    fn parallel_steps() {
+        // two towers taken apart at once
+        let problem = blocks_world(
+            &["a", "b", "c", "d"],
+            &[
+                "on a b", "on c d", "table b", "table d", "clear a", "clear c",
+            ],
+            &["table a", "table c"],
+        );
+        let PlanResult::Found(found) = plan(&problem, 5) else {
+            panic!("the towers can be taken apart");
+        };
+        expect![[r#"[["a from b to table", "c from d to table"]]"#]]
+            .assert_eq(&format!("{:?}", names(&problem, &found)));
+        let stuck = problem
+            .actions
+            .iter()
+            .position(|action| action.name == "b from a to table");
+        assert_eq!(problem.simulate(&[vec![stuck.unwrap()]]), None);
+    }
+}
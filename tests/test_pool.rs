@@ -0,0 +1,82 @@
+#[cfg(test)]
+mod tests {
+    use pror::cdcl::Default;
+    use pror::pool::*;
+    use pror::sat::SatResult;
+
+    #[test]
+    fn bounded_pool() {
+        let mut pool = Pool::new();
+        for i in 0..5 {
+            pool.release(vec![i]);
+        }
+        pool.set_max_retained(Some(3));
+        assert_eq!(pool.len(), 3);
+        pool.release(vec![5]);
+        assert_eq!(pool.len(), 3);
+        assert_eq!(pool.acquire(Vec::new), vec![2]);
+        pool.shrink_to(1);
+        assert_eq!(pool.acquire(Vec::new), vec![0]);
+        assert_eq!(pool.acquire(Vec::new), Vec::<i32>::new());
+        let expected = PoolStats {
+            hits: 2,
+            misses: 1,
+            dropped: 1,
+        };
+        assert_eq!(pool.stats(), expected);
+    }
+
+    #[test]
+    fn acquire_reset() {
+        use pror::bitset::BitSetT;
+        use pror::fixed_bitset::BitSet;
+        let mut pool = Pool::new();
+        let mut bs = BitSet::create();
+        bs.set(70);
+        pool.release(bs);
+        let bs = pool.acquire_reset(BitSet::create);
+        assert_eq!(bs.first_set(), None);
+        // the words are kept
+        assert_eq!(bs.capacity(), 128);
+
+        let mut pool = Pool::new();
+        pool.release(vec![1, 2]);
+        assert!(pool.acquire_reset(Vec::new).is_empty());
+        assert_eq!(pool.acquire_reset(|| vec![3]), vec![3]);
+    }
+
+    #[test]
+    fn sync_pool_across_threads() {
+        use std::sync::Arc;
+        let pool = Arc::new(SyncPool::new());
+        pool.set_max_retained(Some(8));
+        let handles = (0..4)
+            .map(|_| {
+                let pool = Arc::clone(&pool);
+                std::thread::spawn(move || {
+                    for i in 0..100 {
+                        let mut buf = pool.acquire_reset(Vec::new);
+                        assert!(buf.is_empty());
+                        buf.push(i);
+                        pool.release(buf);
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        let stats = pool.stats();
+        assert_eq!(stats.hits + stats.misses, 400);
+        assert!(stats.misses <= 4);
+        assert!(pool.len() <= 4);
+    }
+
+    #[test]
+    fn solver_pool_limit() {
+        let mut solver = Default::new_from_vec(vec![vec![1, 2], vec![-1, 2], vec![-2, 3]]);
+        solver.set_bitset_pool_limit(Some(0));
+        assert!(matches!(solver.run(), SatResult::Sat(_)));
+        assert!(solver.bitset_pool_stats().misses > 0);
+    }
+}
@@ -0,0 +1,86 @@
+use pror::qbf::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    #[test]
+    fn parse_prefix_and_matrix() {
+        let qbf = read_string(
+            "c example\n\
+             p cnf 3 2\n\
+             a 1 0\n\
+             a 2 0\n\
+             e 3 0\n\
+             1 -3 0\n\
+             -2 3 0\n",
+        );
+        let s = format!("{:?} {:?} {}", qbf.prefix, qbf.clauses, qbf.max_var);
+        let expect = expect!["[(Forall, [1, 2]), (Exists, [3])] [[1, -3], [-2, 3]] 3"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn free_variables_are_outermost_existential() {
+        let qbf = read_string("p cnf 3 1\na 2 0\ne 3 0\n1 2 3 0\n");
+        let s = format!("{:?}", qbf.normalized_prefix());
+        let expect = expect!["[(Exists, [1]), (Forall, [2]), (Exists, [3])]"];
+        expect.assert_eq(&s);
+        assert!(solve(&qbf).is_none());
+    }
+
+    #[test]
+    fn forall_exists_true() {
+        // every x has a y equal to it
+        let qbf = read_string("p cnf 2 2\na 1 0\ne 2 0\n1 -2 0\n-1 2 0\n");
+        let s = format!("{:?}", solve(&qbf));
+        let expect = expect!["Some(True({}))"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn forall_exists_false() {
+        // no y works for x1 = x2 = false
+        let qbf = read_string("p cnf 3 3\na 1 2 0\ne 3 0\n1 2 3 0\n1 2 -3 0\n-1 3 0\n");
+        let s = format!("{:?}", solve(&qbf));
+        let expect = expect!["Some(False({1: false, 2: false}))"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn exists_forall_true() {
+        // x1 = true satisfies the matrix whatever y is
+        let qbf = read_string("p cnf 3 2\ne 1 2 0\na 3 0\n1 3 0\n1 -3 2 -2 0\n");
+        let s = format!("{:?}", solve(&qbf));
+        let expect = expect!["Some(True({1: true, 2: false}))"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn exists_forall_false() {
+        let qbf = read_string("p cnf 2 2\ne 1 0\na 2 0\n1 2 0\n-1 -2 0\n");
+        let s = format!("{:?}", solve(&qbf));
+        let expect = expect!["Some(False({}))"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn single_block() {
+        let sat = read_string("p cnf 2 2\n1 2 0\n-1 0\n");
+        let valid = read_string("p cnf 1 1\na 1 0\n1 -1 0\n");
+        let invalid = read_string("p cnf 2 1\na 1 2 0\n1 2 0\n");
+        let s = format!(
+            "{:?}\n{:?}\n{:?}",
+            solve(&sat),
+            solve(&valid),
+            solve(&invalid)
+        );
+        let expect = expect![[r#"
+            Some(True({1: false, 2: true}))
+            Some(True({}))
+            Some(False({1: false, 2: false}))"#]];
+        expect.assert_eq(&s);
+    }
+}
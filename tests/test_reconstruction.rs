@@ -0,0 +1,56 @@
+use pror::reconstruction::Reconstruction;
+use pror::sat::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    use pror::cdcl::Default;
+
+    #[test]
+    fn variable_elimination() {
+        // eliminating 3 from (3 1)(-3 2)(-3 -1) leaves (1 2)(-1 1), i.e. (1 2)
+        let original = vec![vec![3, 1], vec![-3, 2], vec![-3, -1]];
+        let mut reconstruction = Reconstruction::new();
+        for clause in &original {
+            let witness = Literal::from(clause[0]);
+            reconstruction.push(witness, clause.iter().map(|&lit| lit.into()).collect());
+        }
+        let mut models = vec![];
+        for simplified in [vec![vec![-1], vec![2]], vec![vec![1], vec![2]]] {
+            let mut model = match Default::solve(simplified) {
+                SatResult::Sat(model) => model,
+                SatResult::UnsatCore(_) | SatResult::Unknown => unreachable!(),
+            };
+            reconstruction.extend(&mut model);
+            assert!(original.iter().all(|clause| clause
+                .iter()
+                .any(|&lit| model.get(&lit.unsigned_abs()) == Some(&(lit > 0)))));
+            models.push(model);
+        }
+        let s = format!("{:?}", models);
+        let expect = expect!["[{1: false, 2: true, 3: true}, {1: true, 2: true, 3: false}]"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn equivalent_literals() {
+        // 2 was substituted by 1
+        let mut reconstruction = Reconstruction::new();
+        reconstruction.push(
+            Literal::new(2, true),
+            vec![Literal::new(2, true), Literal::new(1, false)],
+        );
+        reconstruction.push(
+            Literal::new(2, false),
+            vec![Literal::new(2, false), Literal::new(1, true)],
+        );
+        let mut model = [(1, true)].into_iter().collect();
+        reconstruction.extend(&mut model);
+        let s = format!("{:?} {}", model, reconstruction.len());
+        let expect = expect!["{1: true, 2: true} 2"];
+        expect.assert_eq(&s);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pror::cdcl::Default;
+    use pror::reduction::*;
+    use pror::sat::SatResult;
+    use pror::solver::*;
+
+    fn candidates() -> Vec<ReductionCandidate> {
+        [
+            (0.5, 2, 3),
+            (3.0, 5, 6),
+            (1.0, 4, 9),
+            (2.0, 2, 4),
+            (0.1, 7, 7),
+        ]
+        .into_iter()
+        .map(|(activity, lbd, len)| ReductionCandidate { activity, lbd, len })
+        .collect()
+    }
+
+    #[test]
+    fn policies_select() {
+        let candidates = candidates();
+        assert_eq!(ByActivity::new(0.5).select(&candidates), [4, 0]);
+        assert_eq!(ByActivity::new(0.0).select(&candidates), [4, 0, 2, 3, 1]);
+        assert_eq!(
+            ByActivity::new(1.0).select(&candidates),
+            Vec::<usize>::new()
+        );
+        assert_eq!(ByLbd::new(0.5).select(&candidates), [4, 1]);
+        assert_eq!(BySize::new(0.4).select(&candidates), [2, 4, 1]);
+        // the two glue clauses are never candidates
+        assert_eq!(Hybrid::new(0.5, 2).select(&candidates), [4]);
+        assert_eq!(Hybrid::new(0.0, 2).select(&candidates), [4, 2, 1]);
+    }
+
+    #[test]
+    fn solve_with_each_policy() {
+        let clauses = pror::generate::random_k_cnf(80, 350, 3, 6);
+        let mut expected = Default::new_from_vec(clauses.clone());
+        let expected = matches!(expected.run(), SatResult::Sat(_));
+        for kind in ["activity", "lbd", "size", "hybrid"] {
+            for bump_reasons in [false, true] {
+                let mut solver = build_solver(SolverOptions {
+                    reduction: ReductionOptions {
+                        kind: kind.parse().unwrap(),
+                        keep_fraction: 0.3,
+                        first: 20,
+                        growth: 1.0,
+                        bump_reasons,
+                    },
+                    ..SolverOptions::default()
+                });
+                for clause in &clauses {
+                    solver.add_clause(clause.clone());
+                }
+                let sat = matches!(solver.solve(), SatResult::Sat(_));
+                assert_eq!(sat, expected, "{} {}", kind, bump_reasons);
+            }
+        }
+        assert!("never".parse::<ReductionKind>().is_err());
+    }
+
+    #[test]
+    fn bump_reasons() {
+        let mut runs = vec![];
+        for bump in [false, true] {
+            let mut solver = Default::new_from_vec(pror::generate::pigeonhole(6));
+            solver.set_reduce_schedule(20, 1.0);
+            solver.set_bump_reasons(bump);
+            let reduction = solver.describe().reduction;
+            assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+            runs.push((reduction, solver.stats().conflicts));
+        }
+        expect![[r#"[("activity(0.5)", 3508), ("activity(0.5) bumping reasons", 2584)]"#]]
+            .assert_eq(&format!("{:?}", runs));
+    }
+}
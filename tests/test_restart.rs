@@ -0,0 +1,66 @@
+#[cfg(test)]
+mod tests {
+    use pror::luby::Luby;
+    use pror::restart::*;
+    use pror::sat::SatResult;
+    use pror::solver::*;
+
+    use expect_test::expect;
+
+    fn limits(schedule: &mut dyn RestartSchedule, n: usize) -> Vec<u64> {
+        (0..n)
+            .map(|_| {
+                let limit = schedule.limit();
+                schedule.restarted();
+                limit
+            })
+            .collect()
+    }
+
+    #[test]
+    fn schedules() {
+        let mut luby = Luby::new(2);
+        let mut geometric = Geometric::new(100, 1.5);
+        let mut inner_outer = InnerOuter::new(10, 2.0);
+        let s = format!(
+            "{:?}\n{:?}\n{:?}",
+            limits(&mut luby, 7),
+            limits(&mut geometric, 5),
+            limits(&mut inner_outer, 10)
+        );
+        let expect = expect![[r#"
+            [2, 2, 4, 2, 2, 4, 8]
+            [100, 150, 225, 337, 506]
+            [10, 10, 20, 10, 20, 40, 10, 20, 40, 80]"#]];
+        expect.assert_eq(&s);
+
+        inner_outer.reset();
+        geometric.reset();
+        assert_eq!(limits(&mut inner_outer, 3), [10, 10, 20]);
+        assert_eq!(limits(&mut geometric, 1), [100]);
+    }
+
+    #[test]
+    fn solve_with_each_schedule() {
+        let clauses = pror::generate::random_k_cnf(60, 270, 3, 2);
+        let mut results = vec![];
+        for kind in ["luby", "geometric", "inner-outer"] {
+            let mut solver = build_solver(SolverOptions {
+                restarts: RestartOptions {
+                    kind: kind.parse().unwrap(),
+                    base: 4,
+                    factor: 1.5,
+                    cap: None,
+                },
+                ..SolverOptions::default()
+            });
+            for clause in &clauses {
+                solver.add_clause(clause.clone());
+            }
+            results.push(matches!(solver.solve(), SatResult::Sat(_)));
+            assert!(solver.stats().restarts > 0);
+        }
+        assert!(results.iter().all(|&sat| sat == results[0]));
+        assert!("sometimes".parse::<RestartKind>().is_err());
+    }
+}
@@ -0,0 +1,38 @@
+use pror::dimacs;
+use pror::sat::*;
+use pror::shuffle;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    use pror::cdcl::Default;
+
+    #[test]
+    fn shuffle_small() {
+        let formula = vec![vec![1, -2], vec![2, 5, -7], vec![-1]];
+        let shuffled = shuffle::shuffle(&formula, 3);
+        let s = format!("{:?}\n{:?}", shuffled.clauses, shuffled.renaming);
+        let expect = expect![[r#"
+            [[-3, 1], [-2, 3, 4], [-1]]
+            {1: 1, 2: 3, 5: 4, 7: 2}"#]];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn shuffled_model_maps_back() {
+        let formula = dimacs::read_example("sudoku");
+        let shuffled = shuffle::shuffle(&formula, 17);
+        match Default::solve(shuffled.clauses.clone()) {
+            SatResult::Sat(model) => {
+                let model = shuffled.original_model(&model);
+                assert!(formula.iter().all(|clause| clause
+                    .iter()
+                    .any(|&lit| model[&lit.unsigned_abs()] == (lit > 0))));
+            }
+            SatResult::UnsatCore(_) | SatResult::Unknown => panic!("sudoku should be satisfiable"),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use pror::sat::*;
+use pror::shared_string_writer::SharedStringWriter;
+use pror::solver::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    #[test]
+    fn every_config_through_the_trait() {
+        let mut results = vec![];
+        for heuristic in [Heuristic::Vsids, Heuristic::Random] {
+            for debug in [false, true] {
+                let mut solver = build_solver(SolverOptions {
+                    heuristic,
+                    debug,
+                    ..SolverOptions::default()
+                });
+                solver.set_writer(Box::new(SharedStringWriter::new()));
+                solver.add_clause(vec![1, 2]);
+                solver.add_clause(vec![-1, 2]);
+                let sat = matches!(solver.solve(), SatResult::Sat(model) if model[&2]);
+                let unsat = matches!(solver.run_with_assumptions(&[-2]), SatResult::UnsatCore(_));
+                results.push((heuristic, debug, sat, unsat));
+            }
+        }
+        let s = format!("{:?}", results);
+        let expect = expect!["[(Vsids, false, true, true), (Vsids, true, true, true), (Random, false, true, true), (Random, true, true, true)]"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn debug_writer() {
+        let mut solver = build_solver(SolverOptions {
+            heuristic: Heuristic::Vsids,
+            debug: true,
+            ..SolverOptions::default()
+        });
+        let writer = SharedStringWriter::new();
+        solver.set_writer(Box::new(writer.clone()));
+        solver.add_clause(vec![1]);
+        solver.solve();
+        assert!(!writer.borrow().is_empty());
+        assert_eq!(solver.stats().variables, 1);
+    }
+
+    #[test]
+    fn debug_writer_to_file() {
+        use pror::shared_string_writer::IoWriter;
+        let path = std::env::temp_dir().join(format!("pror_trace_{}.log", std::process::id()));
+        let mut solver = build_solver(SolverOptions {
+            heuristic: Heuristic::Vsids,
+            debug: true,
+            ..SolverOptions::default()
+        });
+        solver.set_writer(Box::new(IoWriter::create(&path).unwrap()));
+        solver.add_clause(vec![1]);
+        solver.solve();
+        drop(solver);
+        let trace = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!trace.is_empty());
+
+        let mut writer = IoWriter::new(vec![]);
+        std::fmt::Write::write_str(&mut writer, "c hello\n").unwrap();
+        assert_eq!(writer.into_inner(), b"c hello\n");
+    }
+
+    #[test]
+    fn solve_on_another_thread() {
+        let mut solver = build_solver(SolverOptions {
+            heuristic: Heuristic::Vsids,
+            debug: true,
+            ..SolverOptions::default()
+        });
+        let writer = SharedStringWriter::new();
+        solver.set_writer(Box::new(writer.clone()));
+        solver.add_clause(vec![1, -2]);
+        solver.add_clause(vec![2]);
+        let handle = std::thread::spawn(move || solver.solve());
+        let res = handle.join().unwrap();
+        assert!(matches!(res, SatResult::Sat(model) if model[&1]));
+        assert!(!writer.borrow().is_empty());
+    }
+
+    #[test]
+    fn solve_until_deadline() {
+        use std::time::{Duration, Instant};
+        let mut solver = build_solver(SolverOptions::default());
+        for clause in pror::generate::random_k_cnf(200, 860, 3, 9) {
+            solver.add_clause(clause);
+        }
+        assert!(solver.solve_until(Instant::now()).is_none());
+        let mut solver = build_solver(
+            "random"
+                .parse()
+                .map(|heuristic| SolverOptions {
+                    heuristic,
+                    debug: false,
+                    ..SolverOptions::default()
+                })
+                .unwrap(),
+        );
+        solver.add_clause(vec![1, 2]);
+        let res = solver.solve_until(Instant::now() + Duration::from_secs(60));
+        assert!(matches!(res, Some(SatResult::Sat(_))));
+        assert!("foo".parse::<Heuristic>().is_err());
+    }
+
+    #[test]
+    fn describe() {
+        let solver = build_solver(SolverOptions {
+            heuristic: Heuristic::Random,
+            restarts: RestartOptions {
+                kind: RestartKind::Geometric,
+                base: 100,
+                factor: 1.5,
+                cap: None,
+            },
+            ..SolverOptions::default()
+        });
+        let description = solver.describe();
+        assert_eq!(description.version, env!("CARGO_PKG_VERSION"));
+        // type names aren't stable across compilers
+        assert!(description.bitset.contains("BlockBitSet"));
+        assert!(description.score_order.contains("BTreeSet"));
+        let s = format!("{}", description)
+            .lines()
+            .filter(|line| !line.starts_with("c bitset") && !line.starts_with("c score_order"))
+            .collect::<Vec<_>>()
+            .join("\n")
+            .replace(description.version, "VERSION");
+        let expect = expect![[r#"
+            c version VERSION
+            c config pror::cdcl::RandomConfig
+            c heuristic random
+            c debug false
+            c restarts geometric(100, 1.5)
+            c reduction activity(0.5)
+            c vsids_decay 0.95
+            c clause_decay 0.75"#]];
+        expect.assert_eq(&s);
+        let json = build_solver(SolverOptions::default()).describe().to_json();
+        assert!(json.contains(r#""heuristic":"vsids","#));
+        assert!(json.contains(r#""restarts":"luby(32, 2)","#));
+    }
+
+    #[test]
+    fn auto() {
+        let horn = vec![vec![1], vec![-1, 2], vec![-2, -3, 4], vec![-4, -1, 5]];
+        let random = pror::generate::random_k_cnf(60, 256, 3, 3);
+        let structured = vec![vec![1, 2, 3, 4, 5], vec![-1, -2], vec![3, 4, -5, 6]];
+        let mut chosen = vec![];
+        for formula in [horn, random, structured] {
+            let options = SolverOptions::auto(&formula);
+            let mut solver = build_solver(options);
+            for clause in &formula {
+                solver.add_clause(clause.clone());
+            }
+            let description = solver.describe();
+            let sat = matches!(solver.solve(), SatResult::Sat(_));
+            chosen.push((
+                options.initial_phase,
+                description.restarts,
+                description.vsids_decay,
+                description.clause_decay,
+                sat,
+            ));
+        }
+        let expect = expect![[r#"
+            [
+                (
+                    Some(
+                        false,
+                    ),
+                    "luby(32, 2)",
+                    0.95,
+                    0.75,
+                    true,
+                ),
+                (
+                    None,
+                    "geometric(100, 1.5)",
+                    0.99,
+                    0.9,
+                    true,
+                ),
+                (
+                    None,
+                    "luby(32, 2)",
+                    0.95,
+                    0.75,
+                    true,
+                ),
+            ]
+        "#]];
+        expect.assert_debug_eq(&chosen);
+    }
+}
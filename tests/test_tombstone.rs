@@ -0,0 +1,45 @@
+#[cfg(test)]
+mod tests {
+    use pror::tombstone::*;
+
+    #[test]
+    fn gen_vec_reuses_slots() {
+        let mut v = ["a", "b", "c"].into_iter().collect::<GenVec<_>>();
+        assert_eq!(v.len(), 3);
+        let gen = v.generation(1);
+        assert_eq!(v.remove(1), Some("b"));
+        assert_eq!(v.remove(1), None);
+        assert_eq!(v.get(1), None);
+        assert!(!v.is_live(1, gen));
+        assert_eq!(v.len(), 2);
+
+        let idx = v.insert("d");
+        assert_eq!(idx, 1);
+        assert_eq!(v.get_checked(1, gen), None);
+        assert_eq!(v.get_checked(1, v.generation(1)), Some(&"d"));
+        assert_eq!(v.insert("e"), 3);
+        *v.get_mut_exn(0) = "z";
+        assert_eq!(
+            v.iter().collect::<Vec<_>>(),
+            vec![(0, &"z"), (1, &"d"), (2, &"c"), (3, &"e")]
+        );
+    }
+
+    #[test]
+    fn gen_vec_free_list_order() {
+        let mut v = GenVec::new();
+        for i in 0..5 {
+            v.insert(i);
+        }
+        v.remove(1);
+        v.remove(3);
+        assert_eq!(v.insert(10), 3);
+        assert_eq!(v.insert(11), 1);
+        assert_eq!(v.insert(12), 5);
+        assert_eq!(v.slots().len(), 6);
+        assert_eq!(
+            v.values().copied().collect::<Vec<_>>(),
+            vec![0, 11, 2, 10, 4, 12]
+        );
+    }
+}
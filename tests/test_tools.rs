@@ -0,0 +1,176 @@
+use pror::aiger;
+use pror::dimacs;
+use pror::drup;
+use pror::generate;
+use pror::sat::*;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    use pror::cdcl::Default;
+
+    #[test]
+    fn drup_check() {
+        let formula = vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]];
+        let proof = drup::read_string("1 0\nd 1 2 0\n0\n");
+        let s = format!("{:?}\n{:?}", proof, drup::check(&formula, &proof));
+        let expect = expect![[r#"
+            [Add([1]), Delete([1, 2]), Add([])]
+            Ok(())"#]];
+        expect.assert_eq(&s);
+
+        // 1 isn't rup once (1 2) is gone
+        let proof = drup::read_string("d 1 2 0\n1 0\n0\n");
+        let s = format!("{:?}", drup::check(&formula, &proof));
+        let expect = expect!["Err(NotRup(1))"];
+        expect.assert_eq(&s);
+
+        let proof = drup::read_string("1 0\n");
+        let s = format!("{:?}", drup::check(&formula, &proof));
+        let expect = expect!["Err(NoEmptyClause)"];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn aiger_to_cnf() {
+        // output = x ∧ ¬x
+        let contradiction = "aag 2 1 0 1 1\n2\n4\n4 2 3\n";
+        let aiger = aiger::read_string(contradiction).unwrap();
+        let cnf = aiger.to_cnf();
+        let s = format!("{:?}\n{:?}", cnf, Default::solve(cnf.clone()));
+        let expect = expect![[r#"
+            [[-3], [-2, 1], [-2, -1], [2, -1, 1], [2]]
+            UnsatCore([])"#]];
+        expect.assert_eq(&s);
+
+        // output = ¬(x ∧ y)
+        let nand = "aag 3 2 0 1 1\n2\n4\n7\n6 2 4\n";
+        let cnf = aiger::read_string(nand).unwrap().to_cnf();
+        let mut solver = Default::new_from_vec(cnf);
+        let s = format!("{:?}", solver.run_with_assumptions(&[1, 2]));
+        let expect = expect!["UnsatCore([Literal { value: 1 }, Literal { value: 2 }])"];
+        expect.assert_eq(&s);
+
+        let s = format!("{:?}", aiger::read_string("aag 1 0 1 0 0\n2 3\n"));
+        let expect = expect![[r#"Err("latches are not supported")"#]];
+        expect.assert_eq(&s);
+    }
+
+    #[test]
+    fn random_k_cnf() {
+        let formula = generate::random_k_cnf(5, 4, 3, 1);
+        let s = format!("{:?}", formula);
+        let expect = expect!["[[-1, -3, 2], [-3, 2, -1], [4, -1, -2], [3, 1, -5]]"];
+        expect.assert_eq(&s);
+        assert!(generate::random_k_cnf(20, 50, 3, 2)
+            .iter()
+            .all(|clause| clause.len() == 3));
+        assert!(matches!(
+            Default::solve(generate::random_k_cnf(20, 40, 3, 3)),
+            SatResult::Sat(_)
+        ));
+    }
+
+    #[test]
+    fn compressed_dimacs() {
+        let dir = std::env::temp_dir().join(format!("pror-dimacs-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let plain = dir.join("plain.cnf");
+        std::fs::write(&plain, "p cnf 2 2\n1 -2 0\n2 0\n").unwrap();
+        let formula = dimacs::read_file(plain.to_str().unwrap()).unwrap();
+        assert_eq!(formula, vec![vec![1, -2], vec![2]]);
+        #[cfg(not(feature = "gzip"))]
+        {
+            let gz = dir.join("plain.cnf.gz");
+            std::fs::copy(&plain, &gz).unwrap();
+            let err = dimacs::read_file(gz.to_str().unwrap()).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+        }
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn variable_too_large() {
+        let text = "p cnf 2147483648 2\n1 -2 0\n-2147483648 0\n";
+        let s = format!("{:?}", dimacs::read_string(text));
+        expect!["Err(VariableTooLarge(2147483648))"].assert_eq(&s);
+        let lines = "p inccnf\n1 0\na 2147483648 0\n";
+        assert!(dimacs::read_inccnf_string(lines).is_err());
+        let mut reader = dimacs::ClauseReader::new(text.as_bytes());
+        assert_eq!(reader.next().unwrap().unwrap(), vec![1, -2]);
+        let err = reader.next().unwrap().unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let mut solver = Default::new_from_vec(vec![vec![1]]);
+        let s = format!("{:?}", solver.try_add_clause([-1, 1 << 31]));
+        expect!["Err(VariableTooLarge(2147483648))"].assert_eq(&s);
+        assert!(matches!(solver.run(), SatResult::Sat(model) if model.len() == 1));
+    }
+
+    #[test]
+    fn verify_model() {
+        let formula = vec![vec![1, -2], vec![2, 3], vec![-1, -3]];
+        let output = "c some solver\ns SATISFIABLE\nv 1 -2\nv -3 0\n";
+        let model = dimacs::read_model(output).unwrap();
+        let s = format!("{:?} {:?}", model, first_unsatisfied(&formula, &model));
+        expect!["{1: true, 2: false, 3: false} Some(1)"].assert_eq(&s);
+        let model = dimacs::read_model("1 2 -3 0").unwrap();
+        assert_eq!(first_unsatisfied(&formula, &model), None);
+
+        let errors = ["v 1 -1 0", "v 1 x 0", "s UNSATISFIABLE"]
+            .map(|output| dimacs::read_model(output).unwrap_err());
+        let expect = expect![[r#"[Contradiction(1), BadToken("x"), NoModel("s UNSATISFIABLE")]"#]];
+        expect.assert_eq(&format!("{:?}", errors));
+    }
+
+    #[cfg(feature = "examples")]
+    #[test]
+    fn examples() {
+        let mut sizes = vec![];
+        for (name, text) in dimacs::examples() {
+            let header = text.lines().next().unwrap();
+            let formula = dimacs::read_example(name);
+            assert_eq!(dimacs::example(name), Some(text));
+            sizes.push((name, header, formula.len()));
+        }
+        let expect = expect![[r#"
+            [
+                (
+                    "sudoku",
+                    "p cnf 729 3270",
+                    3270,
+                ),
+                (
+                    "fail_eg",
+                    "p cnf 112 245",
+                    245,
+                ),
+                (
+                    "succ_eg",
+                    "p cnf 140 301",
+                    301,
+                ),
+                (
+                    "factor_1234321",
+                    "p cnf 1433 7585",
+                    7585,
+                ),
+                (
+                    "factor_1235321",
+                    "p cnf 1433 7585",
+                    7585,
+                ),
+                (
+                    "subsets_100",
+                    "p cnf 3357 15212",
+                    15212,
+                ),
+            ]
+        "#]];
+        expect.assert_debug_eq(&sizes);
+        assert_eq!(dimacs::example("nonexistent"), None);
+    }
+}
@@ -0,0 +1,42 @@
+#[cfg(test)]
+mod tests {
+    use expect_test::expect;
+    use pror::cdcl::*;
+    use pror::sat::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn render() {
+        let frames = Arc::new(Mutex::new(vec![]));
+        let hook_frames = Arc::clone(&frames);
+        let formula = vec![
+            vec![-1, 2],
+            vec![-1, 3],
+            vec![-2, -3, 4],
+            vec![-5, 6],
+            vec![7, 8],
+        ];
+        let mut solver = Default::new_from_vec(formula);
+        solver.set_event_hook(Box::new(move |_, view| {
+            hook_frames
+                .lock()
+                .unwrap()
+                .push(pror::tui::render(view, 40));
+        }));
+        // the last frame is drawn as -7 is decided, before it's on the trail
+        for lit in [1isize, 5, -7] {
+            while solver.reason(lit.unsigned_abs()).is_none() {
+                solver.step(Some(Literal::from(lit)));
+            }
+        }
+        let frames = frames.lock().unwrap();
+        expect![[r#"
+            level 3  assigned 6/8  conflicts 0  learned 0  restarts 0
+               0        |
+               1      1 |########################
+               2      5 |########
+               3        |
+            activity [@+++++++]"#]]
+        .assert_eq(frames.last().unwrap());
+    }
+}
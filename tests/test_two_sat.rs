@@ -0,0 +1,60 @@
+use pror::cdcl::Default;
+use pror::sat::*;
+use pror::two_sat;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use expect_test::expect;
+
+    #[test]
+    fn implication_chains() {
+        // 1 -> 2 -> 3 -> -1, so 1 is false
+        let formula = vec![vec![-1, 2], vec![-2, 3], vec![-3, -1], vec![1, 4]];
+        let s = format!("{:?}", two_sat::solve(&formula));
+        let expect = expect!["Some({1: false, 2: true, 3: true, 4: true})"];
+        expect.assert_eq(&s);
+        let mut unsat = formula.clone();
+        unsat.push(vec![-4]);
+        assert_eq!(two_sat::solve(&unsat), None);
+        assert_eq!(two_sat::solve(&[vec![]]), None);
+    }
+
+    #[test]
+    fn agrees_with_search() {
+        let mut two_sat_solves = 0;
+        for seed in 0..60 {
+            let formula = pror::generate::random_k_cnf(30, 20 + seed as usize, 2, seed);
+            let mut solver = Default::new_from_vec(formula.clone());
+            let fast = solver.run();
+            two_sat_solves += solver.stats().two_sat_solves;
+            let mut searching = Default::new_from_vec(formula.clone());
+            searching.set_fast_paths(false);
+            let slow = searching.run();
+            assert_eq!(
+                two_sat::solve(&formula).is_some(),
+                matches!(slow, SatResult::Sat(_)),
+                "seed {}",
+                seed
+            );
+            if let SatResult::Sat(model) = fast {
+                assert!(formula.iter().all(|clause| clause
+                    .iter()
+                    .any(|&lit| model[&lit.unsigned_abs()] == (lit > 0))));
+            }
+        }
+        assert_eq!(two_sat_solves, 60);
+    }
+
+    #[test]
+    fn proof_skips_fast_path() {
+        use pror::drup;
+        let formula = vec![vec![1, 2], vec![-1, 2], vec![1, -2], vec![-1, -2]];
+        let mut solver = Default::new_from_vec(formula.clone());
+        solver.start_proof();
+        assert!(matches!(solver.run(), SatResult::UnsatCore(_)));
+        assert_eq!(solver.stats().two_sat_solves, 0);
+        assert_eq!(drup::check(&formula, &solver.take_proof()), Ok(()));
+    }
+}